@@ -0,0 +1,62 @@
+//! Compares the overhead of running many tiny tests in parallel with
+//! different `--chunk-size` values. Run with `cargo bench`.
+
+use std::{env, path::Path, time::Instant};
+
+use libtest_mimic::{Arguments, Measurement, Trial};
+
+
+const NUM_TESTS: usize = 100_000;
+const ITERATIONS: u64 = 5;
+
+/// Runs `NUM_TESTS` empty tests with the given chunk size and returns how long
+/// that took in nanoseconds.
+fn run_tiny_tests(chunk_size: usize, logfile: &Path) -> u64 {
+    let mut args = Arguments::from_iter([
+        "tiny_tests",
+        "--format",
+        "terse",
+        "--test-threads",
+        "4",
+        "--chunk-size",
+        &chunk_size.to_string(),
+    ]);
+    args.logfile = Some(logfile.display().to_string());
+    let tests = (0..NUM_TESTS)
+        .map(|i| Trial::test(format!("tiny_{i}"), || Ok(())))
+        .collect();
+
+    let start = Instant::now();
+    let conclusion = libtest_mimic::run(&args, tests);
+    let elapsed = start.elapsed().as_nanos() as u64;
+    assert_eq!(conclusion.num_passed, NUM_TESTS as u64);
+    elapsed
+}
+
+fn main() {
+    let args = Arguments::from_args();
+
+    let benches = [1, 16, 256]
+        .into_iter()
+        .map(|chunk_size| {
+            Trial::bench(format!("chunk_size_{chunk_size}"), move |test_mode| {
+                let logfile = env::temp_dir().join(format!("libtest_mimic_tiny_{chunk_size}.txt"));
+                if test_mode {
+                    run_tiny_tests(chunk_size, &logfile);
+                    return Ok(None);
+                }
+
+                let times = (0..ITERATIONS)
+                    .map(|_| run_tiny_tests(chunk_size, &logfile))
+                    .collect::<Vec<_>>();
+                let avg = times.iter().sum::<u64>() / ITERATIONS;
+                let max = times.iter().max().unwrap();
+                let min = times.iter().min().unwrap();
+                let _ = std::fs::remove_file(&logfile);
+                Ok(Some(Measurement { avg, variance: max - min, ..Measurement::default() }))
+            })
+        })
+        .collect();
+
+    libtest_mimic::run(&args, benches).exit();
+}
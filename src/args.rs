@@ -1,4 +1,8 @@
-use clap::{Parser, ValueEnum};
+use std::time::Duration;
+
+use clap::{error::ErrorKind, CommandFactory, Parser, ValueEnum};
+
+use crate::FilterSet;
 
 /// Command line arguments.
 ///
@@ -26,6 +30,50 @@ pub struct Arguments {
     #[arg(long = "ignored", help = "Run ignored tests")]
     pub ignored: bool,
 
+    /// Only use ASCII characters in all output produced by the harness itself
+    /// (e.g. `...` instead of `…` when truncating test names). Test names and
+    /// failure messages are still printed as they are. This is useful for
+    /// consoles that cannot display Unicode, like legacy Windows consoles.
+    #[arg(long = "ascii", help = "Only use ASCII characters in output produced by the harness")]
+    pub ascii: bool,
+
+    /// Show an animated spinner next to the currently running test. Only has
+    /// an effect with the pretty format, when running tests sequentially
+    /// (`--test-threads 1`) and if stdout is an interactive terminal.
+    #[arg(
+        long = "spinner",
+        help = "Show a spinner next to the running test (sequential mode only)",
+    )]
+    pub spinner: bool,
+
+    /// Before running, interactively fuzzy-search the test names and select
+    /// which tests to run. Requires stdin to be a terminal.
+    #[arg(long = "pick", help = "Interactively select the tests to run via fuzzy search")]
+    pub pick: bool,
+
+    /// Run ignored and non-ignored tests, but failures of ignored tests are
+    /// only reported and do not make the run fail.
+    #[arg(
+        long = "run-ignored-soft",
+        help = "Run ignored tests, but don't let their failures fail the run",
+    )]
+    pub run_ignored_soft: bool,
+
+    /// If set, the names of all ignored tests are listed after the run.
+    #[arg(
+        long = "list-ignored-after",
+        help = "List the names of all ignored tests after the run",
+    )]
+    pub list_ignored_after: bool,
+
+    /// After the run, print one line per failed test to stderr, e.g.
+    /// `FAIL foo::bar: first line of the message`, regardless of `--format`.
+    #[arg(
+        long = "failure-digest",
+        help = "Print a one-line-per-failure digest to stderr after the run",
+    )]
+    pub failure_digest: bool,
+
     /// Run tests, but not benchmarks.
     #[arg(
         long = "test",
@@ -42,11 +90,52 @@ pub struct Arguments {
     #[arg(long = "list", help = "List all tests and benchmarks")]
     pub list: bool,
 
-    /// No-op, ignored (libtest-mimic always runs in no-capture mode)
-    #[arg(long = "nocapture", help = "No-op (libtest-mimic always runs in no-capture mode)")]
+    /// Only print the effective configuration (after resolving defaults and
+    /// the environment), e.g. the number of threads, the color decision and
+    /// all filters, then exit without running anything.
+    #[arg(
+        long = "print-config",
+        help = "Print the effective configuration and exit without running tests",
+    )]
+    pub print_config: bool,
+
+    /// Only check the list of tests (unique names and valid filters) and
+    /// print how many tests would run, without running any of them. Like
+    /// `cargo test --no-run`, this is useful to check that the harness is
+    /// wired up correctly.
+    #[arg(
+        long = "no-run",
+        help = "Print how many tests would run, without running any",
+    )]
+    pub no_run: bool,
+
+    /// If set, trials with the same name (including the kind) are renamed by
+    /// appending `#2`, `#3`, ... to the second, third, ... of them, instead
+    /// of exiting with an error (see [`validate_names`][crate::validate_names]).
+    #[arg(
+        long = "rename-duplicates",
+        help = "Append #2, #3, ... to duplicate test names instead of failing",
+    )]
+    pub rename_duplicates: bool,
+
+    /// Disables output capture, same as `--capture none`. Output is only
+    /// captured at all if supported by the build (see
+    /// [`Arguments::capture`]).
+    #[arg(long = "nocapture", help = "Don't capture stdout/stderr of tests")]
     pub nocapture: bool,
 
-    /// No-op, ignored. libtest-mimic does not currently capture stdout.
+    /// If set, panics in tests are printed to stderr by the default panic hook
+    /// as soon as they happen. Otherwise, the hook is replaced during the run
+    /// and the location of the panic is only shown in the failure message,
+    /// which avoids interleaved panic messages when tests run in parallel.
+    #[arg(
+        long = "show-panic-immediately",
+        help = "Print panics of tests to stderr as they happen (instead of only in the failure \
+                message)",
+    )]
+    pub show_panic_immediately: bool,
+
+    /// No-op, ignored. Captured output is only shown for failed tests.
     #[arg(long = "show-output")]
     pub show_output: bool,
 
@@ -61,6 +150,16 @@ pub struct Arguments {
     )]
     pub exact: bool,
 
+    /// If set, the filters and `--skip` patterns are interpreted as regular
+    /// expressions. They only have to match a part of the test name, unless
+    /// anchored with `^` and `$`. Requires the `regex` feature.
+    #[arg(
+        long = "filter-regex",
+        conflicts_with = "exact",
+        help = "Interpret filters and skip patterns as regular expressions",
+    )]
+    pub filter_regex: bool,
+
     /// If set, display only one character per test instead of one line.
     /// Especially useful for huge test suites.
     ///
@@ -74,15 +173,79 @@ pub struct Arguments {
     )]
     pub quiet: bool,
 
+    /// If set, the tests are executed in random order. The order is derived
+    /// from `shuffle_seed` or, if that's not set, from `seed`.
+    #[arg(long = "shuffle", help = "Run tests in random order")]
+    pub shuffle: bool,
+
+    /// If set, the tests are executed in random order (like with `--shuffle`)
+    /// determined by this seed. The seed used for shuffling is always printed
+    /// as `-- Test order shuffled with seed N --`, so passing that `N` here
+    /// runs the tests in the exact same order again.
+    #[arg(
+        long = "shuffle-seed",
+        value_name = "N",
+        help = "Run tests in random order, determined by the given seed",
+    )]
+    pub shuffle_seed: Option<u64>,
+
+    /// If set, the wall clock start and end time of each test is printed
+    /// (and included in the JSON output as `start_time`/`end_time`).
+    #[arg(
+        long = "timestamps",
+        help = "Print the start and end time (UTC, ISO 8601) of each test",
+    )]
+    pub timestamps: bool,
+
+    /// If set, the duration of each test is printed after its outcome, e.g.
+    /// `test foo ... ok (0.03s)` (and included in the JSON output as
+    /// `exec_time`, in seconds, like libtest does).
+    #[arg(long = "report-time", help = "Print the duration of each test")]
+    pub report_time: bool,
+
+    /// If set, the tests that are about to run are listed and the user has to
+    /// confirm (or narrow down via an additional filter) before they are
+    /// executed. Only has an effect if stdin is a terminal.
+    #[arg(
+        long = "confirm",
+        help = "List tests that are about to run and wait for confirmation (interactive only)",
+    )]
+    pub confirm: bool,
+
     // ============== OPTIONS =================================================
-    /// Number of threads used for parallel testing.
+    /// Number of threads used for parallel testing. If not set, the
+    /// `RUST_TEST_THREADS` environment variable is used, and if that's not set
+    /// either, the number of logical CPUs.
     #[arg(
         long = "test-threads",
-        help = "Number of threads used for running tests in parallel. If set to 1, \n\
-            all tests are run in the main thread.",
+        help = "Number of threads used for running tests in parallel (default: \n\
+            $RUST_TEST_THREADS or the number of CPUs). If set to 1, all tests are \n\
+            run in the main thread.",
     )]
     pub test_threads: Option<usize>,
 
+    /// Stack size in bytes of the worker threads that run tests in parallel.
+    /// Defaults to the default of `std::thread` (currently 2 MiB). With
+    /// `--test-threads 1`, tests run on the main thread and this has no
+    /// effect.
+    #[arg(
+        long = "thread-stack-size",
+        value_name = "BYTES",
+        help = "Stack size in bytes of the threads running tests in parallel",
+    )]
+    pub thread_stack_size: Option<usize>,
+
+    /// Number of tests run by each job of the thread pool. Larger chunks
+    /// reduce the scheduling overhead for many tiny tests. Outcomes are still
+    /// reported per test, but a run aborted by `--max-failures` only stops
+    /// between chunks. Defaults to 1.
+    #[arg(
+        long = "chunk-size",
+        value_name = "N",
+        help = "Number of tests run by each job of the thread pool (default: 1)",
+    )]
+    pub chunk_size: Option<usize>,
+
     /// Path of the logfile. If specified, everything will be written into the
     /// file instead of stdout.
     #[arg(
@@ -101,6 +264,17 @@ pub struct Arguments {
     )]
     pub skip: Vec<String>,
 
+    /// A list of test names. Tests named exactly like one of these (with or
+    /// without the kind) are skipped, independent of `--exact` and
+    /// `--filter-regex`.
+    #[arg(
+        long = "skip-exact",
+        value_name = "NAME",
+        help = "Skip tests named exactly NAME, independent of --exact (this flag can be used \
+                multiple times)",
+    )]
+    pub skip_exact: Vec<String>,
+
     /// Specifies whether or not to color the output.
     #[arg(
         long = "color",
@@ -117,22 +291,402 @@ pub struct Arguments {
     #[arg(
         long = "format",
         value_enum,
-        value_name = "pretty|terse|json",
+        value_name = "pretty|terse|json|markdown|junit|tap",
         help = "Configure formatting of output: \n\
             - pretty = Print verbose output\n\
             - terse = Display one character per test\n\
-            - json = Print json events\n",
+            - json = Print json events\n\
+            - markdown = Print a Markdown table of all tests after the run\n\
+            - junit = Print a JUnit XML report after the run\n\
+            - tap = Print TAP (Test Anything Protocol) output\n",
     )]
     pub format: Option<FormatSetting>,
 
+    /// Specifies which output streams of tests are captured. `--nocapture`
+    /// is the same as `--capture none` and takes precedence. See
+    /// [`Arguments::capture_setting`].
+    ///
+    /// Note: capturing relies on an unstable `std` function and thus only
+    /// works on nightly when compiled with `--cfg libtest_mimic_capture`
+    /// (e.g. via `RUSTFLAGS`); otherwise this has no effect apart from being
+    /// validated. Even then, output is only captured when tests run
+    /// sequentially (`--test-threads 1`). Captured output of failed tests is
+    /// printed in the failure block of the test.
+    ///
+    /// Only `both` and `none` are supported: stdout and stderr are redirected
+    /// by the same `std` function, so they can only be captured together.
+    #[arg(
+        long = "capture",
+        value_enum,
+        value_name = "both|none",
+        help = "Configure which output streams of tests are captured (needs nightly): \n\
+            - both = Capture stdout and stderr (default)\n\
+            - none = Don't capture anything (same as --nocapture)\n",
+    )]
+    pub capture: Option<CaptureSetting>,
+
+    /// Specifies how the progress of the run is shown.
+    #[arg(
+        long = "progress",
+        value_enum,
+        value_name = "auto|inplace|plain|off",
+        help = "Configure how progress is shown (only with pretty format): \n\
+            - auto = inplace if stdout is an interactive terminal, off otherwise (default)\n\
+            - inplace = Update a single status line in place\n\
+            - plain = Print a new line every 10% of tests\n\
+            - off = Don't show progress\n",
+    )]
+    pub progress: Option<ProgressSetting>,
+
+    /// Master seed for all randomness in this run. Currently, this only
+    /// controls the order of tests with `--shuffle`. If not set, a seed is
+    /// derived from the system clock. The effective seed is always printed
+    /// (except in JSON mode), so that a run can be reproduced.
+    #[arg(
+        long = "seed",
+        value_name = "N",
+        help = "Seed for all randomness (e.g. the order of tests with --shuffle)",
+    )]
+    pub seed: Option<u64>,
+
+    /// If set, the run is aborted with an error (before running any tests) if
+    /// the total number of tests and benchmarks is not exactly this number.
+    /// The number is checked *before* any filtering, so it does not depend
+    /// on the filter or `--skip`.
+    #[arg(
+        long = "expect-tests",
+        value_name = "N",
+        help = "Abort with an error if the total number of tests (before filtering) is not N",
+    )]
+    pub expect_tests: Option<usize>,
+
+    /// If set, the run is aborted once this many tests have failed: no new
+    /// tests are started, except for those marked via
+    /// [`Trial::with_always_run`][crate::Trial::with_always_run]. Tests that
+    /// are not run because of that are counted in
+    /// [`Conclusion::num_not_run`][crate::Conclusion::num_not_run].
+    #[arg(
+        long = "max-failures",
+        value_name = "N",
+        help = "Abort the run after N tests have failed",
+    )]
+    pub max_failures: Option<u64>,
+
+    /// Shorthand for `--max-failures 1`: the run is aborted after the first
+    /// failure. Tests that already run in parallel are still finished.
+    #[arg(
+        long = "fail-fast",
+        conflicts_with = "max_failures",
+        help = "Abort the run after the first failure (same as --max-failures 1)",
+    )]
+    pub fail_fast: bool,
+
+    /// Number of times a failed (or timed out) test is run again before it
+    /// is reported as failed. A test that passes on a retry is reported as
+    /// flaky and counted in
+    /// [`Conclusion::num_flaky`][crate::Conclusion::num_flaky]. Ignored tests
+    /// and benchmarks are never retried, and neither are tests whose runner
+    /// can only be called once: only tests created via
+    /// [`Trial::test_repeatable`][crate::Trial::test_repeatable] are retried.
+    #[arg(
+        long = "retries",
+        value_name = "N",
+        default_value_t = 0,
+        help = "Run failed tests again up to N times before reporting them as failed",
+    )]
+    pub retries: u32,
+
+    /// Number of times the whole (filtered) list of tests is run, e.g. to
+    /// shake out flaky tests. Each iteration is printed like a section of
+    /// [`run_sections`][crate::run_sections], followed by the totals of all
+    /// iterations. Tests whose runner can only be called once are only run
+    /// in the first iteration: only tests created via
+    /// [`Trial::test_repeatable`][crate::Trial::test_repeatable] are repeated.
+    /// `0` is treated like `1`. Ignored with `--list` and `--print-config`.
+    #[arg(
+        long = "repeat",
+        value_name = "N",
+        default_value_t = 1,
+        help = "Run the tests N times (only tests that can run repeatedly are repeated)",
+    )]
+    pub repeat: u32,
+
+    /// Maximum number of failures whose details (i.e. messages) are printed
+    /// after the run. If not set, all failures are shown.
+    #[arg(
+        long = "max-failures-shown",
+        value_name = "N",
+        help = "Print details of at most N failures after the run",
+    )]
+    pub max_failures_shown: Option<usize>,
+
+    /// If set, the given number of slowest tests are listed after the run.
+    /// Tests with equal duration are ordered by name.
+    #[arg(
+        long = "slowest",
+        value_name = "N",
+        help = "List the N slowest tests after the run",
+    )]
+    pub slowest: Option<usize>,
+
+    /// If set, only runs as many tests as (likely) fit into the given number
+    /// of seconds, based on the estimates set via
+    /// `Trial::with_estimated_duration`. Tests with small estimates are run
+    /// first, tests without estimate last. This is best-effort: the estimates
+    /// are not verified and running tests are never interrupted.
+    #[arg(
+        long = "time-budget",
+        value_name = "SECS",
+        help = "Only run as many tests as fit into SECS seconds (best-effort)",
+    )]
+    pub time_budget: Option<f64>,
+
+    /// If set, each test that does not finish within this time (in seconds)
+    /// is reported as failed with "test exceeded timeout of Ns". To enforce
+    /// this, every test runs on its own helper thread, which keeps running in
+    /// the background after a timeout (it cannot be stopped). The stack size
+    /// of that thread is set by `--thread-stack-size`. As the timed out test
+    /// would outlive its isolation, this cannot be combined with tests that
+    /// use `Trial::with_temp_cwd` or with `RunConfig::with_invariant`.
+    #[arg(
+        long = "timeout",
+        value_name = "SECS",
+        value_parser = parse_secs,
+        help = "Fail tests that take longer than SECS seconds",
+    )]
+    pub timeout: Option<Duration>,
+
+    /// Absolute deadline as milliseconds since the Unix epoch, e.g. passed
+    /// down by an orchestrator with its own deadline. Once the wall clock
+    /// passes it, no more tests are started (not even those marked "always
+    /// run") and the remaining tests are reported as not run. Running tests
+    /// are not interrupted.
+    #[arg(
+        long = "deadline-epoch",
+        value_name = "UNIX_MS",
+        help = "Don't start any tests after this time (milliseconds since the Unix epoch)",
+    )]
+    pub deadline_epoch: Option<u64>,
+
+    /// If set and some tests failed, each failed test is re-run afterwards in
+    /// a new process of the current binary, without capturing and with
+    /// `RUST_BACKTRACE=1`. The output is printed after the summary. This only
+    /// works if the binary creates the same tests on every invocation.
+    #[arg(
+        long = "bisect-verbose",
+        help = "Re-run failed tests in a new process with backtraces enabled and print their \
+            output",
+    )]
+    pub bisect_verbose: bool,
+
+    /// If set, benchmarks whose coefficient of variation (`variance / avg`
+    /// of their [`Measurement`][crate::Measurement]) exceeds this percentage
+    /// are listed as unstable after the run.
+    #[arg(
+        long = "bench-unstable-threshold",
+        value_name = "PCT",
+        help = "List benchmarks whose variance exceeds PCT percent of their average",
+    )]
+    pub bench_unstable_threshold: Option<f64>,
+
+    /// Number of times each benchmark is run with `--bench`. The
+    /// measurements of all runs are combined: the averages and variances are
+    /// averaged and the fastest and slowest run are reported as
+    /// [`Measurement::min`][crate::Measurement::min] and
+    /// [`Measurement::max`][crate::Measurement::max]. Only benchmarks created
+    /// via [`Trial::bench_repeatable`][crate::Trial::bench_repeatable] can be
+    /// run several times; all others are run once. `0` is treated like `1`.
+    #[arg(
+        long = "bench-iterations",
+        value_name = "N",
+        default_value_t = 1,
+        help = "Run each (repeatable) benchmark N times and report the spread of the runs",
+    )]
+    pub bench_iterations: u32,
+
+    /// Path of a file to which the outcome of every test is written after the
+    /// run. Several of these files can be merged with
+    /// [`merge_runs`][crate::merge_runs].
+    #[arg(
+        long = "results-file",
+        value_name = "PATH",
+        help = "Write the outcome of every test to the specified file after the run",
+    )]
+    pub results_file: Option<String>,
+
+    /// Metadata about the run (e.g. the commit or the CI build URL) that is
+    /// embedded in machine-readable reports: as `<properties>` of the test
+    /// suite with `--format junit` and as `metadata` object of the
+    /// `suite`/`started` event with `--format json`. Human-readable output
+    /// ignores it. Added after the metadata set via
+    /// [`RunConfig::with_run_metadata`][crate::RunConfig::with_run_metadata].
+    #[arg(
+        long = "run-meta",
+        value_name = "KEY=VALUE",
+        value_parser = parse_key_value,
+        help = "Attach metadata to JUnit and JSON reports (this flag can be used multiple times)",
+    )]
+    pub run_meta: Vec<(String, String)>,
+
+    /// Path of a file to which the outcome of every finished test is written
+    /// while tests run (at most once per second, and at the end). If the run
+    /// is interrupted, it can be continued with `--resume`. The file is
+    /// replaced atomically, so it is never left half-written.
+    #[arg(
+        long = "checkpoint",
+        value_name = "PATH",
+        help = "Continuously write the outcome of finished tests to the specified file",
+    )]
+    pub checkpoint: Option<String>,
+
+    /// Path of a file written by `--checkpoint` in a previous, interrupted
+    /// run. Tests listed in it are not run again; their previous outcomes
+    /// are reported and counted instead, as if they just ran. A missing file
+    /// is treated as empty, so the same path can be passed to `--checkpoint`
+    /// and `--resume` for every run.
+    ///
+    /// This assumes that the tests did not change since the interrupted run.
+    /// Entries of tests that don't exist anymore are ignored.
+    #[arg(
+        long = "resume",
+        value_name = "PATH",
+        help = "Skip tests that already finished according to the specified checkpoint file",
+    )]
+    pub resume: Option<String>,
+
+    /// Path of a TSV file (without header) to which the final state of every
+    /// test is written after the run. It contains one line per test
+    /// (including filtered out ones) in the order the tests were passed to
+    /// [`run`][crate::run], with these three tab-separated columns:
+    ///
+    /// 1. The test name (including the kind in brackets, if set).
+    /// 2. The status: `ok`, `failed`, `ignored`, `bench`, `cached`,
+    ///    `filtered` or `not-run` (if the run was aborted before the test was
+    ///    started).
+    /// 3. The duration of the test in whole milliseconds, or `-` if the test
+    ///    was not executed.
+    #[arg(
+        long = "manifest",
+        value_name = "PATH",
+        help = "Write the name, status and duration of every test as TSV to the specified file",
+    )]
+    pub manifest: Option<String>,
+
+    /// Path of a file listing the names of all tests that are expected to
+    /// exist, one per line. Names include the kind in brackets (if set), just
+    /// like the first column of the `--manifest` file; empty lines are
+    /// ignored. Before any test is run, all defined tests (before filtering)
+    /// are compared against this list and the names of added and removed
+    /// tests are printed. This guards against tests that are accidentally
+    /// lost in a refactoring.
+    #[arg(
+        long = "baseline-list",
+        value_name = "FILE",
+        help = "Compare the names of all tests against FILE (one name per line) and print \
+                added and removed tests",
+    )]
+    pub baseline_list: Option<String>,
+
+    /// If set, the application exits with an error (code 101) before running
+    /// any test if tests listed in `--baseline-list` were removed. Added tests
+    /// are not an error.
+    #[arg(
+        long = "deny-removed-tests",
+        requires = "baseline_list",
+        help = "Exit with an error if tests from the `--baseline-list` were removed",
+    )]
+    pub deny_removed_tests: bool,
+
+    /// If set, a timeline of all tests that ran is written to the given file
+    /// after the run, in the Chrome Trace Event format. It can be opened with
+    /// Perfetto (<https://ui.perfetto.dev>) or `chrome://tracing` to see how
+    /// well tests run in parallel. Each test is a span on the thread it ran on.
+    #[arg(
+        long = "profile",
+        value_name = "FILE",
+        help = "Write a timeline of all test runs (Chrome Trace Event JSON) to FILE",
+    )]
+    pub profile: Option<String>,
+
+    /// If set, the JSON events (see `--format json`) are additionally
+    /// streamed to the Unix domain socket at the given path, e.g. for a live
+    /// dashboard. The harness connects as client; if nothing is listening,
+    /// a note is printed and the run continues normally. If the listener
+    /// goes away or stops reading, the remaining events are dropped. Only
+    /// supported on Unix.
+    #[arg(
+        long = "event-socket",
+        value_name = "PATH",
+        help = "Stream JSON events to the Unix domain socket at PATH",
+    )]
+    pub event_socket: Option<String>,
+
+    /// Path of a file to which the names of all tests are written in the
+    /// order they were started. With multiple threads, this is the order in
+    /// which the worker threads picked up the tests, which is still useful
+    /// but cannot reproduce the exact interleaving.
+    #[arg(
+        long = "record-order",
+        value_name = "PATH",
+        help = "Write the order in which tests were started to the specified file",
+    )]
+    pub record_order: Option<String>,
+
+    /// Path of a file written via `record_order`. Tests are run in the order
+    /// listed in that file; tests not listed are run last. Implies running
+    /// all tests sequentially in the main thread.
+    #[arg(
+        long = "replay-order",
+        value_name = "PATH",
+        help = "Run tests sequentially in the order stored in the specified file (see \
+            --record-order)",
+    )]
+    pub replay_order: Option<String>,
+
+    /// Path of a cache file storing the fingerprints of passed tests. Tests
+    /// whose fingerprint (see [`Trial::with_fingerprint`][crate::Trial::with_fingerprint])
+    /// matches a previous pass are not run, but reported as "cached" (in
+    /// JSON as `"event": "ignored"` with `"cached": true`). The file is
+    /// created if it does not exist and updated after the run.
+    #[arg(
+        long = "skip-unchanged",
+        value_name = "PATH",
+        help = "Skip tests whose fingerprint matches a previous pass stored in the specified file",
+    )]
+    pub skip_unchanged: Option<String>,
+
+    /// Path of a file listing changed files, one path per line. Only tests
+    /// covering at least one of those files (see
+    /// [`Trial::with_source_files`][crate::Trial::with_source_files]) and
+    /// tests that do not declare any files are run.
+    #[arg(
+        long = "changed-files",
+        value_name = "PATH",
+        help = "Only run tests covering one of the files listed in the specified file",
+    )]
+    pub changed_files: Option<String>,
+
+    /// Specifies which numbers are included in the `test result:` line.
+    #[arg(
+        long = "summary-detail",
+        value_enum,
+        value_name = "minimal|normal|full",
+        help = "Configure which numbers are shown in the final summary line: \n\
+            - minimal = only passed and failed\n\
+            - normal = like the official test harness (default)\n\
+            - full = all numbers (e.g. also tests that were not run)\n",
+    )]
+    pub summary_detail: Option<SummaryDetail>,
+
     // ============== POSITIONAL VALUES =======================================
-    /// Filter string. Only tests which contain this string are run.
+    /// Filter strings. Only tests which contain any of these strings are
+    /// run. If empty, all tests are run.
     #[arg(
         value_name = "FILTER",
-        help = "The FILTER string is tested against the name of all tests, and only those tests \
-                whose names contain the filter are run.",
+        help = "The FILTER strings are tested against the name of all tests, and only those \
+                tests whose names contain any of the filters are run.",
     )]
-    pub filter: Option<String>,
+    pub filter: Vec<String>,
 }
 
 impl Arguments {
@@ -142,26 +696,91 @@ impl Arguments {
     /// the application exits. If help is requested (`-h` or `--help`), a help
     /// message is shown and the application exits, too.
     pub fn from_args() -> Self {
-        Parser::parse()
+        Self::validated(Parser::parse())
     }
 
     /// Like `from_args()`, but operates on an explicit iterator and not the
     /// global arguments. Note that the first element is the executable name!
+    /// See [`try_from_iter`][Self::try_from_iter] to handle errors yourself.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_iter<I>(iter: I) -> Self
     where
         Self: Sized,
         I: IntoIterator,
         I::Item: Into<std::ffi::OsString> + Clone,
     {
-        Parser::parse_from(iter)
+        Self::validated(Parser::parse_from(iter))
+    }
+
+    /// Like [`from_iter`][Self::from_iter], but returns the error instead of
+    /// exiting if the arguments are invalid. Note that requesting help (`-h`
+    /// or `--help`) or the version is also reported as error, with kind
+    /// `DisplayHelp` or `DisplayVersion`. The error prints the message (or
+    /// help text) and exits like `from_iter` would via
+    /// [`exit`][clap::Error::exit].
+    ///
+    /// ```
+    /// use libtest_mimic::Arguments;
+    ///
+    /// let args = Arguments::try_from_iter(["harness", "--exact", "foo"]).unwrap();
+    /// assert!(args.exact);
+    /// let err = Arguments::try_from_iter(["harness", "--no-such-flag"]).unwrap_err();
+    /// assert_eq!(err.kind(), clap::error::ErrorKind::UnknownArgument);
+    /// ```
+    pub fn try_from_iter<I>(iter: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<std::ffi::OsString> + Clone,
+    {
+        let args = <Self as Parser>::try_parse_from(iter)?;
+        args.validate()?;
+        Ok(args)
+    }
+
+    /// Exits with a CLI error if the filters are invalid (see `validate`).
+    fn validated(self) -> Self {
+        if let Err(e) = self.validate() {
+            e.exit();
+        }
+        self
+    }
+
+    /// Returns a CLI error if the filters are invalid, e.g. with
+    /// `--filter-regex` and a pattern that is not a valid regex.
+    fn validate(&self) -> Result<(), clap::Error> {
+        let error = |msg| Self::command().error(ErrorKind::ValueValidation, msg);
+        FilterSet::from_args(self).validate().map_err(error)
+    }
+
+    /// Returns which output streams should be captured, taking `--nocapture`
+    /// into account.
+    pub fn capture_setting(&self) -> CaptureSetting {
+        if self.nocapture {
+            CaptureSetting::None
+        } else {
+            self.capture.unwrap_or_default()
+        }
     }
 }
 
+/// Possible values for the `--capture` option. Capturing only stdout or only
+/// stderr is not supported, see [`Arguments::capture`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum CaptureSetting {
+    /// Capture both stdout and stderr. (default)
+    #[default]
+    Both,
+
+    /// Don't capture anything.
+    None,
+}
+
 /// Possible values for the `--color` option.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
 pub enum ColorSetting {
     /// Colorize output if stdout is a tty and tests are run on serially
     /// (default).
+    #[default]
     Auto,
 
     /// Always colorize output.
@@ -171,10 +790,21 @@ pub enum ColorSetting {
     Never,
 }
 
-impl Default for ColorSetting {
-    fn default() -> Self {
-        ColorSetting::Auto
-    }
+/// Possible values for the `--summary-detail` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SummaryDetail {
+    /// Only the number of passed and failed tests.
+    Minimal,
+
+    /// The same numbers as the official test harness. (default)
+    #[default]
+    Normal,
+
+    /// All numbers, including e.g. the number of tests that were not run,
+    /// and the percentage of passed tests (see [`Conclusion::pass_rate`]).
+    ///
+    /// [`Conclusion::pass_rate`]: crate::Conclusion::pass_rate
+    Full,
 }
 
 /// Possible values for the `-Z` option
@@ -184,9 +814,10 @@ pub enum UnstableFlags {
 }
 
 /// Possible values for the `--format` option.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
 pub enum FormatSetting {
     /// One line per test. Output for humans. (default)
+    #[default]
     Pretty,
 
     /// One character per test. Usefull for test suites with many tests.
@@ -194,11 +825,48 @@ pub enum FormatSetting {
 
     /// Json output
     Json,
+
+    /// Markdown document printed after the run, e.g. for PR comments.
+    Markdown,
+
+    /// JUnit XML document printed after the run, e.g. for CI dashboards.
+    /// Combine with `--logfile` to write it to a file.
+    Junit,
+
+    /// TAP (Test Anything Protocol) output, e.g. for test aggregators.
+    Tap,
+}
+
+/// Possible values for the `--progress` option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ProgressSetting {
+    /// `inplace` if the output is an interactive terminal, `off` otherwise.
+    /// (default)
+    #[default]
+    Auto,
+
+    /// A single status line that is updated in place (using `\r`).
+    Inplace,
+
+    /// A new line every time another 10% of all tests finished. Useful for CI
+    /// logs that cannot handle `\r`.
+    Plain,
+
+    /// No progress is shown.
+    Off,
+}
+
+/// Parses a non-negative number of seconds (e.g. `2` or `0.5`).
+fn parse_secs(s: &str) -> Result<Duration, String> {
+    let secs = s.parse::<f64>().map_err(|e| e.to_string())?;
+    Duration::try_from_secs_f64(secs).map_err(|_| format!("invalid number of seconds: {}", s))
 }
 
-impl Default for FormatSetting {
-    fn default() -> Self {
-        FormatSetting::Pretty
+/// Parses a `key=value` pair. The key must not be empty, the value may.
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.into(), value.into())),
+        _ => Err(format!("expected KEY=VALUE, got `{}`", s)),
     }
 }
 
@@ -211,4 +879,74 @@ mod tests {
         use clap::CommandFactory;
         Arguments::command().debug_assert();
     }
+
+    #[test]
+    fn capture_setting() {
+        let parse = |args: &[&str]| {
+            Arguments::try_parse_from(std::iter::once("test").chain(args.iter().copied()))
+                .unwrap()
+                .capture_setting()
+        };
+        assert_eq!(parse(&[]), CaptureSetting::Both);
+        assert_eq!(parse(&["--capture", "none"]), CaptureSetting::None);
+        assert_eq!(parse(&["--nocapture"]), CaptureSetting::None);
+        assert_eq!(parse(&["--nocapture", "--capture", "both"]), CaptureSetting::None);
+    }
+
+    #[test]
+    fn secs() {
+        assert_eq!(parse_secs("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_secs("0.5"), Ok(Duration::from_millis(500)));
+        assert!(parse_secs("-1").is_err());
+        assert!(parse_secs("abc").is_err());
+    }
+
+    #[test]
+    fn key_value() {
+        assert_eq!(parse_key_value("sha=abc"), Ok(("sha".into(), "abc".into())));
+        let url = parse_key_value("url=https://x/?a=b");
+        assert_eq!(url, Ok(("url".into(), "https://x/?a=b".into())));
+        assert_eq!(parse_key_value("empty="), Ok(("empty".into(), "".into())));
+        assert!(parse_key_value("=abc").is_err());
+        assert!(parse_key_value("abc").is_err());
+    }
+
+    #[test]
+    fn fail_fast_conflicts_with_max_failures() {
+        assert!(Arguments::try_parse_from(["test", "--fail-fast"]).is_ok());
+        assert!(Arguments::try_parse_from(["test", "--fail-fast", "--max-failures", "2"]).is_err());
+    }
+
+    #[test]
+    fn capture_scopes() {
+        for scope in ["stdout", "stderr"] {
+            let err = Arguments::try_from_iter(["test", "--capture", scope]).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidValue);
+        }
+        assert!(Arguments::try_from_iter(["test", "--capture", "both"]).is_ok());
+        assert!(Arguments::try_from_iter(["test", "--capture", "none"]).is_ok());
+    }
+
+    #[test]
+    fn try_from_iter() {
+        let args = Arguments::try_from_iter(["test", "foo", "--skip", "bar", "--test-threads", "2"])
+            .unwrap();
+        assert_eq!(args.filter, ["foo"]);
+        assert_eq!(args.skip, ["bar"]);
+        assert_eq!(args.test_threads, Some(2));
+
+        let err = Arguments::try_from_iter(["test", "--test-threads", "many"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        assert!(err.to_string().contains("'many'"), "{err}");
+        let err = Arguments::try_from_iter(["test", "--filter-regex", "("]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        if cfg!(feature = "regex") {
+            assert!(err.to_string().contains("invalid regex `(`"), "{err}");
+        } else {
+            assert!(err.to_string().contains("requires the `regex` feature"), "{err}");
+        }
+        let help = Arguments::try_from_iter(["test", "--help"]).unwrap_err();
+        assert_eq!(help.kind(), ErrorKind::DisplayHelp);
+        assert!(help.to_string().contains("--fail-fast"), "{help}");
+    }
 }
@@ -0,0 +1,66 @@
+//! Async tests (see `Trial::async_test`) and the executor driving them.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{self, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+use crate::{Failed, Outcome, Trial};
+
+/// The future of an async test, as passed to the executor set via
+/// [`RunConfig::with_block_on`][crate::RunConfig::with_block_on].
+pub type TestFuture = Pin<Box<dyn Future<Output = Result<(), Failed>> + Send>>;
+
+/// Drives the future of an async test to completion.
+pub(crate) type BlockOn = Arc<dyn Fn(TestFuture) -> Result<(), Failed> + Send + Sync>;
+
+/// Turns the future of each async trial into its runner, driven by
+/// `block_on` (or by the built-in executor if none was set). The runner then
+/// drives the future on whatever thread runs the test.
+pub(crate) fn bind_executor(tests: &mut [Trial], block_on: Option<&BlockOn>) {
+    for test in tests {
+        let Some(make_future) = test.future.take() else { continue };
+        let block_on = block_on.cloned();
+        test.runner = Box::new(move |_test_mode| {
+            let future = make_future();
+            let result = match block_on {
+                Some(block_on) => block_on(future),
+                None => self::block_on(future),
+            };
+            match result {
+                Ok(()) => Outcome::Passed,
+                Err(failed) => Outcome::Failed(failed),
+            }
+        });
+    }
+}
+
+/// A minimal executor that polls the future on the current thread and parks
+/// the thread while it is pending. This is sufficient for futures that do
+/// not depend on a runtime (e.g. the reactor of tokio).
+fn block_on(mut future: TestFuture) -> Result<(), Failed> {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = task::Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(result) => return result,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+/// Wakes the thread that is blocked in `block_on`.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
@@ -0,0 +1,41 @@
+//! Comparing the names of all tests against a committed list
+//! (`--baseline-list`). The format is documented at `Arguments::baseline_list`.
+
+use std::collections::HashSet;
+
+/// Tests that were added or removed compared to the baseline list.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct BaselineDiff {
+    /// Names of tests that are not in the baseline list, in the order the
+    /// tests were defined.
+    pub(crate) added: Vec<String>,
+
+    /// Names in the baseline list that no test has, in the order of the file.
+    pub(crate) removed: Vec<String>,
+}
+
+impl BaselineDiff {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compares the contents of a baseline list to the names of all tests
+/// (including the kind in brackets, if set). Lines are trimmed and empty lines
+/// are skipped.
+pub(crate) fn diff(baseline: &str, names: &[String]) -> BaselineDiff {
+    let baseline = baseline.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>();
+    let known = baseline.iter().copied().collect::<HashSet<_>>();
+    let current = names.iter().map(String::as_str).collect::<HashSet<_>>();
+
+    BaselineDiff {
+        added: names.iter().filter(|name| !known.contains(name.as_str())).cloned().collect(),
+        removed: baseline.into_iter()
+            .filter(|name| !current.contains(name))
+            .map(str::to_owned)
+            .collect(),
+    }
+}
@@ -0,0 +1,73 @@
+//! Skipping tests that passed in a previous run and did not change since
+//! (`--skip-unchanged`).
+//!
+//! Tests can declare a fingerprint via [`Trial::with_fingerprint`]. The cache
+//! file stores the fingerprint of every test that passed, one line per test:
+//! the fingerprint as hex number, a tab, and the test name (including the kind
+//! in brackets, if set). The file is read before and rewritten after the run.
+
+use std::{collections::BTreeMap, fs, io};
+
+use crate::{Outcome, TestInfo, Trial};
+
+/// The fingerprints of all tests that passed in previous runs.
+#[derive(Debug, Default)]
+pub(crate) struct FingerprintCache {
+    passed: BTreeMap<String, u64>,
+}
+
+impl FingerprintCache {
+    /// Reads the cache file. A missing file is treated as an empty cache.
+    pub(crate) fn read(path: &str) -> io::Result<Self> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+
+        let mut passed = BTreeMap::new();
+        for line in content.lines().filter(|l| !l.is_empty()) {
+            let invalid = || io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed line in cache file: {}", line),
+            );
+            let (fingerprint, name) = line.split_once('\t').ok_or_else(invalid)?;
+            let fingerprint = u64::from_str_radix(fingerprint, 16).map_err(|_| invalid())?;
+            passed.insert(name.to_owned(), fingerprint);
+        }
+        Ok(Self { passed })
+    }
+
+    /// Returns `true` if the given test has a fingerprint and passed with
+    /// that same fingerprint before.
+    pub(crate) fn is_cached(&self, test: &Trial) -> bool {
+        test.info.fingerprint.is_some_and(|fingerprint| {
+            self.passed.get(test.info.test_name_with_kind().as_ref()) == Some(&fingerprint)
+        })
+    }
+
+    /// Updates the cache with the outcome of a test. Only tests that passed
+    /// (or were cached) keep an entry; all others have to run again next time.
+    pub(crate) fn update(&mut self, test: &TestInfo, outcome: &Outcome) {
+        let Some(fingerprint) = test.fingerprint else { return };
+        let name = test.test_name_with_kind().into_owned();
+        match outcome {
+            Outcome::Passed | Outcome::Cached => {
+                self.passed.insert(name, fingerprint);
+            }
+            Outcome::Failed(_) | Outcome::TimedOut(_) => {
+                self.passed.remove(&name);
+            }
+            Outcome::Ignored | Outcome::Measured(_) => {}
+        }
+    }
+
+    /// Writes the cache file.
+    pub(crate) fn write(&self, path: &str) -> io::Result<()> {
+        let mut out = String::new();
+        for (name, fingerprint) in &self.passed {
+            out.push_str(&format!("{:016x}\t{}\n", fingerprint, name));
+        }
+        fs::write(path, out)
+    }
+}
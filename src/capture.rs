@@ -0,0 +1,66 @@
+//! Capturing the output of tests (see `Arguments::capture_setting`).
+//!
+//! This relies on `std::io::set_output_capture`, the same unstable function
+//! the official `libtest` uses. It is thus only available on nightly and when
+//! compiling with `--cfg libtest_mimic_capture` (e.g. via `RUSTFLAGS`).
+//! Otherwise, nothing is captured and all functions here are no-ops.
+
+#[cfg(libtest_mimic_capture)]
+type Sink = Option<std::sync::Arc<std::sync::Mutex<Vec<u8>>>>;
+#[cfg(not(libtest_mimic_capture))]
+type Sink = Option<std::convert::Infallible>;
+
+/// Runs `f` and returns its result together with everything it printed via
+/// `print!`, `eprint!` and friends on the current thread. Returns `None` as
+/// output if nothing was captured (because `enabled` is `false` or capturing
+/// is not supported).
+#[cfg(libtest_mimic_capture)]
+pub(crate) fn capture<R>(enabled: bool, f: impl FnOnce() -> R) -> (R, Option<String>) {
+    use std::sync::{Arc, Mutex};
+
+    if !enabled {
+        return (f(), None);
+    }
+
+    // Restores the previous sink even if `f` panics.
+    struct Restore(Sink);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            std::io::set_output_capture(self.0.take());
+        }
+    }
+
+    let buffer = Arc::new(Mutex::new(Vec::new()));
+    let restore = Restore(std::io::set_output_capture(Some(buffer.clone())));
+    let out = f();
+    drop(restore);
+    let bytes = std::mem::take(&mut *buffer.lock().unwrap_or_else(|e| e.into_inner()));
+    (out, Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+#[cfg(not(libtest_mimic_capture))]
+pub(crate) fn capture<R>(_enabled: bool, f: impl FnOnce() -> R) -> (R, Option<String>) {
+    (f(), None)
+}
+
+/// Returns the capture sink of the current thread, to be passed to
+/// [`set_sink`] in a helper thread running (parts of) a test. Spawned
+/// threads do not inherit the sink automatically.
+pub(crate) fn current_sink() -> Sink {
+    #[cfg(libtest_mimic_capture)]
+    {
+        let sink = std::io::set_output_capture(None);
+        std::io::set_output_capture(sink.clone());
+        sink
+    }
+    #[cfg(not(libtest_mimic_capture))]
+    None
+}
+
+/// Sets the capture sink of the current thread (see [`current_sink`]).
+pub(crate) fn set_sink(sink: Sink) {
+    #[cfg(libtest_mimic_capture)]
+    std::io::set_output_capture(sink);
+    #[cfg(not(libtest_mimic_capture))]
+    let _ = sink;
+}
@@ -0,0 +1,226 @@
+//! Resuming an interrupted run (`--checkpoint` and `--resume`).
+//!
+//! The checkpoint file stores the outcome of every finished test, one line
+//! per test with three tab-separated columns: the outcome (`passed`,
+//! `failed`, `ignored`, `measured`, `cached` or `timed-out`), its data (the
+//! failure message, the measurement or the timeout; empty otherwise) and the
+//! test name (including the kind in brackets, if set). Failure messages are
+//! prefixed with `>` (to distinguish an empty message from none) and have
+//! backslashes, tabs and newlines escaped.
+//!
+//! The file is written while tests run, at most once per `WRITE_INTERVAL` and
+//! once more at the end. It is always written to a temporary file first,
+//! which is then renamed, so that a killed run never leaves a truncated file.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    time::{Duration, Instant},
+};
+
+use crate::{Failed, Measurement, Outcome, TestInfo};
+
+/// Minimum time between two writes of the checkpoint file.
+const WRITE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Reads the outcomes stored in a checkpoint file. A missing file is treated
+/// as an empty checkpoint, so that the same file can be passed to
+/// `--checkpoint` and `--resume` from the first run on.
+pub(crate) fn read(path: &str) -> io::Result<HashMap<String, Outcome>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e),
+    };
+
+    content.lines()
+        .filter(|l| !l.is_empty())
+        .map(|line| parse_line(line).ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("malformed line in checkpoint file: {}", line),
+        )))
+        .collect()
+}
+
+fn parse_line(line: &str) -> Option<(String, Outcome)> {
+    let mut parts = line.splitn(3, '\t');
+    let (tag, data, name) = (parts.next()?, parts.next()?, parts.next()?);
+    let outcome = match tag {
+        "passed" => Outcome::Passed,
+        "failed" => Outcome::Failed(Failed { msg: data.strip_prefix('>').map(unescape) }),
+        "ignored" => Outcome::Ignored,
+        "cached" => Outcome::Cached,
+        "measured" => {
+            let mut parts = data.splitn(6, ',');
+            let (avg, variance) = (parts.next()?, parts.next()?);
+            let mut optional = || parts.next()
+                .filter(|part| !part.is_empty())
+                .map(|part| part.parse())
+                .transpose()
+                .ok();
+            let (bytes, min, max) = (optional()?, optional()?, optional()?);
+            Outcome::Measured(Measurement {
+                avg: avg.parse().ok()?,
+                variance: variance.parse().ok()?,
+                bytes,
+                min,
+                max,
+                unit: parts.next().map(unescape),
+            })
+        }
+        "timed-out" => Outcome::TimedOut(Duration::from_millis(data.parse().ok()?)),
+        _ => return None,
+    };
+    Some((name.to_owned(), outcome))
+}
+
+/// Collects the outcomes of finished tests and periodically writes them to
+/// the checkpoint file.
+#[derive(Debug)]
+pub(crate) struct Checkpoint {
+    path: String,
+    lines: String,
+    last_write: Option<Instant>,
+    /// The first error while writing during the run, returned by `write`.
+    error: Option<io::Error>,
+}
+
+impl Checkpoint {
+    pub(crate) fn new(path: String) -> Self {
+        Self { path, lines: String::new(), last_write: None, error: None }
+    }
+
+    /// Adds the outcome of a finished test and writes the file if the last
+    /// write was long enough ago. If writing fails, the run is not aborted:
+    /// the file is not written again and the error is returned by `write`.
+    pub(crate) fn record(&mut self, test: &TestInfo, outcome: &Outcome) {
+        let (tag, data) = match outcome {
+            Outcome::Passed => ("passed", String::new()),
+            Outcome::Failed(Failed { msg: None }) => ("failed", String::new()),
+            Outcome::Failed(Failed { msg: Some(msg) }) => ("failed", format!(">{}", escape(msg))),
+            Outcome::Ignored => ("ignored", String::new()),
+            Outcome::Cached => ("cached", String::new()),
+            Outcome::Measured(Measurement {
+                avg, variance, unit: None, bytes: None, min: None, max: None,
+            }) => {
+                ("measured", format!("{},{}", avg, variance))
+            }
+            Outcome::Measured(Measurement { avg, variance, unit, bytes, min, max }) => {
+                let optional = |value: &Option<u64>| {
+                    value.map_or(String::new(), |value| value.to_string())
+                };
+                let unit = unit.as_deref()
+                    .map_or(String::new(), |unit| format!(",{}", escape(unit)));
+                let (bytes, min, max) = (optional(bytes), optional(min), optional(max));
+                ("measured", format!("{},{},{},{},{}{}", avg, variance, bytes, min, max, unit))
+            }
+            Outcome::TimedOut(timeout) => ("timed-out", timeout.as_millis().to_string()),
+        };
+        self.lines.push_str(&format!("{}\t{}\t{}\n", tag, data, test.test_name_with_kind()));
+
+        let due = self.last_write.map_or(true, |last| last.elapsed() >= WRITE_INTERVAL);
+        if due && self.error.is_none() {
+            self.error = self.write_file().err();
+        }
+    }
+
+    /// Writes all outcomes recorded so far, or returns the error of an
+    /// earlier write.
+    pub(crate) fn write(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => self.write_file(),
+        }
+    }
+
+    /// Writes the file via a temporary file.
+    fn write_file(&mut self) -> io::Result<()> {
+        let tmp_path = format!("{}.tmp", self.path);
+        fs::write(&tmp_path, &self.lines)?;
+        fs::rename(&tmp_path, &self.path)?;
+        self.last_write = Some(Instant::now());
+        Ok(())
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => out.push('\t'),
+            Some('n') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_roundtrip() {
+        for s in ["", "plain", "a\tb\nc", "back\\slash\\", "\\n is not a newline"] {
+            assert_eq!(unescape(&escape(s)), s);
+            assert!(!escape(s).contains(['\t', '\n']));
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_line("passed\t\tfoo"), Some(("foo".into(), Outcome::Passed)));
+        assert_eq!(
+            parse_line("failed\t>a\\nb\t[kind] x\ty"),
+            Some(("[kind] x\ty".into(), Outcome::Failed("a\nb".into()))),
+        );
+        assert_eq!(
+            parse_line("failed\t\tfoo"),
+            Some(("foo".into(), Outcome::Failed(Failed { msg: None }))),
+        );
+        assert_eq!(
+            parse_line("measured\t12,3\tb"),
+            Some(("b".into(), Outcome::Measured(Measurement {
+                avg: 12,
+                variance: 3,
+                ..Measurement::default()
+            }))),
+        );
+        assert_eq!(
+            parse_line("timed-out\t1500\tt"),
+            Some(("t".into(), Outcome::TimedOut(Duration::from_millis(1500)))),
+        );
+        assert_eq!(
+            parse_line("measured\t12,3,4096,10,15,cy\\tcles\tb"),
+            Some(("b".into(), Outcome::Measured(Measurement {
+                avg: 12,
+                variance: 3,
+                bytes: Some(4096),
+                unit: Some("cy\tcles".into()),
+                min: Some(10),
+                max: Some(15),
+            }))),
+        );
+        assert_eq!(
+            parse_line("measured\t12,3,,,,cycles\tb"),
+            Some(("b".into(), Outcome::Measured(Measurement {
+                avg: 12,
+                variance: 3,
+                unit: Some("cycles".into()),
+                ..Measurement::default()
+            }))),
+        );
+        assert_eq!(parse_line("exploded\t\tfoo"), None);
+        assert_eq!(parse_line("passed foo"), None);
+    }
+}
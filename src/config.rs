@@ -0,0 +1,507 @@
+//! Configuration of a run that cannot be expressed via CLI arguments.
+
+use std::{fmt, io, sync::{atomic::AtomicBool, Arc, Mutex}};
+
+use termcolor::Color;
+
+use crate::{async_test::BlockOn, FilterSet, Outcome, TestInfo};
+
+/// Additional configuration for [`run_with_config`][crate::run_with_config].
+///
+/// Everything in here is set programmatically by the harness author, as
+/// opposed to [`Arguments`][crate::Arguments] which are usually set by the
+/// user via CLI. The default configuration results in exactly the same
+/// behavior as [`run`][crate::run].
+#[derive(Clone, Default)]
+pub struct RunConfig {
+    pub(crate) theme: Theme,
+    pub(crate) hooks: TestHooks,
+    pub(crate) thread_hooks: ThreadHooks,
+    pub(crate) thread_stack_size: Option<usize>,
+    pub(crate) invariants: Vec<(String, Invariant)>,
+    pub(crate) failure_header: Option<String>,
+    pub(crate) compact_failures: bool,
+    pub(crate) status_text: Option<StatusText>,
+    pub(crate) run_metadata: Vec<(String, String)>,
+    pub(crate) filter: FilterSet,
+    pub(crate) block_on: Option<BlockOn>,
+    pub(crate) interrupt: Vec<Arc<AtomicBool>>,
+    pub(crate) writer: Option<Writer>,
+    pub(crate) on_result: Option<ResultHook>,
+    pub(crate) prefiltered: bool,
+    #[cfg(feature = "sigint")]
+    pub(crate) handle_sigint: bool,
+}
+
+impl fmt::Debug for RunConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("RunConfig");
+        s.field("theme", &self.theme)
+            .field("before_all", &self.hooks.before_all.as_ref().map(|_| "<hook>"))
+            .field("after_all", &self.hooks.after_all.as_ref().map(|_| "<hook>"))
+            .field("before_each", &self.hooks.before_each.as_ref().map(|_| "<hook>"))
+            .field("after_each", &self.hooks.after_each.as_ref().map(|_| "<hook>"))
+            .field("on_test_begin", &self.hooks.begin.as_ref().map(|_| "<hook>"))
+            .field("on_test_end", &self.hooks.end.as_ref().map(|_| "<hook>"))
+            .field("thread_init", &self.thread_hooks.init.as_ref().map(|_| "<hook>"))
+            .field("thread_teardown", &self.thread_hooks.teardown.as_ref().map(|_| "<hook>"))
+            .field("thread_stack_size", &self.thread_stack_size)
+            .field("invariants", &self.invariants.iter().map(|(name, _)| name).collect::<Vec<_>>())
+            .field("failure_header", &self.failure_header)
+            .field("compact_failures", &self.compact_failures)
+            .field("status_text", &self.status_text.as_ref().map(|_| "<hook>"))
+            .field("run_metadata", &self.run_metadata)
+            .field("filter", &self.filter)
+            .field("block_on", &self.block_on.as_ref().map(|_| "<hook>"))
+            .field("interrupt", &self.interrupt)
+            .field("writer", &self.writer.as_ref().map(|_| "<writer>"))
+            .field("on_result", &self.on_result.as_ref().map(|_| "<hook>"))
+            .field("prefiltered", &self.prefiltered);
+        #[cfg(feature = "sigint")]
+        s.field("handle_sigint", &self.handle_sigint);
+        s.finish()
+    }
+}
+
+impl RunConfig {
+    /// Sets the colors used for printing outcomes. (Default:
+    /// [`Theme::default()`])
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Sets the header of the section listing failed tests after the run.
+    /// The section for failures of ignored tests (see `--run-ignored-soft`)
+    /// is prefixed with "ignored ". (Default: `failures`)
+    pub fn with_failure_section_header(mut self, header: impl Into<String>) -> Self {
+        self.failure_header = Some(header.into());
+        self
+    }
+
+    /// If `true`, each failure is printed as a single `name: message` line
+    /// (multi-line messages are joined with `; `) instead of a
+    /// `---- name ----` block followed by a list of all names.
+    /// (Default: `false`, like cargo)
+    pub fn with_compact_failures(mut self, compact: bool) -> Self {
+        self.compact_failures = compact;
+        self
+    }
+
+    /// Sets the executor driving the futures of async tests (see
+    /// [`Trial::async_test`][crate::Trial::async_test]) to completion, e.g.
+    /// the `block_on` of a tokio runtime. It is called on the thread running
+    /// the test, possibly on several threads at the same time. (Default: a
+    /// minimal built-in executor that does not support runtime-dependent
+    /// futures). Requires the `async` cargo feature.
+    ///
+    /// ```
+    /// use libtest_mimic::RunConfig;
+    ///
+    /// # fn my_block_on<F: std::future::Future>(f: F) -> F::Output { unimplemented!() }
+    /// let config = RunConfig::default().with_block_on(|future| my_block_on(future));
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn with_block_on(
+        mut self,
+        block_on: impl Fn(crate::TestFuture) -> Result<(), crate::Failed> + Send + Sync + 'static,
+    ) -> Self {
+        self.block_on = Some(Arc::new(block_on));
+        self
+    }
+
+    /// Sets the function that returns the status token printed for an outcome
+    /// in pretty output, e.g. to add emoji prefixes like `✅ ok`. The text is
+    /// printed verbatim, colored according to the theme (if coloring is
+    /// enabled). It is also used for the `test result: ...` summary line,
+    /// which passes `Outcome::Passed` or a failure without message.
+    /// (Default: like cargo, i.e. `ok`, `FAILED`, `ignored`, `bench` and
+    /// `cached`)
+    ///
+    /// The measurements of benchmarks are still printed after the token.
+    /// Soft failures (see `--run-ignored-soft`) are always printed as
+    /// `ignored (failed)`.
+    pub fn with_status_text(
+        mut self,
+        f: impl Fn(&Outcome) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.status_text = Some(Arc::new(f));
+        self
+    }
+
+    /// Adds metadata about the run (e.g. the commit SHA, branch or CI build
+    /// URL) that is embedded in machine-readable reports, so that they
+    /// describe their provenance: as `<property>` of the test suite with
+    /// `--format junit` (and Bazel's `XML_OUTPUT_FILE`), and in the
+    /// `metadata` object of the `suite`/`started` event with `--format json`.
+    /// Human-readable output ignores it. Entries passed via `--run-meta` are
+    /// added after these.
+    pub fn with_run_metadata(
+        mut self,
+        metadata: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Self {
+        self.run_metadata.extend(metadata.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Sets a hook that is called once before the first test runs, e.g. to
+    /// set up a fixture shared by all tests. It is not called with `--list`.
+    /// (Default: no-op)
+    ///
+    /// As the hook can only be called once, it is only called by the first
+    /// run this config (or a clone of it) is used for.
+    pub fn with_before_all(mut self, hook: impl FnOnce() + Send + 'static) -> Self {
+        self.hooks.before_all = Some(Arc::new(Mutex::new(Some(Box::new(hook)))));
+        self
+    }
+
+    /// Sets a hook that is called once after all tests finished, before the
+    /// failures and the summary are printed, e.g. to tear down what
+    /// [`with_before_all`][Self::with_before_all] set up. It is called even if
+    /// tests failed or panicked, or the run was aborted (e.g. due to
+    /// `--max-failures`). Like `with_before_all`, it is only called by the
+    /// first run. (Default: no-op)
+    pub fn with_after_all(mut self, hook: impl FnOnce() + Send + 'static) -> Self {
+        self.hooks.after_all = Some(Arc::new(Mutex::new(Some(Box::new(hook)))));
+        self
+    }
+
+    /// Sets a hook that is called with the info of each test right before
+    /// its runner is called, on the thread that runs the test. Ignored and
+    /// cached tests are not run, so the hook is not called for them.
+    /// (Default: no-op)
+    ///
+    /// If the hook panics, the test is not run and fails with the panic
+    /// message. Likewise, a panic in the hook of
+    /// [`with_after_each`][Self::with_after_each] fails the test that just
+    /// ran.
+    pub fn with_before_each(mut self, hook: impl Fn(&TestInfo) + Send + Sync + 'static) -> Self {
+        self.hooks.before_each = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets a hook that is called with the info of each test right after its
+    /// runner returned (or panicked). See
+    /// [`with_before_each`][Self::with_before_each]. (Default: no-op)
+    pub fn with_after_each(mut self, hook: impl Fn(&TestInfo) + Send + Sync + 'static) -> Self {
+        self.hooks.after_each = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets a hook that is called with the name of each test (including the
+    /// kind in brackets, if set) right before its runner is called.
+    /// (Default: no-op)
+    ///
+    /// The hook is called on the thread that runs the test. Together with
+    /// [`with_on_test_end`][Self::with_on_test_end], this can be used to
+    /// segment data per test, e.g. by resetting coverage counters. Note that
+    /// real per-test coverage also needs support by the coverage tooling,
+    /// which is outside of this crate. It is most accurate with
+    /// `--test-threads 1`, as otherwise tests run concurrently.
+    pub fn with_on_test_begin(mut self, hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.hooks.begin = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets a hook that is called with the name of each test right after its
+    /// runner returned (or panicked). See
+    /// [`with_on_test_begin`][Self::with_on_test_begin]. (Default: no-op)
+    pub fn with_on_test_end(mut self, hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.hooks.end = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets a hook that is called with the outcome of each test and
+    /// benchmark as soon as it finished, e.g. to stream results to a live
+    /// dashboard instead of waiting for the run to finish. Tests that are not
+    /// run (filtered out or after the run was aborted) are not reported.
+    /// (Default: no-op)
+    ///
+    /// The hook is always called from the thread that started the run (also
+    /// when tests run in a thread pool), one call after the other.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use libtest_mimic::{Arguments, RunConfig, Trial};
+    ///
+    /// let finished = Arc::new(Mutex::new(Vec::new()));
+    /// let log = Arc::clone(&finished);
+    /// let config = RunConfig::default().with_on_result(move |info, outcome| {
+    ///     log.lock().unwrap().push((info.name().to_owned(), outcome.clone()));
+    /// });
+    /// let tests = vec![Trial::test("foo", || Ok(()))];
+    /// let _ = libtest_mimic::run_with_config(&Arguments::default(), tests, config);
+    /// assert_eq!(finished.lock().unwrap().len(), 1);
+    /// ```
+    pub fn with_on_result(
+        mut self,
+        hook: impl Fn(&TestInfo, &Outcome) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_result = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets a hook that is called once on each worker thread of the pool
+    /// before it runs any test, e.g. to set up thread-local resources like a
+    /// connection pool. (Default: no-op)
+    ///
+    /// The hook is called on all worker threads (`--test-threads` many) right
+    /// after the pool is started, even on workers that end up running no
+    /// test. If tests run sequentially (e.g. with `--test-threads 1`), the
+    /// hook is called once on the main thread instead. Note that with
+    /// `--timeout`, each test runs on its own thread, so thread-local state
+    /// set up by this hook is not visible to the tests.
+    pub fn with_thread_init(mut self, hook: impl Fn() + Send + Sync + 'static) -> Self {
+        self.thread_hooks.init = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets a hook that is called once on each worker thread after all tests
+    /// finished, e.g. to tear down what
+    /// [`with_thread_init`][Self::with_thread_init] set up. All calls have
+    /// returned by the time the final summary is printed. If tests run
+    /// sequentially, the hook is called once on the main thread.
+    /// (Default: no-op)
+    pub fn with_thread_teardown(mut self, hook: impl Fn() + Send + Sync + 'static) -> Self {
+        self.thread_hooks.teardown = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets a flag that interrupts the run gracefully once it is set to
+    /// `true`, e.g. from a Ctrl-C handler: no new tests are started, the
+    /// running tests are waited for and the summary of everything that
+    /// finished is printed as usual. The remaining tests are counted in
+    /// [`Conclusion::num_not_run`][crate::Conclusion::num_not_run] and
+    /// [`Conclusion::interrupted`][crate::Conclusion::interrupted] is set, so
+    /// that the run exits with code 130. Can be called several times, the run
+    /// is interrupted as soon as any of the flags is set.
+    ///
+    /// Unless [`with_handle_sigint`][Self::with_handle_sigint] is used (which
+    /// requires the `sigint` cargo feature), this crate does not install a
+    /// signal handler itself. Set the flag from your own, e.g. with the
+    /// `ctrlc` crate:
+    ///
+    /// ```
+    /// use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+    /// use libtest_mimic::RunConfig;
+    ///
+    /// let interrupt = Arc::new(AtomicBool::new(false));
+    /// let flag = Arc::clone(&interrupt);
+    /// // ctrlc::set_handler(move || flag.store(true, Ordering::SeqCst)).unwrap();
+    /// # flag.store(false, Ordering::SeqCst);
+    /// let config = RunConfig::default().with_interrupt_flag(interrupt);
+    /// ```
+    pub fn with_interrupt_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.interrupt.push(flag);
+        self
+    }
+
+    /// If `true`, `SIGINT` (Ctrl-C) interrupts the run gracefully, like a flag
+    /// set via [`with_interrupt_flag`][Self::with_interrupt_flag]: no new
+    /// tests are started and the summary is printed. A second `SIGINT`
+    /// terminates the process right away. (Default: `false`) Requires the
+    /// `sigint` cargo feature.
+    ///
+    /// The handler is only active during the run. Afterwards, `SIGINT`
+    /// terminates the process again, like its default disposition. A
+    /// handler installed before (e.g. with the `ctrlc` crate) is still
+    /// called for each `SIGINT`, but if `SIGINT` was ignored before, it is
+    /// not ignored anymore after the run.
+    #[cfg(feature = "sigint")]
+    pub fn with_handle_sigint(mut self, handle_sigint: bool) -> Self {
+        self.handle_sigint = handle_sigint;
+        self
+    }
+
+    /// Writes all output to `out` instead of stdout (or the `--logfile`).
+    /// Useful to snapshot-test your own harness or to show the output
+    /// somewhere else, e.g. in a GUI. Colors are only written (as ANSI escape
+    /// codes) with `--color always`. (Default: stdout)
+    ///
+    /// The writer is shared, so that it can be read after the run:
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use libtest_mimic::{Arguments, RunConfig, Trial};
+    ///
+    /// let out = Arc::new(Mutex::new(Vec::new()));
+    /// let config = RunConfig::default().with_writer(Arc::clone(&out));
+    /// let tests = vec![Trial::test("foo", || Ok(()))];
+    /// let conclusion = libtest_mimic::run_with_config(&Arguments::default(), tests, config);
+    /// assert_eq!(conclusion.num_passed, 1);
+    /// let out = String::from_utf8(out.lock().unwrap().clone()).unwrap();
+    /// assert!(out.contains("test foo ... ok"));
+    /// ```
+    pub fn with_writer<W: io::Write + Send + 'static>(mut self, out: Arc<Mutex<W>>) -> Self {
+        self.writer = Some(out);
+        self
+    }
+
+    /// Sets the stack size in bytes of the worker threads that run tests in
+    /// parallel. `--thread-stack-size` takes precedence over this.
+    /// (Default: the default of `std::thread`, currently 2 MiB)
+    ///
+    /// With `--test-threads 1`, tests run on the main thread, so its stack
+    /// size applies instead.
+    pub fn with_thread_stack_size(mut self, bytes: usize) -> Self {
+        self.thread_stack_size = Some(bytes);
+        self
+    }
+
+    /// Adds a predicate that decides which trials are run, in addition to the
+    /// filters given via CLI arguments. Only trials for which all predicates
+    /// return `true` are run, the others count as filtered out. See
+    /// [`FilterSet`] for how this is combined with the other filters.
+    pub fn with_filter_predicate(
+        mut self,
+        predicate: impl Fn(&TestInfo) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.filter = self.filter.with_predicate(predicate);
+        self
+    }
+
+    /// If `true`, the given tests are not filtered by name again. Useful if
+    /// the caller already selected the tests itself (e.g. via
+    /// [`list_tests`][crate::list_tests] and a [`FilterSet`]), as filtering
+    /// twice can lead to confusing results. (Default: `false`)
+    ///
+    /// Only the name filters are ignored then: the filter patterns, `--skip`,
+    /// `--skip-exact`, `--exact` and `--filter-regex`. The given tests are
+    /// still filtered (and counted as filtered out) by:
+    /// - `--ignored`, `--test` and `--bench`,
+    /// - `--changed-files`,
+    /// - the predicates of [`with_filter_predicate`][Self::with_filter_predicate],
+    /// - the selection of `--pick` and `--confirm`.
+    ///
+    /// All other arguments are honored as usual, e.g. `--list`, the output
+    /// format, `--test-threads`, `--include-ignored` and the ones that do not
+    /// run some of the tests for other reasons (like `--max-failures`,
+    /// `--time-budget` or `--skip-unchanged`).
+    pub fn with_prefiltered(mut self, prefiltered: bool) -> Self {
+        self.prefiltered = prefiltered;
+        self
+    }
+
+    /// Adds an invariant that is checked after each test that ran. If `check`
+    /// returns `false`, the test that just ran is marked as failed with the
+    /// message "invariant '{name}' violated". This is useful to detect tests
+    /// that leak global state.
+    ///
+    /// As global state cannot be attributed to a single test if several run
+    /// concurrently, all tests are run in the main thread (like with
+    /// `--test-threads 1`) if any invariant is set. For the same reason, the
+    /// run is rejected with an error if `--timeout` is passed as well: a
+    /// timed out test keeps running in the background.
+    pub fn with_invariant(
+        mut self,
+        name: impl Into<String>,
+        check: impl Fn() -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.invariants.push((name.into(), Arc::new(check)));
+        self
+    }
+}
+
+type Hook = Arc<dyn Fn(&str) + Send + Sync>;
+type InfoHook = Arc<dyn Fn(&TestInfo) + Send + Sync>;
+type OnceHook = Arc<Mutex<Option<Box<dyn FnOnce() + Send>>>>;
+pub(crate) type ThreadHook = Arc<dyn Fn() + Send + Sync>;
+pub(crate) type StatusText = Arc<dyn Fn(&Outcome) -> String + Send + Sync>;
+pub(crate) type Invariant = Arc<dyn Fn() -> bool + Send + Sync>;
+pub(crate) type Writer = Arc<Mutex<dyn io::Write + Send>>;
+pub(crate) type ResultHook = Arc<dyn Fn(&TestInfo, &Outcome) + Send + Sync>;
+
+/// The hooks called around the whole run (see `RunConfig::with_before_all`)
+/// and around each runner (see `RunConfig::with_before_each` and
+/// `RunConfig::with_on_test_begin`).
+#[derive(Clone, Default)]
+pub(crate) struct TestHooks {
+    before_all: Option<OnceHook>,
+    after_all: Option<OnceHook>,
+    before_each: Option<InfoHook>,
+    after_each: Option<InfoHook>,
+    begin: Option<Hook>,
+    end: Option<Hook>,
+}
+
+impl TestHooks {
+    /// Calls `f`, surrounded by the hooks.
+    pub(crate) fn around<T>(&self, info: &TestInfo, f: impl FnOnce() -> T) -> T {
+        let name = info.test_name_with_kind();
+        if let Some(before_each) = &self.before_each {
+            before_each(info);
+        }
+        if let Some(begin) = &self.begin {
+            begin(&name);
+        }
+        let out = f();
+        if let Some(end) = &self.end {
+            end(&name);
+        }
+        if let Some(after_each) = &self.after_each {
+            after_each(info);
+        }
+        out
+    }
+
+    /// Calls the before-all hook (if it was not called before) and returns a
+    /// guard that calls the after-all hook when dropped, which also happens
+    /// when unwinding.
+    pub(crate) fn around_all(&self) -> AfterAll {
+        if let Some(hook) = take_once(&self.before_all) {
+            hook();
+        }
+        AfterAll(take_once(&self.after_all))
+    }
+}
+
+fn take_once(hook: &Option<OnceHook>) -> Option<Box<dyn FnOnce() + Send>> {
+    hook.as_ref()?.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// Calls the after-all hook when dropped, see `TestHooks::around_all`.
+pub(crate) struct AfterAll(Option<Box<dyn FnOnce() + Send>>);
+
+impl Drop for AfterAll {
+    fn drop(&mut self) {
+        if let Some(hook) = self.0.take() {
+            hook();
+        }
+    }
+}
+
+/// The hooks called once per worker thread (see `RunConfig::with_thread_init`).
+#[derive(Clone, Default)]
+pub(crate) struct ThreadHooks {
+    pub(crate) init: Option<ThreadHook>,
+    pub(crate) teardown: Option<ThreadHook>,
+}
+
+/// The colors used to print the outcome of tests (e.g. `ok` or `FAILED`).
+///
+/// Colors are only used if coloring is enabled at all (see `--color`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Color of passed tests. (Default: green)
+    pub pass: Color,
+
+    /// Color of failed tests and benchmarks. (Default: red)
+    pub fail: Color,
+
+    /// Color of ignored tests and benchmarks. (Default: yellow)
+    pub ignore: Color,
+
+    /// Color of measured benchmarks. (Default: cyan)
+    pub bench: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            pass: Color::Green,
+            fail: Color::Red,
+            ignore: Color::Yellow,
+            bench: Color::Cyan,
+        }
+    }
+}
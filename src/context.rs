@@ -0,0 +1,72 @@
+//! Definition of `Context`, which is passed to runners created via
+//! [`Trial::test_with_context`][crate::Trial::test_with_context].
+
+use std::cell::RefCell;
+
+use crate::Failed;
+
+thread_local! {
+    /// Warnings emitted by the test currently running on this thread. They
+    /// are taken by the harness after the runner returned (or panicked).
+    static WARNINGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Returns and clears all warnings emitted on the current thread.
+pub(crate) fn take_warnings() -> Vec<String> {
+    WARNINGS.with(|warnings| std::mem::take(&mut *warnings.borrow_mut()))
+}
+
+/// Adds the given warnings to the ones of the current thread, e.g. to move
+/// warnings from a helper thread to the thread that collects them.
+pub(crate) fn add_warnings(new: Vec<String>) {
+    WARNINGS.with(|warnings| warnings.borrow_mut().extend(new));
+}
+
+/// Per-test state that is passed to the runner of a test created via
+/// [`Trial::test_with_context`][crate::Trial::test_with_context].
+#[derive(Debug, Default)]
+pub struct Context {
+    soft_failures: Vec<String>,
+}
+
+impl Context {
+    /// Records a failure with the given message if `cond` is `false`. Unlike
+    /// returning `Err`, this does not stop the test: all soft failures are
+    /// collected and reported together once the runner returns. The test
+    /// fails if at least one soft assertion failed.
+    pub fn soft_assert(&mut self, cond: bool, msg: impl Into<String>) {
+        if !cond {
+            self.soft_failures.push(msg.into());
+        }
+    }
+
+    /// Emits a non-fatal warning (e.g. about deprecated usage or a skipped
+    /// optional check). Warnings are listed after the run, but do not affect
+    /// the outcome of the test or the exit code.
+    pub fn warn(&mut self, msg: impl Into<String>) {
+        let msg = msg.into();
+        WARNINGS.with(|warnings| warnings.borrow_mut().push(msg));
+    }
+
+    /// Returns the messages of all soft assertions that failed so far.
+    pub fn soft_failures(&self) -> &[String] {
+        &self.soft_failures
+    }
+
+    /// Combines the result of the runner with all recorded soft failures.
+    pub(crate) fn finish(self, result: Result<(), Failed>) -> Result<(), Failed> {
+        if self.soft_failures.is_empty() {
+            return result;
+        }
+
+        let plural_s = if self.soft_failures.len() == 1 { "" } else { "s" };
+        let mut msg = format!("{} soft assertion{} failed:", self.soft_failures.len(), plural_s);
+        for failure in &self.soft_failures {
+            msg += &format!("\n- {}", failure);
+        }
+        if let Err(Failed { msg: Some(err) }) = result {
+            msg += &format!("\n\n{}", err);
+        }
+        Err(msg.into())
+    }
+}
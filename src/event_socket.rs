@@ -0,0 +1,75 @@
+//! Streaming JSON events to a Unix domain socket (`--event-socket`).
+//!
+//! The events are exactly the ones printed with `--format json`, one JSON
+//! object per line:
+//! - `{ "type": "suite", "event": "started", "test_count": N }`
+//! - `{ "type": "test", "event": "started", "name": "..." }`
+//! - `{ "type": "test", "name": "...", "event": "ok" | "failed" | "ignored" }`
+//!   (with `"stdout"` containing the captured output and failure message, if
+//!   any, and `"cached": true` for tests skipped by `--skip-unchanged`)
+//! - `{ "type": "bench", "name": "...", "median": N, "deviation": N }`
+//! - `{ "type": "suite", "event": "ok" | "failed", "passed": N, ... }`
+//!
+//! The harness connects as client to a listener at the given path, e.g. a
+//! dashboard. If there is no listener or it goes away, events are dropped and
+//! the run continues normally. The same happens if the listener stops reading:
+//! a write that blocks for longer than `WRITE_TIMEOUT` (once the socket's
+//! buffer is full) disables the stream, so a stuck listener never stalls the
+//! run.
+
+use termcolor::WriteColor;
+
+/// How long a single write may block before the stream is given up.
+#[cfg(unix)]
+const WRITE_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Connects to the socket at `path` and returns a writer for the events.
+/// Returns `None` (after printing a note) if connecting failed.
+pub(crate) fn connect(path: &str) -> Option<Box<dyn WriteColor>> {
+    #[cfg(unix)]
+    {
+        let stream = std::os::unix::net::UnixStream::connect(path).and_then(|stream| {
+            stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            Ok(stream)
+        });
+        match stream {
+            Ok(stream) => Some(Box::new(termcolor::NoColor::new(Lossy(Some(stream))))),
+            Err(e) => {
+                eprintln!("note: not streaming events, failed to connect to '{}': {}", path, e);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        eprintln!("note: `--event-socket {}` is only supported on Unix", path);
+        None
+    }
+}
+
+/// Writer that stops writing (without reporting an error) after the first
+/// error, e.g. when the listener disconnected or a write timed out.
+#[cfg(unix)]
+struct Lossy<W>(Option<W>);
+
+#[cfg(unix)]
+impl<W: std::io::Write> std::io::Write for Lossy<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(w) = &mut self.0 {
+            if w.write_all(buf).is_err() {
+                self.0 = None;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if let Some(w) = &mut self.0 {
+            if w.flush().is_err() {
+                self.0 = None;
+            }
+        }
+        Ok(())
+    }
+}
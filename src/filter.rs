@@ -0,0 +1,330 @@
+//! Definition of `FilterSet`, which decides which trials are run.
+
+use std::{fmt, sync::Arc};
+#[cfg(feature = "regex")]
+use std::sync::OnceLock;
+
+use crate::{Arguments, TestInfo};
+
+type Predicate = Arc<dyn Fn(&TestInfo) -> bool + Send + Sync>;
+
+/// All sources that decide which trials are run, combined with a fixed
+/// precedence.
+///
+/// A trial is selected if and only if **all** of the following hold:
+///
+/// 1. It matches **any** of the filter strings, if some are set (see
+///    [`with_filter`]).
+/// 2. It matches **none** of the skip patterns (see [`with_skip`] and
+///    [`with_skip_exact`]).
+/// 3. If only ignored trials are selected, it has the ignored flag (see
+///    [`with_only_ignored`]).
+/// 4. If only tests or only benchmarks are selected, it is of that kind (see
+///    [`with_only_tests`] and [`with_only_benches`]).
+/// 5. Every predicate returns `true` for it (see [`with_predicate`]).
+///
+/// So the filter strings and predicates can only narrow the selection down,
+/// while a single matching skip pattern is enough to exclude a trial. The
+/// order in which the sources are added does not matter. The default
+/// `FilterSet` selects everything.
+///
+/// With [`with_regex`], the filter strings and skip patterns are regular
+/// expressions instead (this requires the `regex` feature).
+///
+/// [`run`][crate::run] builds the set via [`from_args`] (plus the predicates
+/// of [`RunConfig::with_filter_predicate`][crate::RunConfig::with_filter_predicate]).
+/// Trials that are not selected are counted as "filtered out".
+///
+/// [`with_filter`]: Self::with_filter
+/// [`with_skip`]: Self::with_skip
+/// [`with_skip_exact`]: Self::with_skip_exact
+/// [`with_only_ignored`]: Self::with_only_ignored
+/// [`with_only_tests`]: Self::with_only_tests
+/// [`with_only_benches`]: Self::with_only_benches
+/// [`with_predicate`]: Self::with_predicate
+/// [`from_args`]: Self::from_args
+/// [`with_regex`]: Self::with_regex
+#[derive(Clone, Default)]
+pub struct FilterSet {
+    filters: Vec<String>,
+    skip: Vec<String>,
+    skip_exact: Vec<String>,
+    exact: bool,
+    regex: bool,
+    only_ignored: bool,
+    only_tests: bool,
+    only_benches: bool,
+    predicates: Vec<Predicate>,
+
+    /// The compiled filter strings and skip patterns, if `regex` is set.
+    /// Reset whenever the patterns change.
+    #[cfg(feature = "regex")]
+    compiled: OnceLock<Result<Regexes, String>>,
+}
+
+/// The regexes of the filter strings and skip patterns, for `--filter-regex`.
+#[cfg(feature = "regex")]
+#[derive(Clone)]
+struct Regexes {
+    filters: Vec<regex::Regex>,
+    skip: Vec<regex::Regex>,
+}
+
+impl fmt::Debug for FilterSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FilterSet")
+            .field("filters", &self.filters)
+            .field("skip", &self.skip)
+            .field("skip_exact", &self.skip_exact)
+            .field("exact", &self.exact)
+            .field("regex", &self.regex)
+            .field("only_ignored", &self.only_ignored)
+            .field("only_tests", &self.only_tests)
+            .field("only_benches", &self.only_benches)
+            .field("predicates", &self.predicates.len())
+            .finish()
+    }
+}
+
+impl FilterSet {
+    /// Creates the set described by the CLI arguments: the filter strings,
+    /// `--skip`, `--skip-exact`, `--exact`, `--filter-regex`, `--ignored`,
+    /// `--test` and `--bench`.
+    pub fn from_args(args: &Arguments) -> Self {
+        Self {
+            filters: args.filter.clone(),
+            skip: args.skip.clone(),
+            skip_exact: args.skip_exact.clone(),
+            exact: args.exact,
+            regex: args.filter_regex,
+            only_ignored: args.ignored,
+            only_tests: args.test,
+            only_benches: args.bench,
+            predicates: Vec::new(),
+            #[cfg(feature = "regex")]
+            compiled: OnceLock::new(),
+        }
+    }
+
+    /// Adds a filter string: only trials whose name (including the kind in
+    /// brackets, if set) contains any of the filter strings are selected, or
+    /// that are named exactly like one of them with
+    /// [`with_exact`][Self::with_exact].
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filters.push(filter.into());
+        self.patterns_changed();
+        self
+    }
+
+    /// Adds a skip pattern: trials whose name contains it (or is exactly it,
+    /// with [`with_exact`][Self::with_exact]) are not selected.
+    pub fn with_skip(mut self, pattern: impl Into<String>) -> Self {
+        self.skip.push(pattern.into());
+        self.patterns_changed();
+        self
+    }
+
+    /// Adds an exact skip pattern: trials whose name (with or without the
+    /// kind) is exactly `name` are not selected. Unlike
+    /// [`with_skip`][Self::with_skip], this does not depend on
+    /// [`with_exact`][Self::with_exact] and [`with_regex`][Self::with_regex],
+    /// so substring filters can be combined with exact skips.
+    pub fn with_skip_exact(mut self, name: impl Into<String>) -> Self {
+        self.skip_exact.push(name.into());
+        self
+    }
+
+    /// If `true`, the filter strings and skip patterns have to match the name
+    /// exactly instead of being a substring. For exact matches, both the name
+    /// with and without the kind are accepted. (Default: `false`)
+    pub fn with_exact(mut self, exact: bool) -> Self {
+        self.exact = exact;
+        self
+    }
+
+    /// If `true`, the filter strings and skip patterns are interpreted as
+    /// regular expressions, which only have to match a part of the name
+    /// (with or without the kind) unless anchored with `^` and `$`. This
+    /// requires the `regex` feature, see [`validate`][Self::validate].
+    /// (Default: `false`)
+    pub fn with_regex(mut self, regex: bool) -> Self {
+        self.regex = regex;
+        self.patterns_changed();
+        self
+    }
+
+    /// If `true`, only trials with the ignored flag are selected.
+    /// (Default: `false`)
+    pub fn with_only_ignored(mut self, only_ignored: bool) -> Self {
+        self.only_ignored = only_ignored;
+        self
+    }
+
+    /// If `true`, only tests (no benchmarks) are selected. (Default: `false`)
+    pub fn with_only_tests(mut self, only_tests: bool) -> Self {
+        self.only_tests = only_tests;
+        self
+    }
+
+    /// If `true`, only benchmarks are selected. (Default: `false`)
+    pub fn with_only_benches(mut self, only_benches: bool) -> Self {
+        self.only_benches = only_benches;
+        self
+    }
+
+    /// Adds a predicate: only trials for which it returns `true` are
+    /// selected.
+    pub fn with_predicate(
+        mut self,
+        predicate: impl Fn(&TestInfo) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.predicates.push(Arc::new(predicate));
+        self
+    }
+
+    /// Adds all predicates of `other` to this set.
+    pub(crate) fn with_predicates_of(mut self, other: &FilterSet) -> Self {
+        self.predicates.extend(other.predicates.iter().cloned());
+        self
+    }
+
+    /// Returns all filter strings.
+    pub fn filters(&self) -> &[String] {
+        &self.filters
+    }
+
+    /// Returns all skip patterns.
+    pub fn skip_patterns(&self) -> &[String] {
+        &self.skip
+    }
+
+    /// Returns all exact skip patterns (see
+    /// [`with_skip_exact`][Self::with_skip_exact]).
+    pub fn skip_exact_patterns(&self) -> &[String] {
+        &self.skip_exact
+    }
+
+    /// Returns whether the filter strings and skip patterns have to match
+    /// exactly.
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    /// Returns whether the filter strings and skip patterns are regular
+    /// expressions.
+    pub fn is_regex(&self) -> bool {
+        self.regex
+    }
+
+    /// Returns the number of predicates.
+    pub fn num_predicates(&self) -> usize {
+        self.predicates.len()
+    }
+
+    /// Checks that this set can be used for matching. This only fails with
+    /// [`with_regex`][Self::with_regex], if one of the patterns is not a
+    /// valid regular expression or if the `regex` feature is disabled. The
+    /// error describes the problem.
+    ///
+    /// [`run`][crate::run] checks this before running anything, so invalid
+    /// patterns result in an error instead of a panic in
+    /// [`matches`][Self::matches].
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.regex {
+            return Ok(());
+        }
+
+        #[cfg(feature = "regex")]
+        return self.regexes().map(|_| ());
+
+        #[cfg(not(feature = "regex"))]
+        Err("`--filter-regex` requires the `regex` feature of libtest-mimic".into())
+    }
+
+    /// Returns the compiled patterns, compiling them on first use.
+    #[cfg(feature = "regex")]
+    fn regexes(&self) -> Result<&Regexes, String> {
+        let compile = |patterns: &[String]| {
+            patterns.iter()
+                .map(|pattern| regex::Regex::new(pattern)
+                    .map_err(|e| format!("invalid regex `{}`: {}", pattern, e)))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        self.compiled
+            .get_or_init(|| Ok(Regexes {
+                filters: compile(&self.filters)?,
+                skip: compile(&self.skip)?,
+            }))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    fn patterns_changed(&mut self) {
+        #[cfg(feature = "regex")]
+        {
+            self.compiled = OnceLock::new();
+        }
+    }
+
+    /// Returns whether this set selects the given trial. See the type
+    /// documentation for the precedence of all sources.
+    ///
+    /// # Panics
+    ///
+    /// If [`validate`][Self::validate] fails.
+    pub fn matches(&self, info: &TestInfo) -> bool {
+        // Match against the full test name, including the kind. This upholds the invariant that if
+        // --list prints out:
+        //
+        // <some string>: test
+        //
+        // then "--exact <some string>" runs exactly that test.
+        let name_with_kind = info.test_name_with_kind();
+
+        // For exact matches, we want to match against either the test name (to maintain
+        // backwards compatibility with older versions of libtest-mimic), or the test kind
+        // (technically more correct with respect to matching against the output of --list.)
+        let is_match = |pattern: &str| match self.exact {
+            true => info.name == pattern || name_with_kind == pattern,
+            false => name_with_kind.contains(pattern),
+        };
+
+        let (is_selected, is_skipped) = if self.regex {
+            self.matches_regexes(info, &name_with_kind)
+        } else {
+            (
+                self.filters.is_empty() || self.filters.iter().any(|pattern| is_match(pattern)),
+                self.skip.iter().any(|pattern| is_match(pattern)),
+            )
+        };
+
+        let is_skipped_exactly = self.skip_exact.iter()
+            .any(|name| info.name == *name || name_with_kind == name.as_str());
+
+        is_selected
+            && !is_skipped
+            && !is_skipped_exactly
+            && (!self.only_ignored || info.is_ignored)
+            && (!self.only_tests || !info.is_bench)
+            && (!self.only_benches || info.is_bench)
+            && self.predicates.iter().all(|predicate| predicate(info))
+    }
+
+    /// Returns whether the trial matches any of the filter regexes (or there
+    /// are none) and whether it matches any of the skip regexes. Regexes may
+    /// match either the name or the name with kind.
+    #[cfg(feature = "regex")]
+    fn matches_regexes(&self, info: &TestInfo, name_with_kind: &str) -> (bool, bool) {
+        let regexes = self.regexes().unwrap_or_else(|e| panic!("{}", e));
+        let is_match = |re: &regex::Regex| re.is_match(&info.name) || re.is_match(name_with_kind);
+        (
+            regexes.filters.is_empty() || regexes.filters.iter().any(is_match),
+            regexes.skip.iter().any(is_match),
+        )
+    }
+
+    #[cfg(not(feature = "regex"))]
+    fn matches_regexes(&self, _: &TestInfo, _: &str) -> (bool, bool) {
+        panic!("{}", self.validate().unwrap_err())
+    }
+}
@@ -0,0 +1,117 @@
+//! Per-run result files and merging them into a flakiness report.
+//!
+//! With `--results-file PATH`, the outcome of every executed test is written
+//! to `PATH` after the run. The format is very simple: one line per test, the
+//! status (`ok`, `failed`, `ignored`, `cached` or `bench`), a tab, and the
+//! test name (including the kind in brackets, if set). Several such files (e.g. from
+//! different shards or repeated CI runs) can then be merged with
+//! [`merge_runs`].
+
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use crate::Outcome;
+
+/// Returns the status string used in results files for the given outcome.
+pub(crate) fn status_of(outcome: &Outcome) -> &'static str {
+    match outcome {
+        Outcome::Passed => "ok",
+        Outcome::Failed(_) | Outcome::TimedOut(_) => "failed",
+        Outcome::Ignored => "ignored",
+        Outcome::Cached => "cached",
+        Outcome::Measured(_) => "bench",
+    }
+}
+
+/// Writes a results file containing the given `(name, status)` pairs.
+pub(crate) fn write_results(path: &str, results: &[(String, &str)]) -> io::Result<()> {
+    let mut out = String::new();
+    for (name, status) in results {
+        out.push_str(status);
+        out.push('\t');
+        out.push_str(name);
+        out.push('\n');
+    }
+    fs::write(path, out)
+}
+
+/// How often a single test passed and failed across several runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TestRunStats {
+    /// Number of runs in which the test passed (benchmarks that were measured
+    /// also count as passed).
+    pub passed: u64,
+
+    /// Number of runs in which the test failed.
+    pub failed: u64,
+}
+
+impl TestRunStats {
+    /// Returns the ratio of passed runs to all runs in which the test was
+    /// executed, or `None` if it was never executed.
+    pub fn pass_ratio(&self) -> Option<f64> {
+        let total = self.passed + self.failed;
+        if total == 0 {
+            None
+        } else {
+            Some(self.passed as f64 / total as f64)
+        }
+    }
+
+    /// Returns `true` if the test both passed and failed at least once.
+    pub fn is_flaky(&self) -> bool {
+        self.passed > 0 && self.failed > 0
+    }
+}
+
+/// Aggregated outcomes of several runs, see [`merge_runs`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlakinessReport {
+    /// Statistics per test name. Ignored tests are not counted, but are
+    /// still listed if they appeared in any results file.
+    pub tests: BTreeMap<String, TestRunStats>,
+}
+
+impl FlakinessReport {
+    /// Returns all tests that both passed and failed at least once.
+    pub fn flaky_tests(&self) -> impl Iterator<Item = (&str, &TestRunStats)> {
+        self.tests.iter()
+            .filter(|(_, stats)| stats.is_flaky())
+            .map(|(name, stats)| (name.as_str(), stats))
+    }
+}
+
+/// Reads several results files (written via `--results-file`) and aggregates
+/// them into per-test pass/fail counts.
+///
+/// Returns an error if any file cannot be read or contains a malformed line.
+pub fn merge_runs<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+) -> io::Result<FlakinessReport> {
+    let mut report = FlakinessReport::default();
+    for path in paths {
+        let content = fs::read_to_string(path.as_ref())?;
+        for line in content.lines().filter(|l| !l.is_empty()) {
+            let (status, name) = line.split_once('\t').ok_or_else(|| io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed line in results file '{}': {}", path.as_ref().display(), line),
+            ))?;
+
+            let stats = report.tests.entry(name.to_owned()).or_default();
+            match status {
+                "ok" | "bench" => stats.passed += 1,
+                "failed" => stats.failed += 1,
+                "ignored" | "cached" => {}
+                other => return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "unknown status '{}' in results file '{}'",
+                        other,
+                        path.as_ref().display(),
+                    ),
+                )),
+            }
+        }
+    }
+
+    Ok(report)
+}
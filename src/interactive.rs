@@ -0,0 +1,202 @@
+//! Interactive features that read from stdin (e.g. `--confirm`).
+//!
+//! All of these only make sense if a human is sitting in front of a terminal.
+//! If stdin is not a terminal, they are no-ops and print a note to stderr
+//! (or, if they cannot be skipped like `--pick`, exit with an error).
+
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use crate::{printer::Printer, Trial};
+
+/// Result of asking the user for confirmation.
+pub(crate) enum Confirmation {
+    /// The remaining tests should be run.
+    Run,
+
+    /// The user aborted the run (e.g. by closing stdin).
+    Abort,
+}
+
+/// Implements `--confirm`: lists all `tests` on stderr and waits for the user
+/// to press enter. If the user types a string instead, only tests containing
+/// that string are kept and the prompt is shown again.
+pub(crate) fn confirm(tests: &mut Vec<Trial>) -> Confirmation {
+    if !io::stdin().is_terminal() {
+        eprintln!("note: `--confirm` has no effect as stdin is not a terminal");
+        return Confirmation::Run;
+    }
+
+    let mut stdin = io::stdin().lock();
+    let mut stderr = io::stderr().lock();
+    loop {
+        Printer::write_list(tests, false, &mut stderr).unwrap();
+        let plural_s = if tests.len() == 1 { "" } else { "s" };
+        write!(
+            stderr,
+            "\nPress enter to run {} test{}, or type a filter to narrow them down: ",
+            tests.len(),
+            plural_s,
+        ).unwrap();
+        stderr.flush().unwrap();
+
+        let mut line = String::new();
+        if stdin.read_line(&mut line).unwrap_or(0) == 0 {
+            writeln!(stderr).unwrap();
+            return Confirmation::Abort;
+        }
+
+        let filter = line.trim();
+        if filter.is_empty() {
+            return Confirmation::Run;
+        }
+        tests.retain(|test| test.info.test_name_with_kind().contains(filter));
+        writeln!(stderr).unwrap();
+    }
+}
+
+/// Maximum number of matches shown by `--pick`.
+const MAX_PICK_MATCHES: usize = 20;
+
+/// Implements `--pick`: repeatedly asks the user for a fuzzy search query and
+/// lets them select some of the best matching tests. Only the selected tests
+/// are kept. Returns an error if stdin is not a terminal.
+pub(crate) fn pick(tests: &mut Vec<Trial>) -> io::Result<Confirmation> {
+    if !io::stdin().is_terminal() {
+        let msg = "`--pick` requires stdin to be an interactive terminal";
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, msg));
+    }
+
+    let mut stdin = io::stdin().lock();
+    let mut stderr = io::stderr().lock();
+    let mut read_line = |stderr: &mut io::StderrLock<'_>, prompt: &str| {
+        write!(stderr, "{}", prompt).unwrap();
+        stderr.flush().unwrap();
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim().to_owned()),
+        }
+    };
+
+    loop {
+        let Some(query) = read_line(&mut stderr, "Search tests: ") else {
+            writeln!(stderr).unwrap();
+            return Ok(Confirmation::Abort);
+        };
+
+        // Rank all tests by score, best first. Ties keep the original order.
+        let mut matches = tests.iter()
+            .enumerate()
+            .filter_map(|(i, test)| {
+                fuzzy_score(&query, &test.info.test_name_with_kind()).map(|score| (i, score))
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+        matches.truncate(MAX_PICK_MATCHES);
+        if matches.is_empty() {
+            writeln!(stderr, "No tests match '{}'.\n", query).unwrap();
+            continue;
+        }
+
+        for (n, (i, _)) in matches.iter().enumerate() {
+            writeln!(stderr, "{:>4}. {}", n + 1, tests[*i].info.test_name_with_kind()).unwrap();
+        }
+        let prompt = format!(
+            "\nEnter numbers to run (e.g. `1 3`), `a` for all {} shown, or nothing to search \
+                again: ",
+            matches.len(),
+        );
+        let Some(selection) = read_line(&mut stderr, &prompt) else {
+            writeln!(stderr).unwrap();
+            return Ok(Confirmation::Abort);
+        };
+
+        let selected = if selection.is_empty() {
+            writeln!(stderr).unwrap();
+            continue;
+        } else if selection == "a" {
+            matches.iter().map(|(i, _)| *i).collect::<Vec<_>>()
+        } else {
+            let parsed = selection.split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse::<usize>().ok().filter(|n| (1..=matches.len()).contains(n)))
+                .collect::<Option<Vec<_>>>();
+            match parsed {
+                Some(numbers) => numbers.into_iter().map(|n| matches[n - 1].0).collect(),
+                None => {
+                    writeln!(stderr, "Invalid selection '{}'.\n", selection).unwrap();
+                    continue;
+                }
+            }
+        };
+
+        let mut i = 0;
+        tests.retain(|_| {
+            i += 1;
+            selected.contains(&(i - 1))
+        });
+        writeln!(stderr).unwrap();
+        return Ok(Confirmation::Run);
+    }
+}
+
+/// Simple fuzzy matcher: returns `None` if the characters of `query` do not
+/// appear in `name` in order (ignoring case). Otherwise, returns a score that
+/// is higher for better matches: consecutive characters and characters at the
+/// start of words (e.g. after `_` or `::`) score higher, gaps lower the score.
+fn fuzzy_score(query: &str, name: &str) -> Option<i64> {
+    let mut score = 0;
+    let mut prev_match: Option<usize> = None;
+    let mut name_chars = name.chars().enumerate();
+    let mut prev_char = None;
+
+    for q in query.chars().flat_map(char::to_lowercase) {
+        loop {
+            let (i, c) = name_chars.next()?;
+            let before = prev_char.replace(c);
+            if !c.to_lowercase().eq(std::iter::once(q)) {
+                continue;
+            }
+
+            score += 1;
+            let word_start = before.map_or(true, |b: char| !b.is_alphanumeric())
+                || before.is_some_and(|b| b.is_lowercase() && c.is_uppercase());
+            if word_start {
+                score += 5;
+            }
+            match prev_match {
+                Some(prev) if prev + 1 == i => score += 5,
+                Some(prev) => score -= ((i - prev - 1) as i64).min(3),
+                None => {}
+            }
+            prev_match = Some(i);
+            break;
+        }
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "parser::lex"), None);
+        assert_eq!(fuzzy_score("lp", "parser::lex"), None);
+        assert!(fuzzy_score("PL", "parser::lex").is_some());
+
+        // Consecutive characters and word starts are preferred.
+        let score = |q| fuzzy_score(q, "parser::lex_numbers").unwrap();
+        assert!(score("lex") > score("lxn"));
+        assert!(score("pln") > score("aer"));
+
+        let best = ["test_parse_number", "print_long_names", "parse_name"]
+            .into_iter()
+            .max_by_key(|name| fuzzy_score("pname", name))
+            .unwrap();
+        assert_eq!(best, "parse_name");
+    }
+}
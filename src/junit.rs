@@ -0,0 +1,177 @@
+//! Output as JUnit XML document (`--format junit`), e.g. for CI dashboards.
+//!
+//! Nothing is printed while tests run. After the run, a `<testsuites>`
+//! document with a single `<testsuite>` and one `<testcase>` per test that
+//! finished is printed (use `--logfile` to write it to a file).
+//!
+//! When running under Bazel (i.e. `XML_OUTPUT_FILE` is set), the same
+//! document is additionally written to that file, independent of `--format`.
+//! In that case, the test suite is named after `TEST_TARGET` and each
+//! `<testcase>` gets the `status` and `result` attributes Bazel's result
+//! viewers expect (like the XML written by GoogleTest).
+
+use std::{fmt::Write, time::Duration};
+
+use crate::{printer::strip_ansi, Conclusion};
+
+/// How a single test case ended, as far as JUnit is concerned.
+#[derive(Debug)]
+pub(crate) enum CaseResult {
+    Passed,
+
+    /// With the failure message, if any.
+    Failed(Option<String>),
+
+    /// With a message explaining why, if any.
+    Skipped(Option<String>),
+}
+
+/// Collects the results of a run to print them as JUnit XML at the end.
+#[derive(Debug, Default)]
+pub(crate) struct Report {
+    /// `(classname, name, duration, result)` of each test, in the order they
+    /// finished.
+    cases: Vec<(String, String, Option<Duration>, CaseResult)>,
+
+    /// The suite name and whether the Bazel attributes are added.
+    bazel: Option<String>,
+
+    /// Metadata of the run, printed as `<properties>` of the suite.
+    properties: Vec<(String, String)>,
+}
+
+impl Report {
+    /// Creates a report with the given metadata of the run.
+    pub(crate) fn new(properties: Vec<(String, String)>) -> Self {
+        Self { cases: Vec::new(), bazel: None, properties }
+    }
+
+    /// Creates a report for Bazel's `XML_OUTPUT_FILE`, with the given name of
+    /// the test target as suite name.
+    pub(crate) fn bazel(target: String, properties: Vec<(String, String)>) -> Self {
+        Self { cases: Vec::new(), bazel: Some(target), properties }
+    }
+
+    /// Adds a test case. The `classname` is the kind of the test if set.
+    /// Otherwise, like libtest does, it's the module path of the test (e.g.
+    /// `foo::bar` for `foo::bar::baz`) or `integration` if the name has none.
+    pub(crate) fn add(
+        &mut self,
+        name: &str,
+        kind: &str,
+        duration: Option<Duration>,
+        result: CaseResult,
+    ) {
+        let (classname, name) = if kind.is_empty() {
+            name.rsplit_once("::").unwrap_or(("integration", name))
+        } else {
+            (kind, name)
+        };
+        self.cases.push((classname.into(), name.into(), duration, result));
+    }
+
+    /// Renders the whole document and clears the collected results.
+    pub(crate) fn finish(&mut self, conclusion: &Conclusion, execution_time: Duration) -> String {
+        let cases = std::mem::take(&mut self.cases);
+
+        let mut out = String::new();
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#).unwrap();
+        writeln!(out, "<testsuites>").unwrap();
+        writeln!(
+            out,
+            concat!(
+                r#"  <testsuite name="{}" package="{0}" id="0" tests="{}" errors="0""#,
+                r#" failures="{}" skipped="{}" time="{:.3}">"#,
+            ),
+            escape(self.bazel.as_deref().unwrap_or("test")),
+            cases.len(),
+            conclusion.num_failed,
+            conclusion.num_ignored + conclusion.num_ignored_failed + conclusion.num_cached,
+            execution_time.as_secs_f64(),
+        ).unwrap();
+        if !self.properties.is_empty() {
+            writeln!(out, "    <properties>").unwrap();
+            for (name, value) in &self.properties {
+                writeln!(
+                    out,
+                    r#"      <property name="{}" value="{}"/>"#,
+                    escape(name),
+                    escape(value),
+                ).unwrap();
+            }
+            writeln!(out, "    </properties>").unwrap();
+        }
+        for (classname, name, duration, result) in &cases {
+            write!(
+                out,
+                r#"    <testcase classname="{}" name="{}" time="{:.3}""#,
+                escape(classname),
+                escape(name),
+                duration.unwrap_or_default().as_secs_f64(),
+            ).unwrap();
+            if self.bazel.is_some() {
+                let (status, result) = match result {
+                    CaseResult::Skipped(_) => ("notrun", "skipped"),
+                    _ => ("run", "completed"),
+                };
+                write!(out, r#" status="{}" result="{}""#, status, result).unwrap();
+            }
+            match result {
+                CaseResult::Passed => writeln!(out, "/>").unwrap(),
+                CaseResult::Failed(msg) => {
+                    writeln!(out, ">").unwrap();
+                    match msg {
+                        Some(msg) => {
+                            let msg = strip_ansi(msg);
+                            let first_line = msg.lines().next().unwrap_or_default();
+                            writeln!(
+                                out,
+                                r#"      <failure type="failure" message="{}">{}</failure>"#,
+                                escape(first_line),
+                                escape(&msg),
+                            ).unwrap();
+                        }
+                        None => writeln!(out, r#"      <failure type="failure"/>"#).unwrap(),
+                    }
+                    writeln!(out, "    </testcase>").unwrap();
+                }
+                CaseResult::Skipped(msg) => {
+                    writeln!(out, ">").unwrap();
+                    match msg {
+                        Some(msg) => {
+                            let msg = escape(&strip_ansi(msg));
+                            writeln!(out, r#"      <skipped message="{}"/>"#, msg).unwrap();
+                        }
+                        None => writeln!(out, "      <skipped/>").unwrap(),
+                    }
+                    writeln!(out, "    </testcase>").unwrap();
+                }
+            }
+        }
+        writeln!(out, "    <system-out/>").unwrap();
+        writeln!(out, "    <system-err/>").unwrap();
+        writeln!(out, "  </testsuite>").unwrap();
+        writeln!(out, "</testsuites>").unwrap();
+        out
+    }
+}
+
+/// Escapes the given string for use in XML text and attribute values.
+/// Control characters (which are not allowed in XML 1.0) are replaced by
+/// `\u{fffd}`.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' | '\t' | '\r' => out.push(c),
+            c if c.is_control() => out.push('\u{fffd}'),
+            c => out.push(c),
+        }
+    }
+    out
+}
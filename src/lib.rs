@@ -61,25 +61,86 @@
 //!
 //! Some of the notable differences:
 //!
-//! - Output capture and `--nocapture`: simply not supported. The official
-//!   `libtest` uses internal `std` functions to temporarily redirect output.
-//!   `libtest-mimic` cannot use those. See [this issue][capture] for more
+//! - Output capture: the official `libtest` uses internal `std` functions to
+//!   temporarily redirect output. `libtest-mimic` can only use those on
+//!   nightly, when compiled with `--cfg libtest_mimic_capture`, and then only
+//!   captures output of tests run sequentially. Otherwise, `--nocapture` and
+//!   `--capture` have no effect. See [this issue][capture] for more
 //!   information.
-//! - `--format=json|junit`
+//! - `--format=json` uses the same events and field names as the (unstable)
+//!   JSON output of `libtest`, so existing parsers work unchanged. The
+//!   `<testcase>` elements of `--format=junit` are not exactly the same as
+//!   the ones of `libtest`, but follow the common JUnit schema.
+//! - When the `XML_OUTPUT_FILE` environment variable is set (as done by
+//!   Bazel), a JUnit XML report is additionally written to that file,
+//!   independent of `--format`.
+//! - `--filter-regex` is specific to `libtest-mimic` and requires the `regex`
+//!   cargo feature (disabled by default).
+//! - Async tests (`Trial::async_test`) require the `async` cargo feature
+//!   (disabled by default).
+//! - Handling Ctrl-C gracefully (`RunConfig::with_handle_sigint`) requires
+//!   the `sigint` cargo feature (disabled by default).
 //!
 //! [capture]: https://github.com/LukasKalbertodt/libtest-mimic/issues/9
 
 #![forbid(unsafe_code)]
-
-use std::{borrow::Cow, fmt, process::{self, ExitCode}, sync::mpsc, time::Instant};
+#![cfg_attr(libtest_mimic_capture, feature(internal_output_capture))]
+
+use std::{
+    borrow::Cow,
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    fmt, io,
+    path::PathBuf,
+    process::{self, ExitCode},
+    sync::{atomic::{self, AtomicBool}, mpsc, Arc, Barrier, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 mod args;
+mod async_test;
+mod baseline;
+mod cache;
+mod capture;
+mod checkpoint;
+mod config;
+mod context;
+mod event_socket;
+mod filter;
+mod flakiness;
+mod interactive;
+mod junit;
+mod manifest;
+mod markdown;
+mod order;
+mod panic_hook;
 mod printer;
+mod profile;
+mod rerun;
+mod rng;
+mod shared;
+#[cfg(feature = "sigint")]
+mod sigint;
+mod spinner;
+mod temp_cwd;
+mod timing;
 
 use printer::Printer;
-use threadpool::ThreadPool;
+use timing::Timing;
+
+pub use termcolor::Color;
 
-pub use crate::args::{Arguments, ColorSetting, FormatSetting};
+pub use crate::{
+    args::{Arguments, CaptureSetting, ColorSetting, FormatSetting, ProgressSetting, SummaryDetail},
+    config::{RunConfig, Theme},
+    context::Context,
+    filter::FilterSet,
+    flakiness::{merge_runs, FlakinessReport, TestRunStats},
+    shared::SharedTrial,
+};
+
+#[cfg(feature = "async")]
+pub use crate::async_test::TestFuture;
 
 
 
@@ -96,6 +157,14 @@ pub use crate::args::{Arguments, ColorSetting, FormatSetting};
 /// compare the panic payload to an expected value anyway.
 pub struct Trial {
     runner: Box<dyn FnOnce(bool) -> Outcome + Send>,
+
+    /// The runner again, if it can be called repeatedly (see
+    /// `Trial::test_repeatable`).
+    repeatable: Option<Arc<dyn Fn(bool) -> Outcome + Send + Sync>>,
+
+    /// Creates the future of an async trial (see `Trial::async_test`). Turned
+    /// into the runner once the executor is known.
+    future: Option<Box<dyn FnOnce() -> async_test::TestFuture + Send>>,
     info: TestInfo,
 }
 
@@ -113,15 +182,127 @@ impl Trial {
                 Ok(()) => Outcome::Passed,
                 Err(failed) => Outcome::Failed(failed),
             }),
-            info: TestInfo {
-                name: name.into(),
-                kind: String::new(),
-                is_ignored: false,
-                is_bench: false,
-            },
+            repeatable: None,
+            future: None,
+            info: TestInfo::new(name.into(), false),
+        }
+    }
+
+    /// Creates a (non-benchmark) test whose runner can be called several
+    /// times. Apart from that, this is the same as [`Trial::test`].
+    ///
+    /// This is required for [`with_retry_if`][Self::with_retry_if].
+    pub fn test_repeatable<R>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: Fn() -> Result<(), Failed> + Send + Sync + 'static,
+    {
+        let repeatable = Arc::new(move |_test_mode| match runner() {
+            Ok(()) => Outcome::Passed,
+            Err(failed) => Outcome::Failed(failed),
+        });
+        let runner = Arc::clone(&repeatable);
+        Self {
+            runner: Box::new(move |test_mode| runner(test_mode)),
+            repeatable: Some(repeatable),
+            future: None,
+            info: TestInfo::new(name.into(), false),
+        }
+    }
+
+    /// Creates an async (non-benchmark) test. Apart from that, this is the
+    /// same as [`Trial::test`].
+    ///
+    /// The future returned by the runner is driven to completion on the
+    /// thread running the test, by the executor set via
+    /// [`RunConfig::with_block_on`]. Without one, a minimal built-in executor
+    /// is used, which cannot drive futures that need a runtime (e.g. tokio's
+    /// I/O and timers). Requires the `async` cargo feature.
+    ///
+    /// ```
+    /// use libtest_mimic::Trial;
+    ///
+    /// let test = Trial::async_test("answer", || async {
+    ///     let answer = async { 42 }.await;
+    ///     assert_eq!(answer, 42);
+    ///     Ok(())
+    /// });
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn async_test<R, F>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: FnOnce() -> F + Send + 'static,
+        F: std::future::Future<Output = Result<(), Failed>> + Send + 'static,
+    {
+        Self {
+            runner: Box::new(|_| unreachable!("executor of async trial not bound")),
+            repeatable: None,
+            future: Some(Box::new(move || Box::pin(runner()))),
+            info: TestInfo::new(name.into(), false),
         }
     }
 
+    /// Creates a (non-benchmark) test whose runner gets a [`Context`].
+    ///
+    /// Apart from that, this is the same as [`Trial::test`]. The context can
+    /// be used to record soft assertions, which do not stop the test:
+    ///
+    /// ```
+    /// use libtest_mimic::Trial;
+    ///
+    /// let test = Trial::test_with_context("math", |ctx| {
+    ///     ctx.soft_assert(1 + 1 == 2, "addition is broken");
+    ///     ctx.soft_assert(2 * 2 == 4, "multiplication is broken");
+    ///     Ok(())
+    /// });
+    /// ```
+    pub fn test_with_context<R>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: FnOnce(&mut Context) -> Result<(), Failed> + Send + 'static,
+    {
+        Self::test(name, move || {
+            let mut ctx = Context::default();
+            let result = runner(&mut ctx);
+            ctx.finish(result)
+        })
+    }
+
+    /// Creates a test that runs the given external command. The test passes
+    /// iff the process exits successfully (with exit code 0).
+    ///
+    /// The stdout and stderr of the process are captured. If the process
+    /// fails (or cannot be started at all), they are included in the failure
+    /// message.
+    ///
+    /// ```no_run
+    /// use std::process::Command;
+    /// use libtest_mimic::Trial;
+    ///
+    /// let mut cmd = Command::new("sh");
+    /// cmd.arg("tests/scripts/smoke.sh");
+    /// let test = Trial::from_command("smoke.sh", cmd);
+    /// ```
+    pub fn from_command(name: impl Into<String>, mut command: process::Command) -> Self {
+        Self::test(name, move || {
+            let output = command.output()
+                .map_err(|e| format!("failed to start {:?}: {}", command.get_program(), e))?;
+            if output.status.success() {
+                return Ok(());
+            }
+
+            let mut msg = format!("process exited with {}", output.status);
+            for (label, captured) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+                if !captured.is_empty() {
+                    msg += &format!(
+                        "\n--- {} ---\n{}",
+                        label,
+                        String::from_utf8_lossy(captured).trim_end(),
+                    );
+                }
+            }
+            Err(msg.into())
+        })
+    }
+
     /// Creates a benchmark with the given name and runner.
     ///
     /// If the runner's parameter `test_mode` is `true`, the runner function
@@ -146,20 +327,161 @@ impl Trial {
                 Ok(None)
                     => Outcome::Failed("bench runner returned `Ok(None)` in bench mode".into()),
             }),
-            info: TestInfo {
-                name: name.into(),
-                kind: String::new(),
-                is_ignored: false,
-                is_bench: true,
-            },
+            repeatable: None,
+            future: None,
+            info: TestInfo::new(name.into(), true),
+        }
+    }
+
+    /// Creates a benchmark whose runner can be called several times. Apart
+    /// from that, this is the same as [`Trial::bench`].
+    ///
+    /// This is required for `--bench-iterations`.
+    pub fn bench_repeatable<R>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: Fn(bool) -> Result<Option<Measurement>, Failed> + Send + Sync + 'static,
+    {
+        let repeatable = Arc::new(move |test_mode| match runner(test_mode) {
+            Err(failed) => Outcome::Failed(failed),
+            Ok(_) if test_mode => Outcome::Passed,
+            Ok(Some(measurement)) => Outcome::Measured(measurement),
+            Ok(None)
+                => Outcome::Failed("bench runner returned `Ok(None)` in bench mode".into()),
+        });
+        let runner = Arc::clone(&repeatable);
+        Self {
+            runner: Box::new(move |test_mode| runner(test_mode)),
+            repeatable: Some(repeatable),
+            future: None,
+            info: TestInfo::new(name.into(), true),
         }
     }
 
+    /// Creates a benchmark (see [`Trial::bench`]) that fails if its average
+    /// regresses by more than `max_regression_pct` percent compared to
+    /// `baseline_avg` (in ns/iter), turning it into a regression gate.
+    ///
+    /// The failure message states the measured and the baseline average as
+    /// well as the allowed regression. There is no built-in loading of
+    /// baselines from a file: the harness has to obtain `baseline_avg`
+    /// itself, e.g. from a file committed to the repository. In test mode,
+    /// nothing is measured and thus nothing is compared.
+    ///
+    /// ```
+    /// use libtest_mimic::{Trial, Measurement};
+    ///
+    /// let bench = Trial::bench_with_threshold("parse", 1_000, 10.0, |_test_mode| {
+    ///     Ok(Some(Measurement { avg: 1_050, variance: 0, ..Measurement::default() }))
+    /// });
+    /// ```
+    pub fn bench_with_threshold<R>(
+        name: impl Into<String>,
+        baseline_avg: u64,
+        max_regression_pct: f64,
+        runner: R,
+    ) -> Self
+    where
+        R: FnOnce(bool) -> Result<Option<Measurement>, Failed> + Send + 'static,
+    {
+        Self::bench(name, move |test_mode| {
+            let measurement = runner(test_mode)?;
+            if let (false, Some(&Measurement { avg, .. })) = (test_mode, measurement.as_ref()) {
+                let (avg_f, baseline_f) = (avg as f64, baseline_avg as f64);
+                if avg_f * 100.0 > baseline_f * (100.0 + max_regression_pct) {
+                    let regression_pct = (avg_f / baseline_f - 1.0) * 100.0;
+                    return Err(format!(
+                        "benchmark regressed: {} ns/iter vs. baseline {} ns/iter \
+                            (+{:.1}%, allowed: {}%)",
+                        printer::fmt_with_thousand_sep(avg),
+                        printer::fmt_with_thousand_sep(baseline_avg),
+                        regression_pct,
+                        max_regression_pct,
+                    ).into());
+                }
+            }
+            Ok(measurement)
+        })
+    }
+
+    /// Creates a benchmark (see [`Trial::bench`]) that fails if its average
+    /// exceeds `max_avg_ns` (in ns/iter), i.e. an absolute performance gate
+    /// that is independent of any baseline (unlike
+    /// [`bench_with_threshold`][Self::bench_with_threshold]).
+    ///
+    /// The failure message states the measured and the allowed average. In
+    /// test mode, the runner only runs once without measuring, so the limit
+    /// is not checked.
+    ///
+    /// ```
+    /// use libtest_mimic::{Trial, Measurement};
+    ///
+    /// let bench = Trial::bench_with_limit("parse", 500_000, |_test_mode| {
+    ///     Ok(Some(Measurement { avg: 420_000, variance: 0, ..Measurement::default() }))
+    /// });
+    /// ```
+    pub fn bench_with_limit<R>(name: impl Into<String>, max_avg_ns: u64, runner: R) -> Self
+    where
+        R: FnOnce(bool) -> Result<Option<Measurement>, Failed> + Send + 'static,
+    {
+        Self::bench(name, move |test_mode| {
+            let measurement = runner(test_mode)?;
+            if let (false, Some(&Measurement { avg, .. })) = (test_mode, measurement.as_ref()) {
+                if avg > max_avg_ns {
+                    return Err(format!(
+                        "benchmark exceeded limit: {} ns/iter vs. allowed {} ns/iter",
+                        printer::fmt_with_thousand_sep(avg),
+                        printer::fmt_with_thousand_sep(max_avg_ns),
+                    ).into());
+                }
+            }
+            Ok(measurement)
+        })
+    }
+
+    /// Creates one benchmark per element of `inputs`, all sharing the same
+    /// runner.
+    ///
+    /// Each benchmark is named `base_name[input]` (see [`param_name`]) and
+    /// owns its input, which is passed to the runner together
+    /// with `test_mode` (see [`Trial::bench`] for its meaning). The resulting
+    /// trials are normal benchmarks, i.e. they are filtered and counted like
+    /// any other benchmark.
+    ///
+    /// ```
+    /// use libtest_mimic::{Trial, Measurement};
+    ///
+    /// let benches = Trial::bench_group("sort", [10, 100, 1000], |len, _test_mode| {
+    ///     let mut v = (0..len).rev().collect::<Vec<_>>();
+    ///     v.sort();
+    ///     Ok(Some(Measurement { avg: len, variance: 0, ..Measurement::default() }))
+    /// });
+    /// assert_eq!(benches[1].name(), "sort[100]");
+    /// ```
+    pub fn bench_group<I, R>(
+        base_name: impl Into<String>,
+        inputs: impl IntoIterator<Item = I>,
+        runner: R,
+    ) -> Vec<Self>
+    where
+        I: fmt::Display + Send + 'static,
+        R: Fn(I, bool) -> Result<Option<Measurement>, Failed> + Send + Sync + 'static,
+    {
+        let base_name = base_name.into();
+        let runner = Arc::new(runner);
+        inputs.into_iter()
+            .map(|input| {
+                let name = param_name(&base_name, &input);
+                let runner = Arc::clone(&runner);
+                Self::bench(name, move |test_mode| runner(input, test_mode))
+            })
+            .collect()
+    }
+
     /// Sets the "kind" of this test/benchmark. If this string is not
     /// empty, it is printed in brackets before the test name (e.g.
     /// `test [my-kind] test_name`). (Default: *empty*)
     ///
-    /// This is the only extension to the original libtest.
+    /// The original libtest has no such concept.
     pub fn with_kind(self, kind: impl Into<String>) -> Self {
         Self {
             info: TestInfo {
@@ -170,6 +492,22 @@ impl Trial {
         }
     }
 
+    /// Sets the module path of this test/benchmark (e.g. `parser::expr`),
+    /// used to group tests in reports: `--list` prints the tests as a tree of
+    /// modules, the run summary shows pass/fail counts per module and
+    /// [`Conclusion::modules`] contains these counts. Segments are separated
+    /// by `::`. Unlike the kind, the module is not part of the printed test
+    /// name. (Default: none)
+    pub fn with_module(self, path: impl Into<String>) -> Self {
+        Self {
+            info: TestInfo {
+                module: Some(path.into()),
+                ..self.info
+            },
+            ..self
+        }
+    }
+
     /// Sets whether or not this test is considered "ignored". (Default: `false`)
     ///
     /// With the built-in test suite, you can annotate `#[ignore]` on tests to
@@ -186,6 +524,203 @@ impl Trial {
         }
     }
 
+    /// Marks this test as "ignored" (see
+    /// [`with_ignored_flag`][Self::with_ignored_flag]) and sets the reason
+    /// why, like `#[ignore = "reason"]` of the built-in test suite.
+    ///
+    /// The reason is shown wherever the test is reported as ignored: after
+    /// `ignored` in the pretty output and in the `--list-ignored-after`
+    /// list, as `reason` of the JSON event, as message of the JUnit
+    /// `<skipped>` element and in the TAP `SKIP` directive.
+    pub fn with_ignored_reason(self, reason: impl Into<String>) -> Self {
+        Self {
+            info: TestInfo {
+                is_ignored: true,
+                ignored_reason: Some(reason.into()),
+                ..self.info
+            },
+            ..self
+        }
+    }
+
+    /// Sets whether this test is still run after the run has been aborted
+    /// (e.g. via `--max-failures`). (Default: `false`)
+    ///
+    /// This is useful for important validation or cleanup tests that should
+    /// be executed in any case. Note that such tests are also not skipped
+    /// when they come after the failure that aborted the run.
+    pub fn with_always_run(self, always_run: bool) -> Self {
+        Self {
+            info: TestInfo {
+                always_run,
+                ..self.info
+            },
+            ..self
+        }
+    }
+
+    /// Retries this test up to `max_retries` times if it fails (or panics)
+    /// and `should_retry` returns `true` for the failure, e.g. to only retry
+    /// on "connection reset" errors. Deterministic failures can thus fail
+    /// right away, without wasting time on retries.
+    ///
+    /// If the test failed after retries, the number of attempts is appended
+    /// to the failure message. If it passed after retries, a warning notes
+    /// the number of attempts (see [`Context::warn`]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if this trial was not created via
+    /// [`test_repeatable`][Self::test_repeatable], as only those can be run
+    /// several times.
+    pub fn with_retry_if(
+        self,
+        max_retries: u32,
+        should_retry: impl Fn(&Failed) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        let repeatable = self.repeatable.clone()
+            .expect("`with_retry_if` requires a trial created via `Trial::test_repeatable`");
+        let runner = Box::new(move |test_mode| {
+            let mut attempts = 1;
+            loop {
+                let outcome = panic_hook::catch(|| repeatable(test_mode))
+                    .unwrap_or_else(|(payload, location)| panic_outcome(payload, location));
+                match outcome {
+                    Outcome::Failed(failed) if attempts <= max_retries && should_retry(&failed) => {
+                        attempts += 1;
+                    }
+                    Outcome::Failed(failed) if attempts > 1 => {
+                        return failed_after_attempts(failed, attempts);
+                    }
+                    outcome => {
+                        if attempts > 1 {
+                            let warning = format!("passed after {} attempts", attempts);
+                            context::add_warnings(vec![warning]);
+                        }
+                        return outcome;
+                    }
+                }
+            }
+        });
+        Self { runner, ..self }
+    }
+
+    /// Returns a new trial with the same metadata and runner, if the runner
+    /// can be called several times (see `test_repeatable`).
+    fn repeated(&self) -> Option<Trial> {
+        let repeatable = self.repeatable.clone()?;
+        let runner = Arc::clone(&repeatable);
+        Some(Self {
+            runner: Box::new(move |test_mode| runner(test_mode)),
+            repeatable: Some(repeatable),
+            future: None,
+            info: self.info.clone(),
+        })
+    }
+
+    /// Sets an estimate of how long this test takes to run. (Default: *none*)
+    ///
+    /// This is used by `--time-budget` to decide which tests fit into the
+    /// budget.
+    pub fn with_estimated_duration(self, estimate: Duration) -> Self {
+        Self {
+            info: TestInfo {
+                estimated_duration: Some(estimate),
+                ..self.info
+            },
+            ..self
+        }
+    }
+
+    /// Sets a fingerprint of this test, i.e. a hash of everything the outcome
+    /// of this test depends on (its inputs, the code under test, ...).
+    /// (Default: *none*)
+    ///
+    /// With `--skip-unchanged`, a test whose fingerprint matches the one
+    /// stored for a previous pass is not run again, but reported as "cached".
+    /// This crate just compares the numbers: the correctness of this caching
+    /// completely depends on the quality of your fingerprints. If the
+    /// fingerprint misses something the test depends on, a failing test might
+    /// be reported as cached.
+    pub fn with_fingerprint(self, fingerprint: u64) -> Self {
+        Self {
+            info: TestInfo {
+                fingerprint: Some(fingerprint),
+                ..self.info
+            },
+            ..self
+        }
+    }
+
+    /// Declares which source files this test covers. (Default: *none*)
+    ///
+    /// With `--changed-files`, only tests that cover at least one of the
+    /// changed files are run. Tests that do not declare any files are always
+    /// run. Paths are compared exactly as given, so they should be written
+    /// the same way as in the list of changed files (e.g. relative to the
+    /// repository root).
+    pub fn with_source_files<P: Into<PathBuf>>(self, paths: impl IntoIterator<Item = P>) -> Self {
+        Self {
+            info: TestInfo {
+                source_files: paths.into_iter().map(Into::into).collect(),
+                ..self.info
+            },
+            ..self
+        }
+    }
+
+    /// Sets whether this test compares against snapshots, e.g. via `insta`.
+    /// (Default: `false`)
+    ///
+    /// If any snapshot test fails, a hint to review pending snapshots with
+    /// `cargo insta review` is printed after the failures.
+    pub fn with_snapshot_flag(self, is_snapshot: bool) -> Self {
+        Self {
+            info: TestInfo {
+                is_snapshot,
+                ..self.info
+            },
+            ..self
+        }
+    }
+
+    /// Sets the source location where this test is defined. It is printed
+    /// below the failure message (e.g. `at src/foo.rs:42:5`) and included in
+    /// the JSON output as `location`. (Default: *none*)
+    ///
+    /// This is useful for harnesses that generate tests from files and know
+    /// the path and line where each test is defined.
+    pub fn with_location(self, file: &'static str, line: u32, column: u32) -> Self {
+        Self {
+            info: TestInfo {
+                location: Some((file, line, column)),
+                ..self.info
+            },
+            ..self
+        }
+    }
+
+    /// Sets whether this test runs in a fresh temporary directory as its
+    /// current working directory. (Default: `false`)
+    ///
+    /// This is useful for tests that create files relative to the working
+    /// directory. The directory is created (inside [`std::env::temp_dir`])
+    /// right before the test starts and is removed with all its contents
+    /// afterwards, even if the test panicked. As the working directory is
+    /// shared by the whole process, all tests are run sequentially on the
+    /// main thread if any test has this flag set. For the same reason, such
+    /// tests cannot be combined with `--timeout`: the run is rejected with an
+    /// error if any of them is selected.
+    pub fn with_temp_cwd(self, temp_cwd: bool) -> Self {
+        Self {
+            info: TestInfo {
+                temp_cwd,
+                ..self.info
+            },
+            ..self
+        }
+    }
+
     /// Returns the name of this trial.
     pub fn name(&self) -> &str {
         &self.info.name
@@ -197,11 +732,57 @@ impl Trial {
         &self.info.kind
     }
 
+    /// Returns the module path of this trial, if one was set.
+    pub fn module(&self) -> Option<&str> {
+        self.info.module.as_deref()
+    }
+
     /// Returns whether this trial has been marked as *ignored*.
     pub fn has_ignored_flag(&self) -> bool {
         self.info.is_ignored
     }
 
+    /// Returns the reason why this trial is ignored, if one was set.
+    pub fn ignored_reason(&self) -> Option<&str> {
+        self.info.ignored_reason.as_deref()
+    }
+
+    /// Returns whether this trial is still run after the run has been aborted.
+    pub fn is_always_run(&self) -> bool {
+        self.info.always_run
+    }
+
+    /// Returns the fingerprint of this trial, if one was set.
+    pub fn fingerprint(&self) -> Option<u64> {
+        self.info.fingerprint
+    }
+
+    /// Returns the estimated duration of this trial, if one was set.
+    pub fn estimated_duration(&self) -> Option<Duration> {
+        self.info.estimated_duration
+    }
+
+    /// Returns the source files this trial covers.
+    pub fn source_files(&self) -> &[PathBuf] {
+        &self.info.source_files
+    }
+
+    /// Returns whether this trial has been marked as snapshot test.
+    pub fn is_snapshot(&self) -> bool {
+        self.info.is_snapshot
+    }
+
+    /// Returns whether this trial runs in a fresh temporary working directory.
+    pub fn has_temp_cwd(&self) -> bool {
+        self.info.temp_cwd
+    }
+
+    /// Returns the source location (file, line, column) of this trial, if one
+    /// was set.
+    pub fn location(&self) -> Option<(&'static str, u32, u32)> {
+        self.info.location
+    }
+
     /// Returns `true` iff this trial is a test (as opposed to a benchmark).
     pub fn is_test(&self) -> bool {
         !self.info.is_bench
@@ -226,22 +807,138 @@ impl fmt::Debug for Trial {
             .field("runner", &OpaqueRunner)
             .field("name", &self.info.name)
             .field("kind", &self.info.kind)
+            .field("module", &self.info.module)
             .field("is_ignored", &self.info.is_ignored)
+            .field("ignored_reason", &self.info.ignored_reason)
             .field("is_bench", &self.info.is_bench)
+            .field("always_run", &self.info.always_run)
+            .field("fingerprint", &self.info.fingerprint)
+            .field("estimated_duration", &self.info.estimated_duration)
+            .field("source_files", &self.info.source_files)
+            .field("is_snapshot", &self.info.is_snapshot)
+            .field("temp_cwd", &self.info.temp_cwd)
+            .field("location", &self.info.location)
             .finish()
     }
 }
 
-#[derive(Debug)]
-struct TestInfo {
+/// Metadata of a single trial, i.e. everything except the runner.
+///
+/// Returned by [`list_tests`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestInfo {
     name: String,
     kind: String,
+    module: Option<String>,
     is_ignored: bool,
+    ignored_reason: Option<String>,
     is_bench: bool,
+    always_run: bool,
+    fingerprint: Option<u64>,
+    estimated_duration: Option<Duration>,
+    source_files: Vec<PathBuf>,
+    is_snapshot: bool,
+    temp_cwd: bool,
+    location: Option<(&'static str, u32, u32)>,
 }
 
 impl TestInfo {
-    fn test_name_with_kind(&self) -> Cow<'_, str> {
+    fn new(name: String, is_bench: bool) -> Self {
+        Self {
+            name,
+            kind: String::new(),
+            module: None,
+            is_ignored: false,
+            ignored_reason: None,
+            is_bench,
+            always_run: false,
+            fingerprint: None,
+            estimated_duration: None,
+            source_files: Vec::new(),
+            is_snapshot: false,
+            temp_cwd: false,
+            location: None,
+        }
+    }
+
+    /// Returns the name of the trial.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the kind of the trial. If no kind was set, this is an empty
+    /// string.
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Returns the module path of the trial, if one was set.
+    pub fn module(&self) -> Option<&str> {
+        self.module.as_deref()
+    }
+
+    /// Returns whether the trial has been marked as *ignored*.
+    pub fn has_ignored_flag(&self) -> bool {
+        self.is_ignored
+    }
+
+    /// Returns the reason why the trial is ignored, if one was set.
+    pub fn ignored_reason(&self) -> Option<&str> {
+        self.ignored_reason.as_deref()
+    }
+
+    /// Returns whether the trial is still run after the run has been aborted.
+    pub fn is_always_run(&self) -> bool {
+        self.always_run
+    }
+
+    /// Returns the fingerprint of the trial, if one was set.
+    pub fn fingerprint(&self) -> Option<u64> {
+        self.fingerprint
+    }
+
+    /// Returns the estimated duration of the trial, if one was set.
+    pub fn estimated_duration(&self) -> Option<Duration> {
+        self.estimated_duration
+    }
+
+    /// Returns the source files the trial covers.
+    pub fn source_files(&self) -> &[PathBuf] {
+        &self.source_files
+    }
+
+    /// Returns whether the trial has been marked as snapshot test.
+    pub fn is_snapshot(&self) -> bool {
+        self.is_snapshot
+    }
+
+    /// Returns whether the trial runs in a fresh temporary working directory.
+    pub fn has_temp_cwd(&self) -> bool {
+        self.temp_cwd
+    }
+
+    /// Returns the source location (file, line, column) of the trial, if one
+    /// was set.
+    pub fn location(&self) -> Option<(&'static str, u32, u32)> {
+        self.location
+    }
+
+    /// Returns the location formatted as `file:line:column`, if one was set.
+    pub(crate) fn location_string(&self) -> Option<String> {
+        self.location.map(|(file, line, column)| format!("{}:{}:{}", file, line, column))
+    }
+
+    /// Returns `true` iff the trial is a test (as opposed to a benchmark).
+    pub fn is_test(&self) -> bool {
+        !self.is_bench
+    }
+
+    /// Returns `true` iff the trial is a benchmark (as opposed to a test).
+    pub fn is_bench(&self) -> bool {
+        self.is_bench
+    }
+
+    pub(crate) fn test_name_with_kind(&self) -> Cow<'_, str> {
         if self.kind.is_empty() {
             Cow::Borrowed(&self.name)
         } else {
@@ -251,20 +948,77 @@ impl TestInfo {
 }
 
 /// Output of a benchmark.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// ```
+/// use libtest_mimic::Measurement;
+///
+/// // Printed as `bench:       1,234 ns/iter (+/- 56) = 3.09 MiB/s`.
+/// let measurement = Measurement {
+///     avg: 1_234,
+///     variance: 56,
+///     bytes: Some(4_000),
+///     ..Measurement::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Measurement {
-    /// Average time in ns.
+    /// Average time in ns (or in `unit`, if set).
     pub avg: u64,
 
-    /// Variance in ns.
+    /// Variance in ns (or in `unit`, if set).
     pub variance: u64,
+
+    /// Unit of `avg` and `variance`, printed instead of `ns` (e.g. `cycles`).
+    /// (Default: *none*, i.e. nanoseconds)
+    pub unit: Option<String>,
+
+    /// Number of bytes processed per iteration. If set (and `unit` is not),
+    /// the throughput in MiB/s is printed after the average. (Default:
+    /// *none*)
+    pub bytes: Option<u64>,
+
+    /// Average of the fastest run, if the benchmark was run several times
+    /// (e.g. with `--bench-iterations`). If set, it is printed after the
+    /// variance. (Default: *none*)
+    pub min: Option<u64>,
+
+    /// Average of the slowest run, see [`min`][Self::min]. (Default: *none*)
+    pub max: Option<u64>,
+}
+
+impl Measurement {
+    /// Returns `variance / avg`, i.e. the variance relative to the average.
+    /// Returns infinity if `avg` is 0 (but the variance is not).
+    pub fn coefficient_of_variation(&self) -> f64 {
+        if self.variance == 0 {
+            0.0
+        } else {
+            self.variance as f64 / self.avg as f64
+        }
+    }
+
+    /// Combines the measurements of several runs of the same benchmark (see
+    /// `--bench-iterations`): `avg` and `variance` are averaged, `min` and
+    /// `max` are the extremes of all runs (using `avg` for runs without
+    /// them). `unit` and `bytes` are taken from the first run.
+    pub(crate) fn combine(runs: &[Measurement]) -> Measurement {
+        let n = runs.len().max(1) as u64;
+        Measurement {
+            avg: runs.iter().map(|m| m.avg).sum::<u64>() / n,
+            variance: runs.iter().map(|m| m.variance).sum::<u64>() / n,
+            unit: runs.first().and_then(|m| m.unit.clone()),
+            bytes: runs.first().and_then(|m| m.bytes),
+            min: runs.iter().map(|m| m.min.unwrap_or(m.avg)).min(),
+            max: runs.iter().map(|m| m.max.unwrap_or(m.avg)).max(),
+        }
+    }
 }
 
 /// Indicates that a test/benchmark has failed. Optionally carries a message.
 ///
 /// You usually want to use the `From` impl of this type, which allows you to
 /// convert any `T: fmt::Display` (e.g. `String`, `&str`, ...) into `Failed`.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Failed {
     msg: Option<String>,
 }
@@ -292,8 +1046,41 @@ impl<M: std::fmt::Display> From<M> for Failed {
 
 
 /// The outcome of performing a test/benchmark.
-#[derive(Debug, Clone)]
-enum Outcome {
+///
+/// This is part of the public API so that custom reporters can describe
+/// results in their own types. New variants may be added in the future, so
+/// matches on this type need a wildcard arm.
+///
+/// The outcomes of all tests of a run are available in
+/// [`Conclusion::results`]:
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use libtest_mimic::{Arguments, Outcome, RunConfig, Trial};
+///
+/// let tests = vec![
+///     Trial::test("passes", || Ok(())),
+///     Trial::test("fails", || Err("wrong answer".into())),
+/// ];
+/// let config = RunConfig::default().with_writer(Arc::new(Mutex::new(std::io::sink())));
+/// let conclusion = libtest_mimic::run_with_config(&Arguments::default(), tests, config);
+/// let rows = conclusion.results.iter()
+///     .map(|(name, outcome)| {
+///         let status = match outcome {
+///             Outcome::Passed => "passed".to_owned(),
+///             Outcome::Failed(failed) => format!("failed: {}", failed.message().unwrap_or("")),
+///             Outcome::Ignored => "ignored".to_owned(),
+///             Outcome::Measured(m) => format!("{} ns/iter", m.avg),
+///             _ => "other".to_owned(),
+///         };
+///         format!("<tr><td>{}</td><td>{}</td></tr>", name, status)
+///     })
+///     .collect::<Vec<_>>();
+/// assert!(rows.contains(&"<tr><td>fails</td><td>failed: wrong answer</td></tr>".to_owned()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Outcome {
     /// The test passed.
     Passed,
 
@@ -305,6 +1092,14 @@ enum Outcome {
 
     /// The benchmark was successfully run.
     Measured(Measurement),
+
+    /// The test was not run as it passed before with the same fingerprint
+    /// (see `--skip-unchanged`).
+    Cached,
+
+    /// The test did not finish within the given `--timeout`. This is treated
+    /// and reported like a failure.
+    TimedOut(Duration),
 }
 
 /// Contains information about the entire test run. Is returned by[`run`].
@@ -312,12 +1107,14 @@ enum Outcome {
 /// This type is marked as `#[must_use]`. Usually, you just call
 /// [`exit()`][Conclusion::exit] on the result of `run` to exit the application
 /// with the correct exit code. But you can also store this value and inspect
-/// its data.
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// its data. New fields might be added in minor releases, so this type can only
+/// be created via `Default`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
 #[must_use = "Call `exit()` or `exit_if_failed()` to set the correct return code"]
 pub struct Conclusion {
     /// Number of tests and benchmarks that were filtered out (either by the
-    /// filter-in pattern or by `--skip` arguments).
+    /// filter-in pattern or by `--skip`/`--skip-exact` arguments).
     pub num_filtered_out: u64,
 
     /// Number of passed tests.
@@ -329,106 +1126,247 @@ pub struct Conclusion {
     /// Number of ignored tests and benchmarks.
     pub num_ignored: u64,
 
+    /// Number of ignored tests and benchmarks that were run anyway (see
+    /// `--run-ignored-soft`) and failed. These do not count as failures.
+    pub num_ignored_failed: u64,
+
     /// Number of benchmarks that successfully ran.
     pub num_measured: u64,
+
+    /// Number of tests and benchmarks that were not run because they passed
+    /// before with the same fingerprint (see `--skip-unchanged`).
+    pub num_cached: u64,
+
+    /// Number of tests that failed at first, but passed when retried (see
+    /// `--retries`). These are also counted in `num_passed`.
+    pub num_flaky: u64,
+
+    /// Number of tests and benchmarks that were not run because the run was
+    /// aborted (e.g. due to `--max-failures`). The sum of all `num_*` fields
+    /// always equals the total number of tests and benchmarks passed to
+    /// [`run`].
+    pub num_not_run: u64,
+
+    /// Names of all tests and benchmarks that were not run because the run
+    /// was aborted (see `num_not_run`).
+    pub not_run: Vec<String>,
+
+    /// Number of warnings emitted by tests (see [`Context::warn`]).
+    pub num_warnings: u64,
+
+    /// Names and measurements of all benchmarks that successfully ran (see
+    /// `num_measured`), in the order they finished.
+    pub measurements: Vec<(String, Measurement)>,
+
+    /// Names and outcomes of all tests and benchmarks that finished (i.e.
+    /// were not filtered out or aborted), in the order they finished. Soft
+    /// failures (see `--run-ignored-soft`) are listed as
+    /// [`Outcome::Failed`], although they are counted in
+    /// `num_ignored_failed`.
+    pub results: Vec<(String, Outcome)>,
+
+    /// Wall clock time the whole run took, as printed in the summary line.
+    pub total_duration: Duration,
+
+    /// Whether the run was interrupted via the flag set with
+    /// [`RunConfig::with_interrupt_flag`]. The tests that were not started
+    /// anymore are counted in `num_not_run`.
+    pub interrupted: bool,
+
+    /// Counts per module path of all executed tests that have a module (see
+    /// [`Trial::with_module`]), sorted by module path. Tests are only
+    /// counted in their own module, not in parent modules.
+    pub modules: Vec<(String, ModuleCounts)>,
+}
+
+/// Pass/fail counts of the tests of one module (see [`Conclusion::modules`]).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ModuleCounts {
+    /// Number of passed tests, including measured benchmarks and cached tests.
+    pub num_passed: u64,
+
+    /// Number of failed (or timed out) tests.
+    pub num_failed: u64,
+
+    /// Number of ignored tests, including failed ones with
+    /// `--run-ignored-soft`.
+    pub num_ignored: u64,
 }
 
 impl Conclusion {
     /// Returns an exit code that can be returned from `main` to signal
-    /// success/failure to the calling process.
+    /// success/failure to the calling process (see [`Self::raw_exit_code`]).
     pub fn exit_code(&self) -> ExitCode {
-        if self.has_failed() {
-            ExitCode::from(101)
+        ExitCode::from(self.raw_exit_code() as u8)
+    }
+
+    /// Returns the exit code that signals success/failure as number: 130 if
+    /// the run was interrupted (like a process killed by `SIGINT`), 101 if
+    /// there were failures and 0 otherwise. To use other codes, see
+    /// [`Self::exit_with_code`].
+    pub fn raw_exit_code(&self) -> i32 {
+        if self.interrupted {
+            130
+        } else if self.has_failed() {
+            101
         } else {
-            ExitCode::SUCCESS
+            0
         }
     }
 
+    /// Returns `true` if the run was successful, i.e. no test failed and the
+    /// run was not interrupted.
+    pub fn is_success(&self) -> bool {
+        !self.has_failed() && !self.interrupted
+    }
+
     /// Returns whether there have been any failures.
     pub fn has_failed(&self) -> bool {
         self.num_failed > 0
     }
 
-    /// Exits the application with an appropriate error code (0 if all tests
-    /// have passed, 101 if there have been failures). This uses
-    /// [`process::exit`], meaning that destructors are not ran. Consider
-    /// using [`Self::exit_code`] instead for a proper program cleanup.
-    pub fn exit(&self) -> ! {
-        self.exit_if_failed();
-        process::exit(0);
+    /// Returns the percentage (between 0 and 100) of passed tests among all
+    /// tests that passed or failed, or `None` if no test passed or failed.
+    /// Ignored tests and benchmarks are not taken into account.
+    pub fn pass_rate(&self) -> Option<f64> {
+        let total = self.num_passed + self.num_failed;
+        (total > 0).then(|| 100.0 * self.num_passed as f64 / total as f64)
     }
 
-    /// Exits the application with error code 101 if there were any failures.
-    /// Otherwise, returns normally. This uses [`process::exit`], meaning that
+    /// Exits the application with an appropriate error code (see
+    /// [`Self::raw_exit_code`]). This uses [`process::exit`], meaning that
     /// destructors are not ran. Consider using [`Self::exit_code`] instead for
     /// a proper program cleanup.
+    pub fn exit(&self) -> ! {
+        process::exit(self.raw_exit_code());
+    }
+
+    /// Exits the application with the error code returned by `code`, e.g. to
+    /// signal that all tests were filtered out with a distinct code. Like
+    /// [`Self::exit`], this uses [`process::exit`].
+    ///
+    /// ```no_run
+    /// # let conclusion = libtest_mimic::Conclusion::default();
+    /// conclusion.exit_with_code(|c| match c.num_filtered_out {
+    ///     n if n > 0 && c.num_passed + c.num_failed == 0 => 2,
+    ///     _ => c.raw_exit_code(),
+    /// });
+    /// ```
+    pub fn exit_with_code(&self, code: impl FnOnce(&Self) -> i32) -> ! {
+        process::exit(code(self));
+    }
+
+    /// Exits the application with the error code of [`Self::raw_exit_code`]
+    /// if the run was not successful. Otherwise, returns normally. This uses
+    /// [`process::exit`], meaning that destructors are not ran. Consider using
+    /// [`Self::exit_code`] instead for a proper program cleanup.
     pub fn exit_if_failed(&self) {
-        if self.has_failed() {
-            process::exit(101)
+        if !self.is_success() {
+            process::exit(self.raw_exit_code())
         }
     }
 
-    fn empty() -> Self {
-        Self {
-            num_filtered_out: 0,
-            num_passed: 0,
-            num_failed: 0,
-            num_ignored: 0,
-            num_measured: 0,
+    /// Adds all numbers of `other` to the numbers of `self`. Useful to combine
+    /// the conclusions of several runs.
+    pub fn merge(&mut self, other: &Conclusion) {
+        self.num_filtered_out += other.num_filtered_out;
+        self.num_passed += other.num_passed;
+        self.num_failed += other.num_failed;
+        self.num_ignored += other.num_ignored;
+        self.num_ignored_failed += other.num_ignored_failed;
+        self.num_measured += other.num_measured;
+        self.num_cached += other.num_cached;
+        self.num_flaky += other.num_flaky;
+        self.interrupted |= other.interrupted;
+        self.num_not_run += other.num_not_run;
+        self.num_warnings += other.num_warnings;
+        self.not_run.extend(other.not_run.iter().cloned());
+        self.measurements.extend(other.measurements.iter().cloned());
+        self.results.extend(other.results.iter().cloned());
+        for (module, counts) in &other.modules {
+            let own = self.module_counts_mut(module);
+            own.num_passed += counts.num_passed;
+            own.num_failed += counts.num_failed;
+            own.num_ignored += counts.num_ignored;
         }
+        self.total_duration += other.total_duration;
+    }
+
+    /// Returns the counts of the given module, inserting them (sorted by
+    /// module path) if not present yet.
+    fn module_counts_mut(&mut self, module: &str) -> &mut ModuleCounts {
+        let idx = match self.modules.binary_search_by(|(m, _)| m.as_str().cmp(module)) {
+            Ok(idx) => idx,
+            Err(idx) => {
+                self.modules.insert(idx, (module.to_owned(), ModuleCounts::default()));
+                idx
+            }
+        };
+        &mut self.modules[idx].1
+    }
+
+    fn empty() -> Self {
+        Self::default()
     }
 }
 
 impl Arguments {
     /// Returns `true` if the given test should be ignored.
     fn is_ignored(&self, test: &Trial) -> bool {
-        (test.info.is_ignored && !self.ignored && !self.include_ignored)
-            || (test.info.is_bench && self.test)
-            || (!test.info.is_bench && self.bench)
-    }
-
-    fn is_filtered_out(&self, test: &Trial) -> bool {
-        let test_name = test.name();
-        // Match against the full test name, including the kind. This upholds the invariant that if
-        // --list prints out:
-        //
-        // <some string>: test
-        //
-        // then "--exact <some string>" runs exactly that test.
-        let test_name_with_kind = test.info.test_name_with_kind();
-
-        // If a filter was specified, apply this
-        if let Some(filter) = &self.filter {
-            match self.exact {
-                // For exact matches, we want to match against either the test name (to maintain
-                // backwards compatibility with older versions of libtest-mimic), or the test kind
-                // (technically more correct with respect to matching against the output of --list.)
-                true if test_name != filter && &test_name_with_kind != filter => return true,
-                false if !test_name_with_kind.contains(filter) => return true,
-                _ => {}
-            };
-        }
+        test.info.is_ignored && !self.ignored && !self.include_ignored && !self.run_ignored_soft
+    }
 
-        // If any skip pattern were specified, test for all patterns.
-        for skip_filter in &self.skip {
-            match self.exact {
-                // For exact matches, we want to match against either the test name (to maintain
-                // backwards compatibility with older versions of libtest-mimic), or the test kind
-                // (technically more correct with respect to matching against the output of --list.)
-                true if test_name == skip_filter || &test_name_with_kind == skip_filter => {
-                    return true
-                }
-                false if test_name_with_kind.contains(skip_filter) => return true,
-                _ => {}
-            }
-        }
+    /// Returns `true` if a failure of the given test does not make the run
+    /// fail (see `--run-ignored-soft`).
+    fn is_soft(&self, test: &TestInfo) -> bool {
+        self.run_ignored_soft && test.is_ignored
+    }
+}
 
-        if self.ignored && !test.info.is_ignored {
-            return true;
-        }
+/// Returns a stable name for a generated (e.g. parameterized) trial:
+/// `base[value]`, using the `Display` impl of `value`.
+///
+/// To keep the name easy to pass to `--exact` and to use as JSON key, all
+/// characters of the formatted value except alphanumeric ones and
+/// `_ - . , : + = ( ) /` are replaced by `_`. This includes whitespace,
+/// quotes, backslashes and brackets. `base` is used as is. As different values
+/// can result in the same name (e.g. `"a b"` and `"a_b"`), [`run`] checks that
+/// all names are unique.
+///
+/// ```
+/// use libtest_mimic::param_name;
+///
+/// assert_eq!(param_name("parse", 42), "parse[42]");
+/// assert_eq!(param_name("parse", "1 + 1"), "parse[1_+_1]");
+/// assert_eq!(param_name("parse", "\"[x]\""), "parse[__x__]");
+/// ```
+pub fn param_name(base: &str, value: impl fmt::Display) -> String {
+    let value = value.to_string()
+        .chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() => c,
+            '_' | '-' | '.' | ',' | ':' | '+' | '=' | '(' | ')' | '/' => c,
+            _ => '_',
+        })
+        .collect::<String>();
+    format!("{}[{}]", base, value)
+}
 
-        false
-    }
+/// Returns the metadata of all trials that would be run with the given
+/// arguments, without running (or printing) anything.
+///
+/// This is the programmatic counterpart to `--list`: the same filtering as in
+/// [`run`] is applied (see [`FilterSet::from_args`]), i.e. the filter strings,
+/// `--skip`, `--skip-exact`, `--exact`, `--ignored`, `--test` and `--bench`
+/// are respected.
+/// Trials that are merely ignored (i.e. have the ignored flag) are still
+/// returned.
+pub fn list_tests(args: &Arguments, tests: &[Trial]) -> Vec<TestInfo> {
+    let filters = FilterSet::from_args(args);
+    tests.iter()
+        .filter(|test| filters.matches(&test.info))
+        .map(|test| test.info.clone())
+        .collect()
 }
 
 /// Runs all given trials (tests & benchmarks).
@@ -439,102 +1377,1150 @@ impl Arguments {
 /// The returned value contains a couple of useful information. See
 /// [`Conclusion`] for more information. If `--list` was specified, a list is
 /// printed and a dummy `Conclusion` is returned.
-pub fn run(args: &Arguments, mut tests: Vec<Trial>) -> Conclusion {
+pub fn run(args: &Arguments, tests: Vec<Trial>) -> Conclusion {
+    run_with_config(args, tests, RunConfig::default())
+}
+
+/// Like [`run`], but with additional configuration that cannot be specified
+/// via CLI arguments (e.g. the color [`Theme`]). See [`RunConfig`].
+pub fn run_with_config(args: &Arguments, tests: Vec<Trial>, config: RunConfig) -> Conclusion {
+    let args = &prefiltered_args(args, &config);
+    run_with_printer(args, tests, &config, Printer::new(args, &config))
+        .unwrap_or_else(|e| fatal_error(&e))
+}
+
+/// Returns `args` without the CLI name filters if the trials were already
+/// filtered (see `RunConfig::with_prefiltered`).
+fn prefiltered_args(args: &Arguments, config: &RunConfig) -> Arguments {
+    if !config.prefiltered {
+        return args.clone();
+    }
+    Arguments {
+        filter: Vec::new(),
+        skip: Vec::new(),
+        skip_exact: Vec::new(),
+        exact: false,
+        filter_regex: false,
+        ..args.clone()
+    }
+}
+
+/// Like [`run`], but returns an error instead of panicking or exiting the
+/// process:
+///
+/// - if the output cannot be written, e.g. if stdout is a closed pipe (like
+///   when piping into `head`) or the `--logfile` cannot be created,
+/// - if a file passed via the CLI (e.g. `--replay-order` or `--results-file`)
+///   cannot be read or written, and
+/// - with [`ErrorKind::InvalidInput`][std::io::ErrorKind::InvalidInput] if
+///   the arguments or trials are invalid, e.g. if two trials have the same
+///   name (see [`validate_names`]) or `--expect-tests` does not match.
+///
+/// After the first write error, no further tests are started (as if the run
+/// was interrupted, see [`RunConfig::with_interrupt_flag`]) and the error is
+/// returned once the tests that are already running finished. To handle
+/// invalid CLI arguments without exiting the process, parse them with
+/// [`Arguments::try_from_iter`]. Panics of the test runners are still caught
+/// and reported as failed tests, like in `run`.
+///
+/// ```no_run
+/// use libtest_mimic::{Arguments, Trial};
+///
+/// let tests = vec![Trial::test("foo", || Ok(()))];
+/// match libtest_mimic::try_run(&Arguments::from_args(), tests) {
+///     Ok(conclusion) => conclusion.exit(),
+///     Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => std::process::exit(141),
+///     Err(e) => panic!("failed to write test output: {e}"),
+/// }
+/// ```
+pub fn try_run(args: &Arguments, tests: Vec<Trial>) -> std::io::Result<Conclusion> {
+    try_run_with_config(args, tests, RunConfig::default())
+}
+
+/// Like [`try_run`], but with additional configuration (see
+/// [`run_with_config`]). A flag set via [`RunConfig::with_interrupt_flag`]
+/// still interrupts the run, just like the first write error does.
+pub fn try_run_with_config(
+    args: &Arguments,
+    tests: Vec<Trial>,
+    config: RunConfig,
+) -> std::io::Result<Conclusion> {
+    let args = &prefiltered_args(args, &config);
+    let mut printer = Printer::try_new(args, &config)?;
+    let errors = printer.track_write_errors();
+    let config = config.with_interrupt_flag(errors.occurred.clone());
+    let result = run_with_printer(args, tests, &config, printer);
+    match errors.take() {
+        Some(e) => Err(e),
+        None => result,
+    }
+}
+
+fn run_with_printer<'a>(
+    args: &'a Arguments,
+    tests: Vec<Trial>,
+    config: &RunConfig,
+    printer: Printer<'a>,
+) -> io::Result<Conclusion> {
+    check_expected_test_count(args, tests.len())?;
+    let mut state = RunState::new(args, config, printer)?;
+    check_baseline_list(args, &mut state.printer, &tests)?;
+    let conclusion = match args.repeat {
+        n if n > 1 && !args.list && !args.print_config && !args.no_run => {
+            run_repeated(&mut state, tests, n)?
+        }
+        _ => run_tests(&mut state, tests)?,
+    };
+    state.finish()?;
+    Ok(conclusion)
+}
+
+/// Implements `--repeat`: runs `tests` `n` times, each iteration printed like
+/// a section of `run_sections`. After the first iteration, only the trials
+/// that can be run several times are run again (see `Trial::repeated`).
+fn run_repeated(state: &mut RunState<'_>, tests: Vec<Trial>, n: u32) -> io::Result<Conclusion> {
+    let start_instant = Instant::now();
+    let templates = tests.iter().filter_map(Trial::repeated).collect::<Vec<_>>();
+    let mut first = Some(tests);
+    let mut conclusion = Conclusion::empty();
+    for iteration in 1..=n {
+        let tests = first.take()
+            .unwrap_or_else(|| templates.iter().filter_map(Trial::repeated).collect());
+        state.printer.print_section_header(&format!("iteration {}/{}", iteration, n));
+        conclusion.merge(&run_tests(state, tests)?);
+        if conclusion.interrupted {
+            break;
+        }
+    }
+    state.printer.print_total(&conclusion, start_instant.elapsed());
+    state.printer.print_repeat_summary(n, &conclusion);
+    Ok(conclusion)
+}
+
+/// Runs the given trials with default arguments (i.e. ignoring the actual CLI
+/// arguments) and without printing anything. Returns `Err` with the complete
+/// output (including all failure messages and the summary) if any trial
+/// failed, or with the error of [`validate_names`] (without running anything)
+/// if two trials have the same name.
+///
+/// This is the recommended way to check a dynamically generated list of
+/// trials from within a normal `#[test]` function, without the process
+/// being exited:
+///
+/// ```
+/// # fn generate_tests() -> Vec<libtest_mimic::Trial> { vec![] }
+/// // Inside of `#[test] fn generated_tests_pass() { ... }`:
+/// libtest_mimic::run_in_test(generate_tests()).unwrap();
+/// ```
+pub fn run_in_test(tests: Vec<Trial>) -> Result<(), String> {
+    // `run_tests` would exit the whole process instead.
+    validate_names(&tests)?;
+
+    let args = Arguments::default();
+    let buffer = SharedBuffer::default();
+    let out = Box::new(termcolor::NoColor::new(buffer.clone()));
+    let config = RunConfig::default();
+    let printer = Printer::with_output(&args, &config, out);
+    let conclusion = RunState::new(&args, &config, printer)
+        .and_then(|mut state| {
+            let conclusion = run_tests(&mut state, tests)?;
+            state.finish()?;
+            Ok(conclusion)
+        })
+        .map_err(|e| e.to_string())?;
+
+    if conclusion.has_failed() {
+        Err(buffer.into_string())
+    } else {
+        Ok(())
+    }
+}
+
+/// An in-memory output that can be shared with the printer.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    fn into_string(self) -> String {
+        let bytes = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
+
+impl std::io::Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs several labeled lists of trials, one after another.
+///
+/// Each section is printed like a separate run of [`run`] (i.e. with its own
+/// list of failures and `test result:` line), preceded by a header with the
+/// section's label. At the end, a line with the total numbers of all sections
+/// is printed. Filtering is applied to each section in the same way as in
+/// [`run`]. The returned `Conclusion` is the [merged][Conclusion::merge]
+/// conclusion of all sections.
+pub fn run_sections(args: &Arguments, sections: Vec<(String, Vec<Trial>)>) -> Conclusion {
+    let config = RunConfig::default();
+    run_sections_with_printer(args, sections, &config, Printer::new(args, &config))
+        .unwrap_or_else(|e| fatal_error(&e))
+}
+
+fn run_sections_with_printer<'a>(
+    args: &'a Arguments,
+    sections: Vec<(String, Vec<Trial>)>,
+    config: &RunConfig,
+    printer: Printer<'a>,
+) -> io::Result<Conclusion> {
+    check_expected_test_count(args, sections.iter().map(|(_, tests)| tests.len()).sum())?;
+    let start_instant = Instant::now();
+    let mut state = RunState::new(args, config, printer)?;
+
+    let mut conclusion = Conclusion::empty();
+    for (label, tests) in sections {
+        state.printer.print_section_header(&label);
+        let section_conclusion = run_tests(&mut state, tests)?;
+        conclusion.merge(&section_conclusion);
+    }
+
+    if !args.list {
+        state.printer.print_total(&conclusion, start_instant.elapsed());
+    }
+    state.finish()?;
+
+    Ok(conclusion)
+}
+
+/// Implements `--expect-tests`: returns an error if the number of trials
+/// (before filtering) does not match.
+fn check_expected_test_count(args: &Arguments, num_tests: usize) -> io::Result<()> {
+    match args.expect_tests {
+        Some(expected) if expected != num_tests => Err(invalid_input(format!(
+            "expected {} tests (`--expect-tests`), but {} were defined",
+            expected,
+            num_tests,
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Implements `--baseline-list`: prints the tests that were added or removed
+/// compared to the list. With `--deny-removed-tests`, an error is returned if
+/// any were removed.
+fn check_baseline_list(
+    args: &Arguments,
+    printer: &mut Printer<'_>,
+    tests: &[Trial],
+) -> io::Result<()> {
+    let Some(path) = &args.baseline_list else { return Ok(()) };
+    let baseline = std::fs::read_to_string(path)
+        .map_err(|e| file_error("failed to read baseline list", path, e))?;
+    let names = tests.iter()
+        .map(|test| test.info.test_name_with_kind().into_owned())
+        .collect::<Vec<_>>();
+    let diff = baseline::diff(&baseline, &names);
+    printer.print_baseline_diff(&diff);
+
+    if args.deny_removed_tests && !diff.removed.is_empty() {
+        let plural_s = if diff.removed.len() == 1 { "" } else { "s" };
+        return Err(invalid_input(format!(
+            "{} test{} from the baseline list removed (`--deny-removed-tests`)",
+            diff.removed.len(),
+            plural_s,
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that no two trials have the same name (including the kind, so
+/// trials of different kinds may share a name). Otherwise, the error lists
+/// the duplicates, e.g. `duplicate test names: a, b`.
+///
+/// [`run`] performs this check up front and exits with the error (unless
+/// `--rename-duplicates` is set), as duplicates would make `--exact`, the
+/// results file and JSON output ambiguous. Call this to handle duplicates
+/// yourself, e.g. to fail a build step with a custom message.
+///
+/// ```
+/// use libtest_mimic::Trial;
+///
+/// let tests = vec![Trial::test("a", || Ok(())), Trial::test("a", || Ok(()))];
+/// assert_eq!(libtest_mimic::validate_names(&tests), Err("duplicate test names: a".into()));
+/// ```
+pub fn validate_names(tests: &[Trial]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    let mut duplicates = tests.iter()
+        .map(|test| test.info.test_name_with_kind())
+        .filter(|name| !seen.insert(name.clone()))
+        .collect::<Vec<_>>();
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+    duplicates.sort();
+    duplicates.dedup();
+    Err(format!("duplicate test names: {}", duplicates.join(", ")))
+}
+
+/// Implements `--rename-duplicates`: the second, third, ... trial with the
+/// same name (including the kind) gets `#2`, `#3`, ... appended to its name,
+/// skipping suffixes that would clash with another trial.
+fn rename_duplicates(tests: &mut [Trial]) {
+    let mut taken = tests.iter()
+        .map(|test| test.info.test_name_with_kind().into_owned())
+        .collect::<HashSet<_>>();
+    let mut seen = HashSet::new();
+    for test in tests {
+        if seen.insert(test.info.test_name_with_kind().into_owned()) {
+            continue;
+        }
+        let base = test.info.name.clone();
+        for n in 2.. {
+            test.info.name = format!("{}#{}", base, n);
+            if taken.insert(test.info.test_name_with_kind().into_owned()) {
+                break;
+            }
+        }
+    }
+}
+
+/// Prints the given error to stderr and exits the application with code 101
+/// (the same code used for test failures).
+fn fatal_error(e: &io::Error) -> ! {
+    eprintln!("error: {}", e);
+    process::exit(101);
+}
+
+/// Creates the error for invalid arguments or trials, which `run` passes to
+/// `fatal_error` (and `try_run_with_config` returns).
+fn invalid_input(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+/// Adds what failed and the path to an error of reading or writing a file,
+/// e.g. `failed to read order file 'order.txt': No such file or directory`.
+fn file_error(what: &str, path: &str, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{} '{}': {}", what, path, e))
+}
+
+/// Everything that is shared between all sections of a single run.
+struct RunState<'a> {
+    args: &'a Arguments,
+
+    /// Used for all output.
+    printer: Printer<'a>,
+
+    /// The outcome of every executed trial, for `--results-file`.
+    results: Vec<(String, &'static str)>,
+
+    /// The master seed all randomness is derived from, if any randomness is
+    /// required in this run.
+    seed: Option<u64>,
+
+    /// Records the order in which tests are started, for `--record-order`.
+    recorder: Option<order::OrderRecorder>,
+
+    /// The order to run tests in, for `--replay-order`.
+    replay_order: Option<order::RecordedOrder>,
+
+    /// Fingerprints of previously passed tests, for `--skip-unchanged`.
+    cache: Option<cache::FingerprintCache>,
+
+    /// The outcomes of finished tests, for `--checkpoint`.
+    checkpoint: Option<checkpoint::Checkpoint>,
+
+    /// The outcomes of tests that finished in an interrupted run, for
+    /// `--resume`.
+    resumed: HashMap<String, Outcome>,
+
+    /// Decides which tests are run, built from the arguments and config.
+    filters: FilterSet,
+
+    /// The files listed in the `--changed-files` file.
+    changed_files: Option<HashSet<PathBuf>>,
+
+    /// Called around each runner.
+    hooks: config::TestHooks,
+    thread_hooks: config::ThreadHooks,
+    thread_stack_size: Option<usize>,
+    invariants: Vec<(String, config::Invariant)>,
+
+    /// The final state of every test, for `--manifest`.
+    manifest: Vec<manifest::ManifestEntry>,
+    profile: Vec<(String, Timing)>,
+
+    /// Called with the outcome of each finished test (see
+    /// `RunConfig::with_on_result`).
+    on_result: Option<config::ResultHook>,
+
+    /// Parsed `--time-budget`.
+    time_budget: Option<Duration>,
+
+    /// Drives async tests (see `RunConfig::with_block_on`).
+    block_on: Option<async_test::BlockOn>,
+
+    /// Set to interrupt the run (see `RunConfig::with_interrupt_flag`).
+    interrupt: Vec<Arc<AtomicBool>>,
+
+    /// Whether `SIGINT` is handled (see `RunConfig::with_handle_sigint`).
+    #[cfg(feature = "sigint")]
+    handle_sigint: bool,
+}
+
+impl<'a> RunState<'a> {
+    fn new(args: &'a Arguments, config: &RunConfig, printer: Printer<'a>) -> io::Result<Self> {
+        // Clap already rejects this, but `Arguments` can also be constructed
+        // manually.
+        if args.test && args.bench {
+            return Err(invalid_input("`--test` and `--bench` cannot be used together".into()));
+        }
+        // A timed out test keeps running in the background, so it could
+        // break an invariant while the next test is checked.
+        if args.timeout.is_some() && !config.invariants.is_empty() {
+            return Err(invalid_input(
+                "`--timeout` cannot be used with invariants (see `RunConfig::with_invariant`)"
+                    .into(),
+            ));
+        }
+        let filters = FilterSet::from_args(args).with_predicates_of(&config.filter);
+        filters.validate()
+            .and_then(|()| parse_test_threads(args).map(|_| ()))
+            .map_err(invalid_input)?;
+        let time_budget = args.time_budget
+            .map(|secs| Duration::try_from_secs_f64(secs).map_err(|_| {
+                invalid_input(format!("invalid value for `--time-budget`: {}", secs))
+            }))
+            .transpose()?;
+
+        // An explicit `--shuffle-seed` is used as is, so no master seed is
+        // needed (and printed) then.
+        let seed = if args.shuffle && args.shuffle_seed.is_none() {
+            Some(args.seed.unwrap_or_else(rng::seed_from_clock))
+        } else {
+            None
+        };
+
+        let replay_order = args.replay_order.as_ref()
+            .map(|path| {
+                order::RecordedOrder::read(path)
+                    .map_err(|e| file_error("failed to read order file", path, e))
+            })
+            .transpose()?;
+
+        let cache = args.skip_unchanged.as_ref()
+            .map(|path| {
+                cache::FingerprintCache::read(path)
+                    .map_err(|e| file_error("failed to read cache file", path, e))
+            })
+            .transpose()?;
+
+        let resumed = match &args.resume {
+            Some(path) => checkpoint::read(path)
+                .map_err(|e| file_error("failed to read checkpoint file", path, e))?,
+            None => HashMap::new(),
+        };
+
+        let changed_files = match &args.changed_files {
+            Some(path) => {
+                let content = std::fs::read_to_string(path)
+                    .map_err(|e| file_error("failed to read changed files", path, e))?;
+                let lines = content.lines().map(str::trim).filter(|l| !l.is_empty());
+                Some(lines.map(PathBuf::from).collect())
+            }
+            None => None,
+        };
+
+        // Both uninstalled when the state is dropped.
+        let interrupt = config.interrupt.clone();
+        #[cfg(feature = "sigint")]
+        let interrupt = match config.handle_sigint {
+            true => interrupt.into_iter().chain([sigint::install()?]).collect(),
+            false => interrupt,
+        };
+        if !args.show_panic_immediately {
+            panic_hook::install();
+        }
+
+        Ok(Self {
+            args,
+            printer,
+            results: Vec::new(),
+            seed,
+            recorder: args.record_order.as_ref().map(|_| order::OrderRecorder::default()),
+            replay_order,
+            cache,
+            checkpoint: args.checkpoint.clone().map(checkpoint::Checkpoint::new),
+            resumed,
+            filters,
+            changed_files,
+            hooks: config.hooks.clone(),
+            thread_hooks: config.thread_hooks.clone(),
+            thread_stack_size: config.thread_stack_size,
+            invariants: config.invariants.clone(),
+            manifest: Vec::new(),
+            profile: Vec::new(),
+            time_budget,
+            on_result: config.on_result.clone(),
+            block_on: config.block_on.clone(),
+            interrupt,
+            #[cfg(feature = "sigint")]
+            handle_sigint: config.handle_sigint,
+        })
+    }
+
+    /// Writes all files that are only written after the whole run.
+    fn finish(mut self) -> io::Result<()> {
+        if let Some(path) = &self.args.results_file {
+            flakiness::write_results(path, &self.results)
+                .map_err(|e| file_error("failed to write results file", path, e))?;
+        }
+        if let (Some(path), Some(recorder)) = (&self.args.record_order, &self.recorder) {
+            recorder.write(path).map_err(|e| file_error("failed to write order file", path, e))?;
+        }
+        if let (Some(path), Some(cache)) = (&self.args.skip_unchanged, &self.cache) {
+            cache.write(path).map_err(|e| file_error("failed to write cache file", path, e))?;
+        }
+        if let (Some(path), Some(checkpoint)) = (&self.args.checkpoint, &mut self.checkpoint) {
+            checkpoint.write().map_err(|e| file_error("failed to write checkpoint file", path, e))?;
+        }
+        if let Some(path) = &self.args.manifest {
+            manifest::write(path, &self.manifest)
+                .map_err(|e| file_error("failed to write manifest", path, e))?;
+        }
+        if let Some(path) = &self.args.profile {
+            profile::write(path, &self.profile)
+                .map_err(|e| file_error("failed to write profile", path, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RunState<'_> {
+    fn drop(&mut self) {
+        if !self.args.show_panic_immediately {
+            panic_hook::uninstall();
+        }
+        #[cfg(feature = "sigint")]
+        if self.handle_sigint {
+            sigint::uninstall();
+        }
+    }
+}
+
+/// Filters, runs and reports a single list of trials.
+fn run_tests(state: &mut RunState<'_>, mut tests: Vec<Trial>) -> io::Result<Conclusion> {
+    async_test::bind_executor(&mut tests, state.block_on.as_ref());
+    let args = state.args;
+    if args.bench && args.bench_iterations > 1 {
+        repeat_benches(&mut tests, args.bench_iterations);
+    }
+    let printer = &mut state.printer;
+    let results = &mut state.results;
+    let cache = &mut state.cache;
+    let checkpoint = &mut state.checkpoint;
+    let profile = &mut state.profile;
+    let on_result = &state.on_result;
     let start_instant = Instant::now();
     let mut conclusion = Conclusion::empty();
 
+    if args.rename_duplicates {
+        rename_duplicates(&mut tests);
+    }
+    validate_names(&tests).map_err(invalid_input)?;
+
+    // For `--manifest`, remember all tests in their original order.
+    let all_names = args.manifest.as_ref().map(|_| {
+        tests.iter().map(|test| test.info.test_name_with_kind().into_owned()).collect::<Vec<_>>()
+    });
+
     // Apply filtering
-    if args.filter.is_some() || !args.skip.is_empty() || args.ignored {
+    let len_before = tests.len() as u64;
+    tests.retain(|test| state.filters.matches(&test.info));
+    conclusion.num_filtered_out = len_before - tests.len() as u64;
+
+    // With `--changed-files`, only tests affected by the changes are run.
+    if let Some(changed) = &state.changed_files {
+        let len_before = tests.len() as u64;
+        tests.retain(|test| {
+            test.info.source_files.is_empty()
+                || test.info.source_files.iter().any(|file| changed.contains(file))
+        });
+        conclusion.num_filtered_out += len_before - tests.len() as u64;
+    }
+
+    // If `--print-config` is specified, just print the configuration and
+    // return.
+    if args.print_config {
+        let num_threads = match runs_sequentially(args, &state.invariants, &tests) {
+            true => "1 (sequentially on the main thread)".into(),
+            false => num_pool_threads(args).to_string(),
+        };
+        let num_threads = match (args.test_threads, std::env::var_os("RUST_TEST_THREADS")) {
+            (None, Some(_)) => format!("{} (from RUST_TEST_THREADS)", num_threads),
+            _ => num_threads,
+        };
+        let patterns = |patterns: &[String]| match patterns.is_empty() {
+            true => "(none)".into(),
+            false => patterns.iter().map(|p| format!("{:?}", p)).collect::<Vec<_>>().join(", "),
+        };
+        let ignored = if args.ignored {
+            "only ignored"
+        } else if args.include_ignored {
+            "included"
+        } else if args.run_ignored_soft {
+            "included (failures don't count)"
+        } else {
+            "skipped"
+        };
+        let features = [
+            ("regex", cfg!(feature = "regex")),
+            ("async", cfg!(feature = "async")),
+            ("sigint", cfg!(feature = "sigint")),
+        ];
+        let features = features.iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>();
+        let features = if features.is_empty() { "(none)".into() } else { features.join(", ") };
+        printer.print_config(args.color.unwrap_or_default(), &[
+            ("test threads", num_threads),
+            ("mode", if args.bench { "benchmarks" } else { "tests" }.into()),
+            ("filters", patterns(state.filters.filters())),
+            ("skip", patterns(state.filters.skip_patterns())),
+            ("skip exact", patterns(state.filters.skip_exact_patterns())),
+            ("exact", state.filters.is_exact().to_string()),
+            ("filter regex", state.filters.is_regex().to_string()),
+            ("ignored tests", ignored.into()),
+            ("selected", format!("{} of {} tests", tests.len(), len_before)),
+            ("features", features),
+        ]);
+        return Ok(Conclusion::empty());
+    }
+
+    // With `--no-run`, only print how many tests would run.
+    if args.no_run && !args.list {
+        let num_ignored = tests.iter().filter(|test| args.is_ignored(test)).count() as u64;
+        let num_run = tests.len() as u64 - num_ignored;
+        printer.print_no_run(num_run, num_ignored, conclusion.num_filtered_out);
+        return Ok(conclusion);
+    }
+
+    // A timed out test keeps running in the background, but its temporary
+    // working directory would be restored and removed right away.
+    if args.timeout.is_some() && !args.list {
+        if let Some(test) = tests.iter().find(|test| test.info.temp_cwd && !args.is_ignored(test)) {
+            return Err(invalid_input(format!(
+                "`--timeout` cannot be used with tests that run in a temporary working \
+                    directory (see `Trial::with_temp_cwd`), like `{}`",
+                test.info.name,
+            )));
+        }
+    }
+
+    // With `--pick` and `--confirm`, the user can narrow down the tests even
+    // further or abort the run altogether.
+    if (args.pick || args.confirm) && !args.list {
         let len_before = tests.len() as u64;
-        tests.retain(|test| !args.is_filtered_out(test));
-        conclusion.num_filtered_out = len_before - tests.len() as u64;
+        let mut confirmation = interactive::Confirmation::Run;
+        if args.pick {
+            confirmation = interactive::pick(&mut tests)?;
+        }
+        if args.confirm && matches!(confirmation, interactive::Confirmation::Run) {
+            confirmation = interactive::confirm(&mut tests);
+        }
+        conclusion.num_filtered_out += len_before - tests.len() as u64;
+        if let interactive::Confirmation::Abort = confirmation {
+            if let Some(all_names) = all_names {
+                let not_run = tests.iter()
+                    .map(|test| test.info.test_name_with_kind().into_owned())
+                    .collect::<Vec<_>>();
+                state.manifest.extend(manifest::entries(all_names, HashMap::new(), &not_run));
+            }
+            conclusion.num_not_run = tests.len() as u64;
+            conclusion.not_run = tests.into_iter().map(|test| test.info.name).collect();
+            return Ok(conclusion);
+        }
+    }
+
+    let shuffle_seed = match args.shuffle_seed {
+        Some(seed) => Some(seed),
+        None if args.shuffle => Some(state.seed.expect("no seed with `--shuffle`")),
+        None => None,
+    };
+    if let (Some(seed), false) = (shuffle_seed, args.list) {
+        rng::Rng::new(seed, rng::Subsystem::Shuffle).shuffle(&mut tests);
+    }
+    if let Some(order) = &state.replay_order {
+        order.apply(&mut tests);
+    }
+
+    // With `--time-budget`, the tests with the smallest estimates are run
+    // first, so that as many as possible fit. Tests without estimate go last.
+    let time_budget = state.time_budget;
+    if time_budget.is_some() && !args.list {
+        let estimate = |test: &Trial| test.info.estimated_duration;
+        tests.sort_by_key(|test| (estimate(test).is_none(), estimate(test)));
     }
     let tests = tests;
 
-    // Create printer which is used for all output.
-    let mut printer = printer::Printer::new(args, &tests);
+    printer.set_name_widths(&tests);
 
     // If `--list` is specified, just print the list and return.
     if args.list {
         printer.print_list(&tests, args.ignored);
-        return Conclusion::empty();
+        return Ok(Conclusion::empty());
     }
 
     // Print number of tests
-    printer.print_title(tests.len() as u64);
+    let num_tests = tests.len() as u64;
+    printer.print_title(num_tests);
+    if let Some(seed) = state.seed {
+        printer.print_seed(seed);
+    }
+    if let Some(seed) = shuffle_seed {
+        printer.print_shuffle_seed(seed);
+    }
+
+    // With `--resume`, tests that finished in the interrupted run are not run
+    // again. With `--skip-unchanged`, tests that passed before with the same
+    // fingerprint are not run again.
+    let known_outcomes = tests.iter()
+        .map(|test| match state.resumed.get(test.info.test_name_with_kind().as_ref()) {
+            Some(outcome) => Some(outcome.clone()),
+            None => cache.as_ref()
+                .is_some_and(|cache| cache.is_cached(test))
+                .then_some(Outcome::Cached),
+        })
+        .collect::<Vec<_>>();
 
     let mut failed_tests = Vec::new();
-    let mut handle_outcome = |outcome: Outcome, test: TestInfo, printer: &mut Printer| {
-        printer.print_single_outcome(&test, &outcome);
+    let mut ignored_failed_tests = Vec::new();
+    let mut durations = Vec::new();
+    let mut ignored_names = Vec::new();
+    let mut executed = HashMap::new();
+    let mut not_run: Vec<TestInfo> = Vec::new();
+    let mut captured = HashMap::new();
+    let mut handle_outcome = |
+        outcome: Outcome,
+        test: TestInfo,
+        timing: Option<Timing>,
+        attempts: u32,
+        output: Option<String>,
+        printer: &mut Printer<'_>,
+    | {
+        let output = output
+            .filter(|output| !output.is_empty())
+            .filter(|_| matches!(outcome, Outcome::Failed(_) | Outcome::TimedOut(_)));
+        printer.print_single_outcome(&test, &outcome, timing.as_ref(), attempts, output.as_deref());
+        if let Some(output) = output {
+            captured.insert(test.test_name_with_kind().into_owned(), output);
+        }
+        printer.print_progress();
+        if all_names.is_some() {
+            executed.insert(
+                test.test_name_with_kind().into_owned(),
+                (flakiness::status_of(&outcome), timing.map(|t| t.duration)),
+            );
+        }
+        if let (Some(_), Some(timing)) = (&args.profile, &timing) {
+            profile.push((test.test_name_with_kind().into_owned(), *timing));
+        }
+        if let (Some(_), Some(timing)) = (args.slowest, &timing) {
+            durations.push((test.test_name_with_kind().into_owned(), timing.duration));
+        }
+        if args.results_file.is_some() {
+            results.push((test.test_name_with_kind().into_owned(), flakiness::status_of(&outcome)));
+        }
+        if let Some(cache) = cache.as_mut() {
+            cache.update(&test, &outcome);
+        }
+        if let Some(checkpoint) = checkpoint.as_mut() {
+            checkpoint.record(&test, &outcome);
+        }
+        conclusion.results.push((test.name.clone(), outcome.clone()));
+        if let Some(on_result) = on_result {
+            on_result(&test, &outcome);
+        }
+
+        if let Some(module) = &test.module {
+            let counts = conclusion.module_counts_mut(module);
+            match &outcome {
+                Outcome::Failed(_) | Outcome::TimedOut(_) if !args.is_soft(&test) => {
+                    counts.num_failed += 1;
+                }
+                Outcome::Failed(_) | Outcome::TimedOut(_) | Outcome::Ignored => {
+                    counts.num_ignored += 1;
+                }
+                Outcome::Passed | Outcome::Measured(_) | Outcome::Cached => counts.num_passed += 1,
+            }
+        }
 
         // Handle outcome
+        // From here on, a timeout is treated like any other failure.
+        let outcome = match outcome {
+            Outcome::TimedOut(timeout) => Outcome::Failed(timeout_message(timeout).into()),
+            other => other,
+        };
         match outcome {
-            Outcome::Passed => conclusion.num_passed += 1,
+            Outcome::Passed => {
+                conclusion.num_passed += 1;
+                if attempts > 1 {
+                    conclusion.num_flaky += 1;
+                }
+            },
+            Outcome::Failed(failed) if args.is_soft(&test) => {
+                ignored_failed_tests.push((test, failed.msg));
+                conclusion.num_ignored_failed += 1;
+            },
             Outcome::Failed(failed) => {
                 failed_tests.push((test, failed.msg));
                 conclusion.num_failed += 1;
             },
-            Outcome::Ignored => conclusion.num_ignored += 1,
-            Outcome::Measured(_) => conclusion.num_measured += 1,
+            Outcome::TimedOut(_) => unreachable!(),
+            Outcome::Ignored => {
+                if args.list_ignored_after {
+                    let name = test.test_name_with_kind().into_owned();
+                    ignored_names.push((name, test.ignored_reason.clone()));
+                }
+                conclusion.num_ignored += 1;
+            },
+            Outcome::Measured(measurement) => {
+                conclusion.measurements.push((test.name, measurement));
+                conclusion.num_measured += 1;
+            },
+            Outcome::Cached => conclusion.num_cached += 1,
         }
+        conclusion.num_failed
     };
 
-    // Execute all tests.
+    // With `--max-failures`, the run is aborted once that many tests failed.
+    // After that, only tests marked as "always run" are still executed.
+    let max_failures = args.max_failures.or(args.fail_fast.then_some(1));
+    let is_aborted = |num_failed: u64| max_failures.is_some_and(|max| num_failed >= max);
+
+    // Tests that are not "always run" are not started anymore once the run is
+    // aborted or if they (likely) don't fit into the `--time-budget` anymore.
+    // No test at all is started after the `--deadline-epoch`.
+    let exec_start = Instant::now();
+    let deadline = args.deadline_epoch.map(|ms| UNIX_EPOCH + Duration::from_millis(ms));
+    let num_over_budget = Cell::new(0);
+    let num_past_deadline = Cell::new(0);
+    let num_interrupted = Cell::new(0);
+    let interrupt = &state.interrupt;
+    let should_skip = |test: &Trial, num_failed: u64| {
+        if interrupt.iter().any(|flag| flag.load(atomic::Ordering::SeqCst)) {
+            num_interrupted.set(num_interrupted.get() + 1);
+            return true;
+        }
+        if deadline.is_some_and(|deadline| SystemTime::now() >= deadline) {
+            num_past_deadline.set(num_past_deadline.get() + 1);
+            return true;
+        }
+        if test.info.always_run {
+            return false;
+        }
+        let estimate = test.info.estimated_duration.unwrap_or_default();
+        let over_budget = time_budget
+            .is_some_and(|budget| exec_start.elapsed() + estimate > budget);
+        if over_budget && !is_aborted(num_failed) {
+            num_over_budget.set(num_over_budget.get() + 1);
+        }
+        is_aborted(num_failed) || over_budget
+    };
+
+    // Execute all tests, sequentially if required (see `runs_sequentially`).
     let test_mode = !args.bench;
-    if platform_defaults_to_one_thread() || args.test_threads == Some(1) {
+    let watchdog = args.timeout.map(|timeout| Watchdog {
+        timeout,
+        stack_size: args.thread_stack_size.or(state.thread_stack_size),
+    });
+    let recorder = &state.recorder;
+    let hooks = &state.hooks;
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sequential = runs_sequentially(args, &state.invariants, &tests);
+    let after_all = hooks.around_all();
+    if sequential {
         // Run test sequentially in main thread
-        for test in tests {
+        if let Some(init) = &state.thread_hooks.init {
+            init();
+        }
+        // Output is only captured here, as it can't be attributed to tests
+        // running concurrently in the pool.
+        let capture_enabled = args.capture_setting() != CaptureSetting::None;
+        let mut num_failed = 0;
+        for (test, known_outcome) in tests.into_iter().zip(known_outcomes) {
+            if should_skip(&test, num_failed) {
+                not_run.push(test.info);
+                continue;
+            }
+
             // Print `test foo    ...`, run the test, then print the outcome in
             // the same line.
             printer.print_test(&test.info);
-            let outcome = if args.is_ignored(&test) {
-                Outcome::Ignored
+            let (outcome, timing, attempts, output) = if args.is_ignored(&test) {
+                (Outcome::Ignored, None, 1, None)
+            } else if let Some(outcome) = known_outcome {
+                (outcome, None, 1, None)
             } else {
-                run_single(test.runner, test_mode)
+                if let Some(recorder) = recorder {
+                    recorder.record(test.info.test_name_with_kind().into_owned());
+                }
+                let name = test.info.test_name_with_kind();
+                let spinner = printer.start_spinner();
+                let ((outcome, timing, attempts), output) = capture::capture(capture_enabled, || {
+                    run_hooked(hooks, &test.info, || {
+                        run_with_retries(
+                            test.runner,
+                            test.repeatable,
+                            &test.info,
+                            args.retries,
+                            test_mode,
+                            watchdog,
+                        )
+                    })
+                });
+                drop(spinner);
+                collect_warnings(&warnings, &name);
+                let outcome = check_invariants(&state.invariants, outcome);
+                (outcome, Some(timing), attempts, output)
             };
-            handle_outcome(outcome, test.info, &mut printer);
+            num_failed = handle_outcome(outcome, test.info, timing, attempts, output, printer);
+        }
+        if let Some(teardown) = &state.thread_hooks.teardown {
+            teardown();
         }
     } else {
         // Run test in thread pool.
-        let pool = match args.test_threads {
-            Some(num_threads) => ThreadPool::new(num_threads),
-            None => ThreadPool::default()
-        };
+        let mut builder = threadpool::Builder::new().num_threads(num_pool_threads(args));
+        if let Some(stack_size) = args.thread_stack_size.or(state.thread_stack_size) {
+            builder = builder.thread_stack_size(stack_size);
+        }
+        let pool = builder.build();
         let (sender, receiver) = mpsc::channel();
+        if let Some(init) = &state.thread_hooks.init {
+            on_each_worker(&pool, init);
+        }
 
-        let num_tests = tests.len();
-        for test in tests {
-            if args.is_ignored(&test) {
-                sender.send((Outcome::Ignored, test.info)).unwrap();
-            } else {
+        // Tests are only dispatched to the pool when a worker is free, so
+        // that we can stop dispatching once the run is aborted. With
+        // `--chunk-size`, each pool job runs several tests to reduce the
+        // scheduling overhead for tiny tests.
+        let chunk_size = args.chunk_size.unwrap_or(1).max(1);
+        let mut remaining = tests.into_iter().zip(known_outcomes);
+        let mut num_in_flight = 0;
+        let mut num_failed = 0;
+        loop {
+            while num_in_flight < pool.max_count() {
+                let mut chunk = Vec::new();
+                while chunk.len() < chunk_size {
+                    let Some((test, known_outcome)) = remaining.next() else { break };
+                    if should_skip(&test, num_failed) {
+                        not_run.push(test.info);
+                    } else if args.is_ignored(&test) {
+                        num_in_flight += 1;
+                        sender.send(vec![(Outcome::Ignored, test.info, None, 1)]).unwrap();
+                    } else if let Some(outcome) = known_outcome {
+                        num_in_flight += 1;
+                        sender.send(vec![(outcome, test.info, None, 1)]).unwrap();
+                    } else {
+                        chunk.push(test);
+                    }
+                }
+                if chunk.is_empty() {
+                    break;
+                }
+
+                num_in_flight += 1;
                 let sender = sender.clone();
+                let recorder = recorder.clone();
+                let hooks = hooks.clone();
+                let warnings = Arc::clone(&warnings);
+                let retries = args.retries;
                 pool.execute(move || {
+                    let outcomes = chunk.into_iter()
+                        .map(|test| {
+                            let name = test.info.test_name_with_kind();
+                            if let Some(recorder) = &recorder {
+                                recorder.record(name.clone().into_owned());
+                            }
+                            let (outcome, timing, attempts) = run_hooked(&hooks, &test.info, || {
+                                run_with_retries(
+                                    test.runner,
+                                    test.repeatable,
+                                    &test.info,
+                                    retries,
+                                    test_mode,
+                                    watchdog,
+                                )
+                            });
+                            collect_warnings(&warnings, &name);
+                            (outcome, test.info, Some(timing), attempts)
+                        })
+                        .collect::<Vec<_>>();
+
                     // It's fine to ignore the result of sending. If the
                     // receiver has hung up, everything will wind down soon
                     // anyway.
-                    let outcome = run_single(test.runner, test_mode);
-                    let _ = sender.send((outcome, test.info));
+                    let _ = sender.send(outcomes);
                 });
             }
-        }
 
-        for (outcome, test_info) in receiver.iter().take(num_tests) {
+            if num_in_flight == 0 {
+                break;
+            }
+
             // In multithreaded mode, we do only print the start of the line
             // after the test ran, as otherwise it would lead to terribly
             // interleaved output.
-            printer.print_test(&test_info);
-            handle_outcome(outcome, test_info, &mut printer);
+            let outcomes = receiver.recv().unwrap();
+            num_in_flight -= 1;
+            for (outcome, test_info, timing, attempts) in outcomes {
+                printer.print_test(&test_info);
+                num_failed = handle_outcome(outcome, test_info, timing, attempts, None, printer);
+            }
+        }
+        if let Some(teardown) = &state.thread_hooks.teardown {
+            on_each_worker(&pool, teardown);
         }
     }
 
+    drop(after_all);
+
+    if let Some(all_names) = all_names {
+        let not_run = not_run.iter()
+            .map(|test| test.test_name_with_kind().into_owned())
+            .collect::<Vec<_>>();
+        state.manifest.extend(manifest::entries(all_names, executed, &not_run));
+    }
+    if let Some(budget) = time_budget {
+        printer.print_time_budget(num_tests - num_over_budget.get(), num_tests, budget);
+    }
+    if num_past_deadline.get() > 0 {
+        printer.print_deadline_reached(num_past_deadline.get());
+    }
+    if num_interrupted.get() > 0 {
+        printer.print_interrupted(num_interrupted.get());
+        conclusion.interrupted = true;
+    }
+    let num_aborted = not_run.len() as u64
+        - num_over_budget.get()
+        - num_past_deadline.get()
+        - num_interrupted.get();
+    if num_aborted > 0 {
+        printer.print_aborted(num_aborted);
+    }
+    if !not_run.is_empty() {
+        conclusion.num_not_run = not_run.len() as u64;
+        conclusion.not_run = not_run.into_iter().map(|test| test.name).collect();
+    }
+
     // Print failures if there were any, and the final summary.
+    if !ignored_failed_tests.is_empty() {
+        printer.print_failures(true, &ignored_failed_tests, &captured);
+    }
     if !failed_tests.is_empty() {
-        printer.print_failures(&failed_tests);
+        printer.print_failures(false, &failed_tests, &captured);
+    }
+    // Tests finish in arbitrary order in the pool, so sort by name.
+    ignored_names.sort();
+    printer.print_ignored_list(&ignored_names);
+    printer.print_module_counts(&conclusion.modules);
+    let mut warnings = std::mem::take(&mut *warnings.lock().unwrap_or_else(|e| e.into_inner()));
+    if !warnings.is_empty() {
+        // Sort by test name, as tests finish in arbitrary order in the pool.
+        warnings.sort_by(|a: &(String, String), b| a.0.cmp(&b.0));
+        printer.print_warnings(&warnings);
+        conclusion.num_warnings = warnings.len() as u64;
+    }
+    if let Some(threshold) = args.bench_unstable_threshold {
+        let mut unstable = conclusion.measurements.iter()
+            .map(|(name, m)| (name.as_str(), m.coefficient_of_variation()))
+            .filter(|(_, cv)| *cv * 100.0 > threshold)
+            .collect::<Vec<_>>();
+        unstable.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        printer.print_unstable_benches(&unstable);
+    }
+    if let Some(n) = args.slowest {
+        timing::sort_by_duration(&mut durations);
+        durations.truncate(n);
+        printer.print_slowest(&durations);
+    }
+    let num_snapshot_failures = failed_tests.iter().filter(|(test, _)| test.is_snapshot).count();
+    if num_snapshot_failures > 0 {
+        printer.print_snapshot_hint(num_snapshot_failures);
     }
 
-    printer.print_summary(&conclusion, start_instant.elapsed());
+    conclusion.total_duration = start_instant.elapsed();
+    printer.print_summary(&conclusion, conclusion.total_duration);
+
+    if args.bisect_verbose && !failed_tests.is_empty() && !rerun::is_rerun() {
+        let names = failed_tests.iter().map(|(test, _)| test.name.clone()).collect::<Vec<_>>();
+        printer.print_verbose_reruns(&rerun::rerun_verbose(&names));
+    }
+
+    // The digest goes to stderr, independent of the output format.
+    if args.failure_digest {
+        for (test, msg) in &failed_tests {
+            let first_line = msg.as_deref()
+                .and_then(|msg| printer::strip_ansi(msg).lines().next().map(str::to_owned));
+            match first_line {
+                Some(line) => eprintln!("FAIL {}: {}", test.test_name_with_kind(), line),
+                None => eprintln!("FAIL {}", test.test_name_with_kind()),
+            }
+        }
+    }
+
+    Ok(conclusion)
+}
+
+/// Returns whether the tests have to run sequentially on the main thread:
+/// when replaying an order, to actually reproduce that order, and if any
+/// invariant is set or test changes the (process-global) working directory.
+fn runs_sequentially(
+    args: &Arguments,
+    invariants: &[(String, config::Invariant)],
+    tests: &[Trial],
+) -> bool {
+    platform_defaults_to_one_thread()
+        || requested_test_threads(args) == Some(1)
+        || args.replay_order.is_some()
+        || !invariants.is_empty()
+        || tests.iter().any(|test| test.info.temp_cwd)
+}
+
+/// Returns the number of worker threads if tests don't run sequentially:
+/// the requested number or the number of logical CPUs.
+fn num_pool_threads(args: &Arguments) -> usize {
+    requested_test_threads(args).unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    })
+}
+
+/// Returns the number of threads requested via `--test-threads` or, if that
+/// is not set, the `RUST_TEST_THREADS` environment variable (like libtest).
+/// Invalid values were already rejected by `RunState::new`.
+fn requested_test_threads(args: &Arguments) -> Option<usize> {
+    parse_test_threads(args).ok().flatten()
+}
+
+fn parse_test_threads(args: &Arguments) -> Result<Option<usize>, String> {
+    if let Some(num_threads) = args.test_threads {
+        if num_threads == 0 {
+            return Err("argument for `--test-threads` must not be 0".into());
+        }
+        return Ok(Some(num_threads));
+    }
 
-    conclusion
+    let Ok(value) = std::env::var("RUST_TEST_THREADS") else { return Ok(None) };
+    match value.parse() {
+        Ok(num_threads) if num_threads > 0 => Ok(Some(num_threads)),
+        _ => Err(format!("RUST_TEST_THREADS is `{}`, should be a positive integer", value)),
+    }
 }
 
 /// Returns whether the current host platform should use a single thread by
@@ -544,22 +2530,217 @@ fn platform_defaults_to_one_thread() -> bool {
     cfg!(target_family = "wasm")
 }
 
-/// Runs the given runner, catching any panics and treating them as a failed test.
-fn run_single(runner: Box<dyn FnOnce(bool) -> Outcome + Send>, test_mode: bool) -> Outcome {
-    use std::panic::{catch_unwind, AssertUnwindSafe};
+/// Implements `--bench-iterations`: the runners of benchmarks that can be run
+/// several times are replaced by ones running them `n` times and combining
+/// the measurements (see `Measurement::combine`). The first failure ends the
+/// iterations.
+fn repeat_benches(tests: &mut [Trial], n: u32) {
+    for test in tests.iter_mut().filter(|test| test.info.is_bench) {
+        let Some(repeatable) = test.repeatable.clone() else { continue };
+        test.runner = Box::new(move |test_mode| {
+            let mut runs = Vec::new();
+            for _ in 0..n {
+                match repeatable(test_mode) {
+                    Outcome::Measured(measurement) => runs.push(measurement),
+                    other => return other,
+                }
+            }
+            Outcome::Measured(Measurement::combine(&runs))
+        });
+    }
+}
 
-    catch_unwind(AssertUnwindSafe(move || runner(test_mode))).unwrap_or_else(|e| {
-        // The `panic` information is just an `Any` object representing the
-        // value the panic was invoked with. For most panics (which use
-        // `panic!` like `println!`), this is either `&str` or `String`.
-        let payload = e.downcast_ref::<String>()
-            .map(|s| s.as_str())
-            .or(e.downcast_ref::<&str>().map(|s| *s));
+/// Marks the test with the given `outcome` as failed if any of the
+/// invariants (see `RunConfig::with_invariant`) is violated after it ran.
+fn check_invariants(invariants: &[(String, config::Invariant)], outcome: Outcome) -> Outcome {
+    // After a timeout, the test might still be running.
+    if matches!(outcome, Outcome::TimedOut(_)) {
+        return outcome;
+    }
+    let Some((name, _)) = invariants.iter().find(|(_, check)| !check()) else {
+        return outcome;
+    };
+    let violation = format!("invariant '{name}' violated");
+    match outcome {
+        Outcome::Failed(Failed { msg: Some(msg) }) => {
+            Outcome::Failed(format!("{msg}\n{violation}").into())
+        }
+        _ => Outcome::Failed(violation.into()),
+    }
+}
 
-        let msg = match payload {
-            Some(payload) => format!("test panicked: {payload}"),
-            None => format!("test panicked"),
-        };
-        Outcome::Failed(msg.into())
-    })
+/// Moves all warnings emitted on this thread (see `Context::warn`) by the
+/// test with the given name to `all`.
+fn collect_warnings(all: &Mutex<Vec<(String, String)>>, name: &str) {
+    let warnings = context::take_warnings();
+    if !warnings.is_empty() {
+        let mut all = all.lock().unwrap_or_else(|e| e.into_inner());
+        all.extend(warnings.into_iter().map(|msg| (name.to_owned(), msg)));
+    }
+}
+
+/// Configuration of the watchdog enforcing `--timeout`.
+#[derive(Debug, Clone, Copy)]
+struct Watchdog {
+    timeout: Duration,
+
+    /// Stack size of the helper thread running the test.
+    stack_size: Option<usize>,
+}
+
+/// Returns the failure message of `Outcome::TimedOut`.
+pub(crate) fn timeout_message(timeout: Duration) -> String {
+    format!("test exceeded timeout of {}s", timeout.as_secs_f64())
+}
+
+/// Calls `f` (running the test), surrounded by the per-test hooks (see
+/// `TestHooks::around`). If a hook panics, the test fails with the panic
+/// message instead of taking down the thread running it.
+fn run_hooked(
+    hooks: &config::TestHooks,
+    info: &TestInfo,
+    f: impl FnOnce() -> (Outcome, Timing, u32),
+) -> (Outcome, Timing, u32) {
+    let (result, timing) = Timing::measure(|| panic_hook::catch(|| hooks.around(info, f)));
+    match result {
+        Ok(out) => out,
+        Err((payload, location)) => (panic_outcome(payload, location), timing, 1),
+    }
+}
+
+/// Runs the given test via `run_single`. If it fails or times out, it is run
+/// again up to `retries` times (see `--retries`), unless it is a benchmark or
+/// its runner can only be called once (i.e. it was not created via
+/// `Trial::test_repeatable`). Returns the outcome of the last attempt, the
+/// timing of all attempts together and the number of attempts.
+fn run_with_retries(
+    runner: Box<dyn FnOnce(bool) -> Outcome + Send>,
+    repeatable: Option<Arc<dyn Fn(bool) -> Outcome + Send + Sync>>,
+    info: &TestInfo,
+    retries: u32,
+    test_mode: bool,
+    watchdog: Option<Watchdog>,
+) -> (Outcome, Timing, u32) {
+    let wrap = |runner| if info.temp_cwd { temp_cwd::wrap(runner) } else { runner };
+    let (mut outcome, mut timing) = run_single(wrap(runner), test_mode, watchdog);
+    let Some(repeatable) = repeatable.filter(|_| !info.is_bench) else {
+        return (outcome, timing, 1);
+    };
+
+    let mut attempts = 1;
+    while attempts <= retries && matches!(outcome, Outcome::Failed(_) | Outcome::TimedOut(_)) {
+        let repeatable = Arc::clone(&repeatable);
+        let runner = wrap(Box::new(move |test_mode| repeatable(test_mode)));
+        let (retry_outcome, retry_timing) = run_single(runner, test_mode, watchdog);
+        outcome = retry_outcome;
+        timing.end = retry_timing.end;
+        timing.duration += retry_timing.duration;
+        attempts += 1;
+    }
+    let outcome = match outcome {
+        Outcome::Failed(failed) if attempts > 1 => failed_after_attempts(failed, attempts),
+        outcome => outcome,
+    };
+    (outcome, timing, attempts)
+}
+
+/// Appends the number of attempts to the message of a test that failed
+/// after retries.
+fn failed_after_attempts(Failed { msg }: Failed, attempts: u32) -> Outcome {
+    let note = format!("(failed after {} attempts)", attempts);
+    let msg = match msg {
+        Some(msg) => format!("{}\n{}", msg, note),
+        None => note,
+    };
+    Outcome::Failed(msg.into())
+}
+
+/// Runs the given runner, catching any panics and treating them as a failed
+/// test. Also measures when and how long the runner ran.
+///
+/// With a `watchdog`, the runner is run on a new helper thread and the test
+/// times out if it does not finish in time. The helper thread cannot be
+/// stopped and keeps running in the background in that case.
+fn run_single(
+    runner: Box<dyn FnOnce(bool) -> Outcome + Send>,
+    test_mode: bool,
+    watchdog: Option<Watchdog>,
+) -> (Outcome, Timing) {
+    let run = move || panic_hook::catch(move || runner(test_mode));
+    let (result, timing) = Timing::measure(|| {
+        let Some(watchdog) = watchdog else { return Some(run()) };
+
+        let (sender, receiver) = mpsc::channel();
+        let mut builder = std::thread::Builder::new();
+        if let Some(stack_size) = watchdog.stack_size {
+            builder = builder.stack_size(stack_size);
+        }
+        let sink = capture::current_sink();
+        builder
+            .spawn(move || {
+                capture::set_sink(sink);
+                let result = run();
+                let _ = sender.send((result, context::take_warnings()));
+            })
+            .expect("failed to spawn test thread");
+        match receiver.recv_timeout(watchdog.timeout) {
+            Ok((result, warnings)) => {
+                context::add_warnings(warnings);
+                Some(result)
+            }
+            Err(_) => None,
+        }
+    });
+    let Some(result) = result else {
+        return (Outcome::TimedOut(watchdog.unwrap().timeout), timing);
+    };
+    let outcome = result.unwrap_or_else(|(payload, location)| panic_outcome(payload, location));
+    (outcome, timing)
+}
+
+/// Calls `f` exactly once on each worker thread of `pool` and waits for all
+/// calls to return. A panic in `f` is resumed on the calling thread.
+fn on_each_worker(pool: &threadpool::ThreadPool, f: &config::ThreadHook) {
+    // Every job blocks on the barrier until all of them started, so no worker
+    // can pick up two of them.
+    let num_workers = pool.max_count();
+    let barrier = Arc::new(Barrier::new(num_workers + 1));
+    let panic = Arc::new(Mutex::new(None));
+    for _ in 0..num_workers {
+        let f = Arc::clone(f);
+        let barrier = Arc::clone(&barrier);
+        let panic = Arc::clone(&panic);
+        pool.execute(move || {
+            if let Err(payload) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f())) {
+                *panic.lock().unwrap_or_else(|e| e.into_inner()) = Some(payload);
+            }
+            barrier.wait();
+        });
+    }
+    barrier.wait();
+
+    let payload = panic.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(payload) = payload {
+        std::panic::resume_unwind(payload);
+    }
+}
+
+/// Turns a caught panic into a failure, with the panic message and location
+/// (if known) as message.
+fn panic_outcome(payload: Box<dyn std::any::Any + Send>, location: Option<String>) -> Outcome {
+    // The `panic` information is just an `Any` object representing the
+    // value the panic was invoked with. For most panics (which use
+    // `panic!` like `println!`), this is either `&str` or `String`.
+    let payload = payload.downcast_ref::<String>()
+        .map(|s| s.as_str())
+        .or(payload.downcast_ref::<&str>().copied());
+
+    let mut msg = match payload {
+        Some(payload) => format!("test panicked: {payload}"),
+        None => "test panicked".to_string(),
+    };
+    if let Some(location) = location {
+        msg += &format!("\n  --> {location}");
+    }
+    Outcome::Failed(msg.into())
 }
@@ -0,0 +1,45 @@
+//! Writing a manifest of the final state of every test (`--manifest`). The
+//! format is documented at `Arguments::manifest`.
+
+use std::{collections::HashMap, fs, io, time::Duration};
+
+/// The final state of a single test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ManifestEntry {
+    pub(crate) name: String,
+    pub(crate) status: &'static str,
+    pub(crate) duration: Option<Duration>,
+}
+
+/// Creates the manifest entries for all tests of a single run of `run_tests`.
+/// `all_names` are all tests in their original order. Tests that are neither
+/// in `executed` nor in `not_run` were filtered out.
+pub(crate) fn entries(
+    all_names: Vec<String>,
+    mut executed: HashMap<String, (&'static str, Option<Duration>)>,
+    not_run: &[String],
+) -> Vec<ManifestEntry> {
+    all_names.into_iter()
+        .map(|name| {
+            let (status, duration) = match executed.remove(&name) {
+                Some(result) => result,
+                None if not_run.contains(&name) => ("not-run", None),
+                None => ("filtered", None),
+            };
+            ManifestEntry { name, status, duration }
+        })
+        .collect()
+}
+
+/// Writes the given entries to a manifest file.
+pub(crate) fn write(path: &str, entries: &[ManifestEntry]) -> io::Result<()> {
+    let mut out = String::new();
+    for entry in entries {
+        let duration = match entry.duration {
+            Some(duration) => duration.as_millis().to_string(),
+            None => "-".into(),
+        };
+        out.push_str(&format!("{}\t{}\t{}\n", entry.name, entry.status, duration));
+    }
+    fs::write(path, out)
+}
@@ -0,0 +1,87 @@
+//! Output as Markdown document (`--format markdown`), e.g. for PR comments.
+//!
+//! Nothing is printed while tests run. After the run, a header with the
+//! totals, a table of all tests (sorted by name) and a collapsible section
+//! with the messages of all failures are printed.
+
+use std::{fmt::Write, time::Duration};
+
+use crate::{printer::strip_ansi, Conclusion};
+
+/// Collects the results of a run to print them as Markdown at the end.
+#[derive(Debug, Default)]
+pub(crate) struct Report {
+    /// `(test name, status, duration)` of each test.
+    rows: Vec<(String, String, Option<Duration>)>,
+
+    /// `(test name, message)` of each failed test.
+    failures: Vec<(String, Option<String>)>,
+}
+
+impl Report {
+    pub(crate) fn add(&mut self, name: String, status: String, duration: Option<Duration>) {
+        self.rows.push((name, status, duration));
+    }
+
+    pub(crate) fn add_failure(&mut self, name: String, msg: Option<String>) {
+        self.failures.push((name, msg));
+    }
+
+    /// Renders the whole document and clears the collected results.
+    pub(crate) fn finish(&mut self, conclusion: &Conclusion, execution_time: Duration) -> String {
+        let mut rows = std::mem::take(&mut self.rows);
+        let mut failures = std::mem::take(&mut self.failures);
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        failures.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut out = String::new();
+        let result = if conclusion.has_failed() { "FAILED" } else { "ok" };
+        writeln!(out, "### Test results: {}\n", result).unwrap();
+        writeln!(
+            out,
+            "{} passed; {} failed; {} ignored; {} measured; {} filtered out; finished in {:.2}s\n",
+            conclusion.num_passed,
+            conclusion.num_failed,
+            conclusion.num_ignored,
+            conclusion.num_measured,
+            conclusion.num_filtered_out,
+            execution_time.as_secs_f64(),
+        ).unwrap();
+
+        if !rows.is_empty() {
+            writeln!(out, "| Test | Status | Duration |").unwrap();
+            writeln!(out, "| --- | --- | --- |").unwrap();
+            for (name, status, duration) in &rows {
+                let duration = match duration {
+                    Some(d) => format!("{:.2}s", d.as_secs_f64()),
+                    None => "-".into(),
+                };
+                writeln!(out, "| {} | {} | {} |", escape_cell(name), status, duration).unwrap();
+            }
+        }
+
+        if !failures.is_empty() {
+            writeln!(out, "\n<details>\n<summary>Failures ({})</summary>\n", failures.len())
+                .unwrap();
+            for (name, msg) in &failures {
+                writeln!(out, "#### {}\n", escape_cell(name)).unwrap();
+                if let Some(msg) = msg {
+                    let msg = strip_ansi(msg);
+                    let fence = "`".repeat(longest_backtick_run(&msg).max(2) + 1);
+                    writeln!(out, "{}text\n{}\n{}\n", fence, msg.trim_end(), fence).unwrap();
+                }
+            }
+            writeln!(out, "</details>").unwrap();
+        }
+        out
+    }
+}
+
+/// Escapes characters that would break a table cell.
+fn escape_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+fn longest_backtick_run(s: &str) -> usize {
+    s.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
@@ -0,0 +1,63 @@
+//! Recording and replaying the execution order of tests (`--record-order` and
+//! `--replay-order`).
+//!
+//! The file format is one test name (including the kind in brackets, if set)
+//! per line, in the order the tests were started.
+
+use std::{collections::HashMap, fs, io, sync::{Arc, Mutex}};
+
+use crate::Trial;
+
+/// Collects the names of tests in the order they are started. Can be shared
+/// with worker threads.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OrderRecorder {
+    names: Arc<Mutex<Vec<String>>>,
+}
+
+impl OrderRecorder {
+    /// Records that the test with the given name has just been started.
+    pub(crate) fn record(&self, name: String) {
+        self.names.lock().unwrap_or_else(|e| e.into_inner()).push(name);
+    }
+
+    /// Writes all recorded names to the given file.
+    pub(crate) fn write(&self, path: &str) -> io::Result<()> {
+        let names = self.names.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+        for name in names.iter() {
+            out.push_str(name);
+            out.push('\n');
+        }
+        fs::write(path, out)
+    }
+}
+
+/// An order read from a file written by `--record-order`.
+#[derive(Debug)]
+pub(crate) struct RecordedOrder {
+    positions: HashMap<String, usize>,
+}
+
+impl RecordedOrder {
+    pub(crate) fn read(path: &str) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let mut positions = HashMap::new();
+        for (i, line) in content.lines().filter(|l| !l.is_empty()).enumerate() {
+            positions.entry(line.to_owned()).or_insert(i);
+        }
+        Ok(Self { positions })
+    }
+
+    /// Sorts the given tests according to the recorded order. Tests that do
+    /// not appear in the recording are moved to the end, keeping their
+    /// relative order.
+    pub(crate) fn apply(&self, tests: &mut [Trial]) {
+        tests.sort_by_cached_key(|test| {
+            self.positions
+                .get(test.info.test_name_with_kind().as_ref())
+                .copied()
+                .unwrap_or(usize::MAX)
+        });
+    }
+}
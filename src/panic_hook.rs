@@ -0,0 +1,75 @@
+//! A panic hook that keeps panicking tests quiet (unless
+//! `--show-panic-immediately` is set).
+//!
+//! The default hook prints `thread '...' panicked at ...` to stderr for every
+//! panicking test, which interleaves messily when tests run in parallel.
+//! While a run is active, the hook installed here records the location of
+//! panics in tests instead, which then becomes part of the failure message.
+//! Panics outside of test runners are passed on to the previous hook.
+
+#![allow(deprecated)] // `PanicInfo` was renamed, but the new name needs Rust 1.81.
+
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    panic::{self, AssertUnwindSafe, PanicInfo},
+    sync::Mutex,
+};
+
+type Hook = Box<dyn Fn(&PanicInfo<'_>) + Sync + Send + 'static>;
+
+/// The number of active runs and the hook that was installed before the
+/// first of them started. Several runs can be active at the same time, e.g.
+/// when `run` is called from several `#[test]` functions.
+static INSTALLED: Mutex<(usize, Option<Hook>)> = Mutex::new((0, None));
+
+thread_local! {
+    /// Whether a test runner is currently executed on this thread.
+    static IN_TEST: Cell<bool> = const { Cell::new(false) };
+
+    /// The location of the last panic in a test on this thread.
+    static LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Installs the quiet hook, if it's not already installed. Has to be paired
+/// with a call to `uninstall`.
+pub(crate) fn install() {
+    let mut installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+    installed.0 += 1;
+    if installed.0 > 1 {
+        return;
+    }
+
+    installed.1 = Some(panic::take_hook());
+    panic::set_hook(Box::new(|info| {
+        if IN_TEST.with(Cell::get) {
+            let location = info.location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+            LOCATION.with(|slot| *slot.borrow_mut() = location);
+        } else if let Some(prev) = &INSTALLED.lock().unwrap_or_else(|e| e.into_inner()).1 {
+            prev(info);
+        }
+    }));
+}
+
+/// Restores the previous hook once the last active run finished.
+pub(crate) fn uninstall() {
+    let mut installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+    installed.0 -= 1;
+    if installed.0 == 0 {
+        if let Some(prev) = installed.1.take() {
+            panic::set_hook(prev);
+        }
+    }
+}
+
+/// Calls `f`, catching any panic. On panic, the payload is returned together
+/// with the location of the panic (if it was recorded by the quiet hook).
+pub(crate) fn catch<T>(f: impl FnOnce() -> T) -> Result<T, (Box<dyn Any + Send>, Option<String>)> {
+    // Calls can be nested, e.g. for `Trial::with_retry_if`.
+    let was_in_test = IN_TEST.with(|in_test| in_test.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    IN_TEST.with(|in_test| in_test.set(was_in_test));
+    let location = LOCATION.with(|slot| slot.borrow_mut().take());
+    result.map_err(|payload| (payload, location))
+}
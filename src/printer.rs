@@ -5,46 +5,180 @@
 //! - `color`
 //! - `format` (and `quiet`)
 //! - `logfile`
+//! - `progress`
 
-use std::{fs::File, time::Duration};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{self, IsTerminal},
+    sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex},
+    time::Duration,
+};
 
-use termcolor::{Ansi, Color, ColorChoice, ColorSpec, NoColor, StandardStream, WriteColor};
+use clap::ValueEnum;
+use termcolor::{Ansi, ColorChoice, ColorSpec, NoColor, StandardStream, WriteColor};
 
 use crate::{
-    Arguments, ColorSetting, Conclusion, Failed, FormatSetting, Measurement, Outcome, TestInfo,
-    Trial,
+    baseline::BaselineDiff,
+    config,
+    event_socket,
+    junit::{self, CaseResult},
+    markdown,
+    spinner::Spinner,
+    timing::{fmt_iso8601, Timing},
+    timeout_message, Arguments, ColorSetting, Conclusion, Failed, FormatSetting, Measurement,
+    ModuleCounts, Outcome,
+    ProgressSetting, RunConfig, SummaryDetail, TestInfo, Theme, Trial,
 };
 
-pub(crate) struct Printer {
-    out: Box<dyn WriteColor>,
+pub(crate) struct Printer<'out> {
+    out: Box<dyn WriteColor + 'out>,
     format: FormatSetting,
     name_width: usize,
     kind_width: usize,
+    terminal_width: Option<usize>,
+    max_failures_shown: Option<usize>,
+    timestamps: bool,
+    report_time: bool,
+    run_ignored_soft: bool,
+    ascii: bool,
+
+    /// Whether `--spinner` was passed and stdout is an interactive terminal.
+    spinner: bool,
+    summary_detail: SummaryDetail,
+    theme: Theme,
+    failure_header: String,
+    compact_failures: bool,
+    status_text: Option<config::StatusText>,
+
+    /// Never `Auto`, that is resolved on creation.
+    progress: ProgressSetting,
+    num_tests: u64,
+    num_finished: u64,
+
+    /// Whether the in-place progress line is currently on screen and has to
+    /// be cleared before printing anything else.
+    progress_shown: bool,
+
+    /// Printer in JSON mode that streams the main events to `--event-socket`.
+    events: Option<Box<Printer<'out>>>,
+
+    /// Results collected for `--format markdown`.
+    markdown: markdown::Report,
+
+    /// Results collected for `--format junit`.
+    junit: junit::Report,
+
+    /// Metadata of the run (see `--run-meta`), for JSON output.
+    run_metadata: Vec<(String, String)>,
+
+    /// Printer in JUnit mode that writes to Bazel's `XML_OUTPUT_FILE`.
+    xml_output: Option<Box<Printer<'out>>>,
 }
 
-impl Printer {
+impl<'out> Printer<'out> {
     /// Creates a new printer configured by the given arguments (`format`,
-    /// `quiet`, `color` and `logfile` options).
-    pub(crate) fn new(args: &Arguments, tests: &[Trial]) -> Self {
+    /// `quiet`, `color` and `logfile` options) and run configuration.
+    ///
+    /// # Panics
+    ///
+    /// If the logfile or the `XML_OUTPUT_FILE` cannot be created (see
+    /// `try_new`).
+    pub(crate) fn new(args: &Arguments, config: &RunConfig) -> Self {
+        Self::try_new(args, config).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `new`, but returns an error if the logfile or the
+    /// `XML_OUTPUT_FILE` cannot be created.
+    pub(crate) fn try_new(args: &Arguments, config: &RunConfig) -> io::Result<Self> {
+        let create = |path: &std::ffi::OsStr, what: &str| {
+            File::create(path).map_err(|e| {
+                let msg = format!("failed to create {} '{}': {}", what, path.to_string_lossy(), e);
+                io::Error::new(e.kind(), msg)
+            })
+        };
         let color_arg = args.color.unwrap_or(ColorSetting::Auto);
 
-        // Determine target of all output
-        let out = if let Some(logfile) = &args.logfile {
-            let f = File::create(logfile).expect("failed to create logfile");
+        // Determine target of all output and, if it's an interactive
+        // terminal, its width.
+        let mut terminal_width = None;
+        let mut interactive = false;
+        // The writer of the run config takes precedence over the logfile.
+        let redirected = match (&config.writer, &args.logfile) {
+            (Some(writer), _) => {
+                Some(Box::new(SharedOutput(Arc::clone(writer))) as Box<dyn io::Write>)
+            }
+            (None, Some(logfile)) => {
+                Some(Box::new(create(logfile.as_ref(), "logfile")?) as Box<dyn io::Write>)
+            }
+            (None, None) => None,
+        };
+        let out = if let Some(f) = redirected {
             if color_arg == ColorSetting::Always {
                 Box::new(Ansi::new(f)) as Box<dyn WriteColor>
             } else {
                 Box::new(NoColor::new(f))
             }
         } else {
+            // `ColorChoice::Auto` only checks `TERM` and `NO_COLOR`, so
+            // colors are explicitly disabled if stdout is not a terminal
+            // (e.g. piped to a file).
+            let is_terminal = std::io::stdout().is_terminal();
             let choice = match color_arg {
-                ColorSetting::Auto => ColorChoice::Auto,
+                ColorSetting::Auto if is_terminal => ColorChoice::Auto,
+                ColorSetting::Auto => ColorChoice::Never,
                 ColorSetting::Always => ColorChoice::Always,
                 ColorSetting::Never => ColorChoice::Never,
             };
+            interactive = is_terminal
+                && std::env::var_os("TERM").map_or(true, |term| term != "dumb");
+            if interactive {
+                terminal_width = terminal_size::terminal_size().map(|(w, _)| w.0 as usize);
+            }
             Box::new(StandardStream::stdout(choice))
         };
 
+        let mut printer = Self::with_output(args, config, out);
+        printer.terminal_width = terminal_width;
+        printer.spinner = args.spinner && interactive;
+        if args.progress.unwrap_or_default() == ProgressSetting::Auto && interactive {
+            printer.progress = ProgressSetting::Inplace;
+        }
+        if let Some(out) = args.event_socket.as_deref().and_then(event_socket::connect) {
+            let mut events = Self::with_output(args, config, out);
+            events.format = FormatSetting::Json;
+            printer.events = Some(Box::new(events));
+        }
+        if let Some(path) = std::env::var_os("XML_OUTPUT_FILE") {
+            let f = create(&path, "`XML_OUTPUT_FILE`")?;
+            let mut xml_output = Self::with_output(args, config, Box::new(NoColor::new(f)));
+            xml_output.format = FormatSetting::Junit;
+            let target = std::env::var("TEST_TARGET").unwrap_or_else(|_| "test".into());
+            xml_output.junit = junit::Report::bazel(target, xml_output.run_metadata.clone());
+            printer.xml_output = Some(Box::new(xml_output));
+        }
+        Ok(printer)
+    }
+
+    /// Makes write errors of the main output non-fatal: instead of
+    /// panicking, the first error is stored in the returned handle and all
+    /// further output is discarded. The handle's flag is set on the first
+    /// error, e.g. to stop the run.
+    pub(crate) fn track_write_errors(&mut self) -> Arc<WriteErrors> {
+        let errors = Arc::new(WriteErrors::default());
+        let inner = std::mem::replace(&mut self.out, Box::new(NoColor::new(io::sink())));
+        self.out = Box::new(TrackedOutput { inner, errors: Arc::clone(&errors) });
+        errors
+    }
+
+    /// Creates a new printer that writes to the given output instead of
+    /// stdout or the logfile.
+    pub(crate) fn with_output(
+        args: &Arguments,
+        config: &RunConfig,
+        out: Box<dyn WriteColor + 'out>,
+    ) -> Self {
         // Determine correct format
         let format = if args.quiet {
             FormatSetting::Terse
@@ -52,18 +186,57 @@ impl Printer {
             args.format.unwrap_or(FormatSetting::Pretty)
         };
 
+        let run_metadata = config.run_metadata.iter()
+            .chain(&args.run_meta)
+            .cloned()
+            .collect::<Vec<_>>();
+        Self {
+            out,
+            format,
+            name_width: 0,
+            kind_width: 0,
+            terminal_width: None,
+            max_failures_shown: args.max_failures_shown,
+            timestamps: args.timestamps,
+            report_time: args.report_time,
+            run_ignored_soft: args.run_ignored_soft,
+            ascii: args.ascii,
+            spinner: false,
+            summary_detail: args.summary_detail.unwrap_or_default(),
+            theme: config.theme.clone(),
+            failure_header: config.failure_header.clone().unwrap_or_else(|| "failures".into()),
+            compact_failures: config.compact_failures,
+            status_text: config.status_text.clone(),
+            progress: match args.progress.unwrap_or_default() {
+                ProgressSetting::Auto => ProgressSetting::Off,
+                other => other,
+            },
+            num_tests: 0,
+            num_finished: 0,
+            progress_shown: false,
+            events: None,
+            markdown: markdown::Report::default(),
+            junit: junit::Report::new(run_metadata.clone()),
+            run_metadata,
+            xml_output: None,
+        }
+    }
+
+    /// Sets the widths used to align test names and kinds, based on the
+    /// longest name and kind in `tests`.
+    pub(crate) fn set_name_widths(&mut self, tests: &[Trial]) {
         // Determine max test name length to do nice formatting later.
         //
         // Unicode is hard and there is no way we can properly align/pad the
         // test names and outcomes. Counting the number of code points is just
         // a cheap way that works in most cases. Usually, these names are
         // ASCII.
-        let name_width = tests.iter()
+        self.name_width = tests.iter()
             .map(|test| test.info.name.chars().count())
             .max()
             .unwrap_or(0);
 
-        let kind_width = tests.iter()
+        self.kind_width = tests.iter()
             .map(|test| {
                 if test.info.kind.is_empty() {
                     0
@@ -75,16 +248,36 @@ impl Printer {
             .max()
             .unwrap_or(0);
 
-        Self {
-            out,
-            format,
-            name_width,
-            kind_width,
+        // If we know the width of the terminal, make sure that the lines
+        // printed in pretty mode fit. Names that are too long are truncated.
+        if let Some(terminal_width) = self.terminal_width {
+            let available = terminal_width.saturating_sub(
+                "test ".len() + self.kind_width + " ... ".len() + "ignored".len()
+            );
+            self.name_width = self.name_width.min(available.max(MIN_NAME_WIDTH));
+        }
+    }
+
+    /// Prints the header of a section (see `run_sections`). Prints nothing in
+    /// JSON mode.
+    pub(crate) fn print_section_header(&mut self, label: &str) {
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                writeln!(self.out).unwrap();
+                writeln!(self.out, "     Running {}", label).unwrap();
+            }
+            FormatSetting::Json | FormatSetting::Markdown | FormatSetting::Junit
+            | FormatSetting::Tap => {}
         }
     }
 
     /// Prints the first line "running 3 tests".
     pub(crate) fn print_title(&mut self, num_tests: u64) {
+        if let Some(events) = &mut self.events {
+            events.print_title(num_tests);
+        }
+        self.num_tests = num_tests;
+        self.num_finished = 0;
         match self.format {
             FormatSetting::Pretty | FormatSetting::Terse => {
                 let plural_s = if num_tests == 1 { "" } else { "s" };
@@ -92,18 +285,64 @@ impl Printer {
                 writeln!(self.out).unwrap();
                 writeln!(self.out, "running {} test{}", num_tests, plural_s).unwrap();
             }
-            FormatSetting::Json => writeln!(
-                self.out,
-                r#"{{ "type": "suite", "event": "started", "test_count": {} }}"#,
-                num_tests
-            )
-            .unwrap(),
+            FormatSetting::Json => {
+                let metadata = match self.run_metadata.is_empty() {
+                    true => String::new(),
+                    false => {
+                        let fields = self.run_metadata.iter()
+                            .map(|(key, value)| format!(
+                                r#""{}": "{}""#,
+                                escape8259::escape(key),
+                                escape8259::escape(value),
+                            ))
+                            .collect::<Vec<_>>();
+                        format!(r#", "metadata": {{ {} }}"#, fields.join(", "))
+                    }
+                };
+                writeln!(
+                    self.out,
+                    r#"{{ "type": "suite", "event": "started", "test_count": {}{} }}"#,
+                    num_tests,
+                    metadata,
+                )
+                .unwrap();
+            }
+            FormatSetting::Tap => writeln!(self.out, "1..{}", num_tests).unwrap(),
+            FormatSetting::Markdown | FormatSetting::Junit => {}
+        }
+    }
+
+    /// Prints the master seed used for all randomness in this run, so that it
+    /// can be reproduced with `--seed`. Prints nothing in JSON mode.
+    pub(crate) fn print_seed(&mut self, seed: u64) {
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                writeln!(self.out, "seed: {} (reproduce with `--seed {}`)", seed, seed).unwrap();
+            }
+            FormatSetting::Json | FormatSetting::Markdown | FormatSetting::Junit
+            | FormatSetting::Tap => {}
+        }
+    }
+
+    /// Prints the seed the order of tests was shuffled with, so that it can be
+    /// reproduced with `--shuffle-seed`. Prints nothing in JSON mode.
+    pub(crate) fn print_shuffle_seed(&mut self, seed: u64) {
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                writeln!(self.out, "-- Test order shuffled with seed {} --", seed).unwrap();
+            }
+            FormatSetting::Json | FormatSetting::Markdown | FormatSetting::Junit
+            | FormatSetting::Tap => {}
         }
     }
 
     /// Prints the text announcing the test (e.g. "test foo::bar ... "). Prints
     /// nothing in terse mode.
     pub(crate) fn print_test(&mut self, info: &TestInfo) {
+        if let Some(events) = &mut self.events {
+            events.print_test(info);
+        }
+        self.clear_progress();
         let TestInfo { name, kind, .. } = info;
         match self.format {
             FormatSetting::Pretty => {
@@ -117,15 +356,18 @@ impl Printer {
                     self.out,
                     "test {: <2$}{: <3$} ... ",
                     kind,
-                    name,
+                    truncate_name(name, self.name_width, self.ascii),
                     self.kind_width,
                     self.name_width,
                 ).unwrap();
                 self.out.flush().unwrap();
             }
-            FormatSetting::Terse => {
+            FormatSetting::Terse | FormatSetting::Markdown | FormatSetting::Junit
+            | FormatSetting::Tap => {
                 // In terse mode, nothing is printed before the job. Only
-                // `print_single_outcome` prints one character.
+                // `print_single_outcome` prints one character. In Markdown
+                // and JUnit mode, everything is printed after the run. In TAP
+                // mode, the whole line is printed with the outcome.
             }
             FormatSetting::Json => {
                 writeln!(
@@ -138,19 +380,71 @@ impl Printer {
         }
     }
 
+    /// Starts a spinner after the text printed by `print_test` if enabled
+    /// (see `--spinner`). It is removed again when the returned value is
+    /// dropped, which has to happen before printing the outcome.
+    pub(crate) fn start_spinner(&self) -> Option<Spinner> {
+        (self.spinner && self.format == FormatSetting::Pretty).then(|| Spinner::start(self.ascii))
+    }
+
     /// Prints the outcome of a single tests. `ok` or `FAILED` in pretty mode
     /// and `.` or `F` in terse mode.
-    pub(crate) fn print_single_outcome(&mut self, info: &TestInfo, outcome: &Outcome) {
+    ///
+    /// With `--timestamps`, the wall clock start and end time of the test is
+    /// printed as well (if it was run). With `--report-time`, its duration.
+    /// The `output` captured while a failed test ran is included in its JSON
+    /// event (the other formats print it together with the failures).
+    pub(crate) fn print_single_outcome(
+        &mut self,
+        info: &TestInfo,
+        outcome: &Outcome,
+        timing: Option<&Timing>,
+        attempts: u32,
+        output: Option<&str>,
+    ) {
+        if let Some(xml_output) = &mut self.xml_output {
+            xml_output.print_single_outcome(info, outcome, timing, attempts, output);
+        }
+        if let Some(events) = &mut self.events {
+            events.print_single_outcome(info, outcome, timing, attempts, output);
+        }
+        let duration = timing.map(|timing| timing.duration);
+        let timing = timing.filter(|_| self.timestamps);
         match self.format {
             FormatSetting::Pretty => {
-                self.print_outcome_pretty(outcome);
+                if self.is_soft_failure(info, outcome) {
+                    self.out.set_color(&self.color_of_outcome(&Outcome::Ignored)).unwrap();
+                    write!(self.out, "ignored (failed)").unwrap();
+                    self.out.reset().unwrap();
+                } else {
+                    self.print_outcome_pretty(outcome);
+                }
+                if let Some(reason) = ignored_reason(info, outcome) {
+                    write!(self.out, ", {}", reason).unwrap();
+                }
+                if attempts > 1 && matches!(outcome, Outcome::Passed) {
+                    write!(self.out, " (flaky, passed on attempt {})", attempts).unwrap();
+                }
+                if let Some(duration) = duration.filter(|_| self.report_time) {
+                    write!(self.out, " ({:.2}s)", duration.as_secs_f64()).unwrap();
+                }
+                if let Some(timing) = timing {
+                    write!(
+                        self.out,
+                        " [{} .. {}]",
+                        fmt_iso8601(timing.start),
+                        fmt_iso8601(timing.end),
+                    ).unwrap();
+                }
                 writeln!(self.out).unwrap();
             }
             FormatSetting::Terse => {
                 let c = match outcome {
                     Outcome::Passed => '.',
-                    Outcome::Failed { .. } => 'F',
+                    _ if self.is_soft_failure(info, outcome) => 'f',
+                    Outcome::Failed { .. } | Outcome::TimedOut(_) => 'F',
                     Outcome::Ignored => 'i',
+                    Outcome::Cached => 'c',
                     Outcome::Measured { .. } => {
                         // Benchmark are never printed in terse mode... for
                         // some reason.
@@ -160,79 +454,346 @@ impl Printer {
                     }
                 };
 
-                self.out.set_color(&color_of_outcome(outcome)).unwrap();
+                self.out.set_color(&self.color_of_outcome(outcome)).unwrap();
                 write!(self.out, "{}", c).unwrap();
                 self.out.reset().unwrap();
+
+                // Like libtest, wrap the line regularly and show the progress.
+                let num_printed = self.num_finished + 1;
+                if num_printed % TERSE_LINE_WIDTH == 0 {
+                    writeln!(self.out, " {}/{}", num_printed, self.num_tests).unwrap();
+                }
             }
             FormatSetting::Json => {
-                if let Outcome::Measured(Measurement { avg, variance }) = outcome {
+                let timestamps = match timing {
+                    Some(timing) => format!(
+                        r#", "start_time": "{}", "end_time": "{}""#,
+                        fmt_iso8601(timing.start),
+                        fmt_iso8601(timing.end),
+                    ),
+                    None => String::new(),
+                };
+                let exec_time = match duration.filter(|_| self.report_time) {
+                    Some(duration) => format!(r#", "exec_time": {}"#, duration.as_secs_f64()),
+                    None => String::new(),
+                };
+                let location = match info.location_string() {
+                    Some(location) => {
+                        format!(r#", "location": "{}""#, escape8259::escape(location))
+                    }
+                    None => String::new(),
+                };
+
+                if let Outcome::Measured(measurement) = outcome {
+                    let mut extra = String::new();
+                    if let Some(unit) = &measurement.unit {
+                        extra += &format!(r#", "unit": "{}""#, escape8259::escape(unit));
+                    }
+                    if let Some(mib_per_second) = mib_per_second(measurement) {
+                        extra += &format!(r#", "mib_per_second": {}"#, mib_per_second as u64);
+                    }
+                    if let Some(min) = measurement.min {
+                        extra += &format!(r#", "min": {}"#, min);
+                    }
+                    if let Some(max) = measurement.max {
+                        extra += &format!(r#", "max": {}"#, max);
+                    }
                     writeln!(
                         self.out,
-                        r#"{{ "type": "bench", "name": "{}", "median": {}, "deviation": {} }}"#,
+                        concat!(
+                            r#"{{ "type": "bench", "name": "{}", "median": {}, "deviation": {}"#,
+                            r#"{}{}{} }}"#,
+                        ),
                         escape8259::escape(&info.name),
-                        avg,
-                        variance,
+                        measurement.avg,
+                        measurement.variance,
+                        extra,
+                        location,
+                        timestamps,
                     )
                     .unwrap();
                 } else {
                     writeln!(
                         self.out,
-                        r#"{{ "type": "test", "name": "{}", "event": "{}"{} }}"#,
+                        r#"{{ "type": "test", "name": "{}", "event": "{}"{}{}{}{} }}"#,
                         escape8259::escape(&info.name),
                         match outcome {
                             Outcome::Passed => "ok",
-                            Outcome::Failed(_) => "failed",
-                            Outcome::Ignored => "ignored",
+                            Outcome::Failed(_) | Outcome::TimedOut(_)
+                                if self.is_soft_failure(info, outcome) => "ignored",
+                            Outcome::Failed(_) | Outcome::TimedOut(_) => "failed",
+                            Outcome::Ignored | Outcome::Cached => "ignored",
                             Outcome::Measured(_) => unreachable!(),
                         },
                         match outcome {
-                            Outcome::Failed(Failed { msg: Some(msg) }) => {
-                                format!(
-                                    r#", "stdout": "Error: \"{}\"\n""#,
-                                    escape8259::escape(msg),
-                                )
+                            Outcome::Failed(Failed { msg }) => json_stdout(output, msg.as_deref()),
+                            Outcome::TimedOut(timeout) => {
+                                json_stdout(output, Some(&timeout_message(*timeout)))
                             }
+                            Outcome::Cached => r#", "cached": true"#.into(),
+                            Outcome::Ignored => match ignored_reason(info, outcome) {
+                                Some(reason) => {
+                                    format!(r#", "reason": "{}""#, escape8259::escape(reason))
+                                }
+                                None => "".into(),
+                            },
                             _ => "".into(),
-                        }
+                        },
+                        exec_time,
+                        location,
+                        timestamps,
                     )
                     .unwrap();
                 }
             }
+            FormatSetting::Markdown => {
+                let status = match outcome {
+                    Outcome::Passed => "ok".to_owned(),
+                    _ if self.is_soft_failure(info, outcome) => "ignored (failed)".into(),
+                    Outcome::Failed(Failed { msg }) => {
+                        let name = info.test_name_with_kind().into_owned();
+                        self.markdown.add_failure(name, msg.clone());
+                        "FAILED".into()
+                    }
+                    Outcome::TimedOut(timeout) => {
+                        let msg = timeout_message(*timeout);
+                        let name = info.test_name_with_kind().into_owned();
+                        self.markdown.add_failure(name, Some(msg));
+                        "FAILED".into()
+                    }
+                    Outcome::Ignored => match ignored_reason(info, outcome) {
+                        Some(reason) => format!("ignored, {}", reason),
+                        None => "ignored".into(),
+                    },
+                    Outcome::Cached => "cached".into(),
+                    Outcome::Measured(measurement) => {
+                        format!("bench: {}", fmt_measurement(measurement, 0))
+                    }
+                };
+                self.markdown.add(info.test_name_with_kind().into_owned(), status, duration);
+            }
+            FormatSetting::Tap => self.print_tap_outcome(info, outcome),
+            FormatSetting::Junit => {
+                let result = match outcome {
+                    Outcome::Passed | Outcome::Measured(_) => CaseResult::Passed,
+                    Outcome::Failed(Failed { msg }) if self.is_soft_failure(info, outcome) => {
+                        let msg = msg.as_deref().map_or("".into(), |msg| format!(": {}", msg));
+                        CaseResult::Skipped(Some(format!("ignored (failed){}", msg)))
+                    }
+                    Outcome::TimedOut(timeout) if self.is_soft_failure(info, outcome) => {
+                        let msg = format!("ignored (failed): {}", timeout_message(*timeout));
+                        CaseResult::Skipped(Some(msg))
+                    }
+                    Outcome::Failed(Failed { msg }) => CaseResult::Failed(msg.clone()),
+                    Outcome::TimedOut(timeout) => {
+                        CaseResult::Failed(Some(timeout_message(*timeout)))
+                    }
+                    Outcome::Ignored => {
+                        CaseResult::Skipped(ignored_reason(info, outcome).map(Into::into))
+                    }
+                    Outcome::Cached => CaseResult::Skipped(Some("cached".into())),
+                };
+                self.junit.add(&info.name, &info.kind, duration, result);
+            }
         }
     }
 
-    /// Prints the summary line after all tests have been executed.
-    pub(crate) fn print_summary(&mut self, conclusion: &Conclusion, execution_time: Duration) {
+    /// Records that another test finished and shows the progress according to
+    /// `--progress`. Only shows something in pretty mode.
+    pub(crate) fn print_progress(&mut self) {
+        self.num_finished += 1;
+        if self.format != FormatSetting::Pretty || self.num_tests == 0 {
+            return;
+        }
+
+        let (finished, total) = (self.num_finished, self.num_tests);
+        match self.progress {
+            ProgressSetting::Inplace => {
+                write!(self.out, "[{}/{} tests finished]", finished, total).unwrap();
+                self.out.flush().unwrap();
+                self.progress_shown = true;
+            }
+            ProgressSetting::Plain => {
+                // Only print a line whenever another 10% are reached.
+                if finished < total && finished * 10 / total > (finished - 1) * 10 / total {
+                    writeln!(self.out, "progress: {}/{} tests finished", finished, total).unwrap();
+                }
+            }
+            ProgressSetting::Auto | ProgressSetting::Off => {}
+        }
+    }
+
+    /// Removes the in-place progress line, if it's currently shown.
+    fn clear_progress(&mut self) {
+        if self.progress_shown {
+            let len = format!("[{}/{} tests finished]", self.num_finished, self.num_tests).len();
+            write!(self.out, "\r{}\r", " ".repeat(len)).unwrap();
+            self.progress_shown = false;
+        }
+    }
+
+    /// Prints the line with the total numbers of all sections (see
+    /// `run_sections`). Prints nothing in JSON mode.
+    pub(crate) fn print_total(&mut self, conclusion: &Conclusion, execution_time: Duration) {
         match self.format {
             FormatSetting::Pretty | FormatSetting::Terse => {
-                let outcome = if conclusion.has_failed() {
-                    Outcome::Failed(Failed { msg: None })
-                } else {
-                    Outcome::Passed
-                };
+                self.print_summary_line("total", conclusion, execution_time);
+            }
+            FormatSetting::Json | FormatSetting::Markdown | FormatSetting::Junit
+            | FormatSetting::Tap => {}
+        }
+    }
 
+    /// Prints the number of executed tests and failures of all `--repeat`
+    /// iterations. Prints nothing in JSON mode.
+    pub(crate) fn print_repeat_summary(&mut self, iterations: u32, conclusion: &Conclusion) {
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                let attempts = conclusion.num_passed
+                    + conclusion.num_failed
+                    + conclusion.num_ignored_failed
+                    + conclusion.num_measured;
+                writeln!(
+                    self.out,
+                    "repeated {} times: {} attempts; {} failures",
+                    iterations,
+                    attempts,
+                    conclusion.num_failed + conclusion.num_ignored_failed,
+                ).unwrap();
+            }
+            FormatSetting::Json | FormatSetting::Markdown | FormatSetting::Junit
+            | FormatSetting::Tap => {}
+        }
+    }
+
+    /// Prints how many tests were run within the `--time-budget`.
+    pub(crate) fn print_time_budget(&mut self, num_run: u64, num_tests: u64, budget: Duration) {
+        self.clear_progress();
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
                 writeln!(self.out).unwrap();
-                write!(self.out, "test result: ").unwrap();
-                self.print_outcome_pretty(&outcome);
                 writeln!(
                     self.out,
-                    ". {} passed; {} failed; {} ignored; {} measured; \
-                        {} filtered out; finished in {:.2}s",
-                    conclusion.num_passed,
-                    conclusion.num_failed,
-                    conclusion.num_ignored,
-                    conclusion.num_measured,
-                    conclusion.num_filtered_out,
-                    execution_time.as_secs_f64()
+                    "ran {} of {} tests within the time budget of {}s",
+                    num_run,
+                    num_tests,
+                    budget.as_secs_f64(),
                 ).unwrap();
+            }
+            FormatSetting::Json | FormatSetting::Markdown | FormatSetting::Junit
+            | FormatSetting::Tap => {}
+        }
+    }
+
+    /// Prints how many tests were not run as the `--deadline-epoch` passed.
+    pub(crate) fn print_deadline_reached(&mut self, num_not_run: u64) {
+        self.clear_progress();
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                let plural_s = if num_not_run == 1 { "" } else { "s" };
+                writeln!(self.out).unwrap();
+                writeln!(
+                    self.out,
+                    "deadline reached: {} test{} not run",
+                    num_not_run,
+                    plural_s,
+                ).unwrap();
+            }
+            FormatSetting::Json | FormatSetting::Markdown | FormatSetting::Junit
+            | FormatSetting::Tap => {}
+        }
+    }
+
+    /// Prints how many tests were not run as the run was interrupted (see
+    /// `RunConfig::with_interrupt_flag`).
+    pub(crate) fn print_interrupted(&mut self, num_not_run: u64) {
+        self.clear_progress();
+        let plural_s = if num_not_run == 1 { "" } else { "s" };
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                writeln!(self.out).unwrap();
+                writeln!(
+                    self.out,
+                    "run interrupted: {} test{} not run",
+                    num_not_run,
+                    plural_s,
+                ).unwrap();
+            }
+            FormatSetting::Tap => {
+                writeln!(
+                    self.out,
+                    "Bail out! run interrupted: {} test{} not run",
+                    num_not_run,
+                    plural_s,
+                ).unwrap();
+            }
+            FormatSetting::Json | FormatSetting::Markdown | FormatSetting::Junit => {}
+        }
+    }
+
+    /// Prints a note that the run was aborted and how many tests were not run
+    /// because of that. Prints nothing in JSON mode.
+    pub(crate) fn print_aborted(&mut self, num_not_run: u64) {
+        self.clear_progress();
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                let plural_s = if num_not_run == 1 { "" } else { "s" };
                 writeln!(self.out).unwrap();
+                writeln!(
+                    self.out,
+                    "run aborted due to too many failures: {} test{} not run",
+                    num_not_run,
+                    plural_s,
+                ).unwrap();
+            }
+            FormatSetting::Tap => {
+                let plural_s = if num_not_run == 1 { "" } else { "s" };
+                writeln!(
+                    self.out,
+                    "Bail out! run aborted due to too many failures: {} test{} not run",
+                    num_not_run,
+                    plural_s,
+                ).unwrap();
+            }
+            FormatSetting::Json | FormatSetting::Markdown | FormatSetting::Junit => {}
+        }
+    }
+
+    /// Prints the summary line after all tests have been executed.
+    pub(crate) fn print_summary(&mut self, conclusion: &Conclusion, execution_time: Duration) {
+        if let Some(xml_output) = &mut self.xml_output {
+            xml_output.print_summary(conclusion, execution_time);
+        }
+        if let Some(events) = &mut self.events {
+            events.print_summary(conclusion, execution_time);
+        }
+        self.clear_progress();
+        match self.format {
+            FormatSetting::Pretty | FormatSetting::Terse => {
+                self.print_summary_line("test result", conclusion, execution_time);
             }
             FormatSetting::Json => {
+                let modules = match conclusion.modules.is_empty() {
+                    true => String::new(),
+                    false => {
+                        let entries = conclusion.modules.iter()
+                            .map(|(module, counts)| format!(
+                                r#""{}": {{ "passed": {}, "failed": {}, "ignored": {} }}"#,
+                                escape8259::escape(module),
+                                counts.num_passed,
+                                counts.num_failed,
+                                counts.num_ignored,
+                            ))
+                            .collect::<Vec<_>>();
+                        format!(r#", "modules": {{ {} }}"#, entries.join(", "))
+                    }
+                };
                 writeln!(
                     self.out,
                     concat!(
                         r#"{{ "type": "suite", "event": "{}", "passed": {}, "failed": {},"#,
-                        r#" "ignored": {}, "measured": {}, "filtered_out": {}, "exec_time": {} }}"#,
+                        r#" "ignored": {}, "measured": {}, "filtered_out": {},"#,
+                        r#" "exec_time": {}{} }}"#,
                     ),
                     if conclusion.num_failed > 0 { "failed" } else { "ok" },
                     conclusion.num_passed,
@@ -240,98 +801,771 @@ impl Printer {
                     conclusion.num_ignored,
                     conclusion.num_measured,
                     conclusion.num_filtered_out,
-                    execution_time.as_secs_f64()
+                    execution_time.as_secs_f64(),
+                    modules,
                 )
                 .unwrap();
             }
+            FormatSetting::Markdown => {
+                let doc = self.markdown.finish(conclusion, execution_time);
+                write!(self.out, "{}", doc).unwrap();
+            }
+            FormatSetting::Junit => {
+                let doc = self.junit.finish(conclusion, execution_time);
+                write!(self.out, "{}", doc).unwrap();
+            }
+            FormatSetting::Tap => {
+                writeln!(
+                    self.out,
+                    "# {} passed; {} failed; {} ignored; {} measured; {} filtered out",
+                    conclusion.num_passed,
+                    conclusion.num_failed,
+                    conclusion.num_ignored,
+                    conclusion.num_measured,
+                    conclusion.num_filtered_out,
+                ).unwrap();
+            }
         }
     }
 
-    /// Prints a list of all tests. Used if `--list` is set.
+    /// Prints a line like `test result: ok. 3 passed; ...` with the given
+    /// label instead of `test result`.
+    fn print_summary_line(
+        &mut self,
+        label: &str,
+        conclusion: &Conclusion,
+        execution_time: Duration,
+    ) {
+        let outcome = if conclusion.has_failed() {
+            Outcome::Failed(Failed { msg: None })
+        } else {
+            Outcome::Passed
+        };
+
+        writeln!(self.out).unwrap();
+        write!(self.out, "{}: ", label).unwrap();
+        self.print_outcome_pretty(&outcome);
+        write!(self.out, ". {} passed; {} failed; ", conclusion.num_passed, conclusion.num_failed)
+            .unwrap();
+        if self.summary_detail != SummaryDetail::Minimal {
+            write!(self.out, "{} ignored; ", conclusion.num_ignored).unwrap();
+            if self.summary_detail == SummaryDetail::Full || conclusion.num_ignored_failed > 0 {
+                write!(self.out, "{} ignored (failed); ", conclusion.num_ignored_failed).unwrap();
+            }
+            write!(
+                self.out,
+                "{} measured; {} filtered out; ",
+                conclusion.num_measured,
+                conclusion.num_filtered_out,
+            ).unwrap();
+        }
+        if self.summary_detail == SummaryDetail::Full
+            || (self.summary_detail == SummaryDetail::Normal && conclusion.num_cached > 0)
+        {
+            write!(self.out, "{} cached; ", conclusion.num_cached).unwrap();
+        }
+        if self.summary_detail == SummaryDetail::Full {
+            write!(self.out, "{} not run; ", conclusion.num_not_run).unwrap();
+            if let Some(pass_rate) = conclusion.pass_rate() {
+                write!(self.out, "{:.1}% passed; ", pass_rate).unwrap();
+            }
+        }
+        writeln!(self.out, "finished in {:.2}s", execution_time.as_secs_f64()).unwrap();
+        writeln!(self.out).unwrap();
+    }
+
+    /// Prints a list of all tests. Used if `--list` is set. With `--format
+    /// json`, the discovery events of libtest are printed: a `discovery`
+    /// event, one `discovered` event per test (with `"type": "bench"` for
+    /// benchmarks) and a `completed` event with the counts. This deviates
+    /// from libtest in two ways:
+    /// - `source_path`, `start_line` and `start_col` come from
+    ///   `Trial::with_location` (empty and 0 if not set), and `end_line` and
+    ///   `end_col` are the same as the start, as only that is known.
+    /// - Tests with a module (see `Trial::with_module`) have an additional
+    ///   `"module"` key, while libtest includes the module in the name.
     pub(crate) fn print_list(&mut self, tests: &[Trial], ignored: bool) {
+        if self.format == FormatSetting::Json {
+            writeln!(self.out, r#"{{ "type": "suite", "event": "discovery" }}"#).unwrap();
+            let tests = tests.iter()
+                .filter(|test| !ignored || test.info.is_ignored)
+                .collect::<Vec<_>>();
+            for test in &tests {
+                let module = match &test.info.module {
+                    Some(module) => format!(r#", "module": "{}""#, escape8259::escape(module)),
+                    None => String::new(),
+                };
+                let (path, line, col) = test.info.location.unwrap_or(("", 0, 0));
+                writeln!(
+                    self.out,
+                    concat!(
+                        r#"{{ "type": "{}", "event": "discovered", "name": "{}"{}, "#,
+                        r#""ignore": {}, "ignore_message": "{}", "source_path": "{}", "#,
+                        r#""start_line": {}, "start_col": {}, "end_line": {}, "end_col": {} }}"#,
+                    ),
+                    if test.info.is_bench { "bench" } else { "test" },
+                    escape8259::escape(&test.info.name),
+                    module,
+                    test.info.is_ignored,
+                    escape8259::escape(test.info.ignored_reason.as_deref().unwrap_or("")),
+                    escape8259::escape(path),
+                    line,
+                    col,
+                    line,
+                    col,
+                ).unwrap();
+            }
+            let num_benches = tests.iter().filter(|test| test.info.is_bench).count();
+            writeln!(
+                self.out,
+                concat!(
+                    r#"{{ "type": "suite", "event": "completed", "tests": {}, "#,
+                    r#""benchmarks": {}, "total": {}, "ignored": {} }}"#,
+                ),
+                tests.len() - num_benches,
+                num_benches,
+                tests.len(),
+                tests.iter().filter(|test| test.info.is_ignored).count(),
+            ).unwrap();
+            return;
+        }
         Self::write_list(tests, ignored, &mut self.out).unwrap();
     }
 
+    /// Writes the `name: test` lines of `--list`. If any test has a module
+    /// (see `Trial::with_module`), the tests are printed as a tree of
+    /// modules, after the tests without module.
     pub(crate) fn write_list(
         tests: &[Trial],
         ignored: bool,
         mut out: impl std::io::Write,
     ) -> std::io::Result<()> {
-        for test in tests {
-            // libtest prints out:
-            // * all tests without `--ignored`
-            // * just the ignored tests with `--ignored`
-            if ignored && !test.info.is_ignored {
-                continue;
-            }
-
+        // libtest prints out:
+        // * all tests without `--ignored`
+        // * just the ignored tests with `--ignored`
+        let tests = tests.iter().filter(|test| !ignored || test.info.is_ignored);
+        let line = |test: &Trial| {
             let kind = if test.info.kind.is_empty() {
                 String::new()
             } else {
                 format!("[{}] ", test.info.kind)
             };
-
-            writeln!(
-                out,
+            format!(
                 "{}{}: {}",
                 kind,
                 test.info.name,
                 if test.info.is_bench { "bench" } else { "test" },
-            )?;
+            )
+        };
+
+        // Sorting by segments (instead of the whole path) keeps submodules
+        // right after their parent.
+        let mut modules = BTreeMap::<Vec<&str>, Vec<String>>::new();
+        for test in tests {
+            match &test.info.module {
+                Some(module) => {
+                    modules.entry(module.split("::").collect()).or_default().push(line(test));
+                }
+                None => writeln!(out, "{}", line(test))?,
+            }
+        }
+
+        let mut previous: &[&str] = &[];
+        for (path, lines) in &modules {
+            let common = path.iter().zip(previous).take_while(|(a, b)| a == b).count();
+            for (depth, segment) in path.iter().enumerate().skip(common) {
+                writeln!(out, "{:indent$}{}:", "", segment, indent = 4 * depth)?;
+            }
+            for line in lines {
+                writeln!(out, "{:indent$}{}", "", line, indent = 4 * path.len())?;
+            }
+            previous = path;
         }
 
         Ok(())
     }
 
-    /// Prints a list of failed tests with their messages. This is only called
-    /// if there were any failures.
-    pub(crate) fn print_failures(&mut self, fails: &[(TestInfo, Option<String>)]) {
-        if self.format == FormatSetting::Json {
+    /// Prints a list of failed tests with their messages, preceded by the
+    /// output captured while the test ran (keyed by `test_name_with_kind`),
+    /// if any. This is only called if there were any failures.
+    pub(crate) fn print_failures(
+        &mut self,
+        soft: bool,
+        fails: &[(TestInfo, Option<String>)],
+        captured: &HashMap<String, String>,
+    ) {
+        self.clear_progress();
+        if self.is_structured() {
             return;
         }
+        let heading = match soft {
+            true => format!("ignored {}", self.failure_header),
+            false => self.failure_header.clone(),
+        };
         writeln!(self.out).unwrap();
-        writeln!(self.out, "failures:").unwrap();
-        writeln!(self.out).unwrap();
+        writeln!(self.out, "{}:", heading).unwrap();
+        if !self.compact_failures {
+            writeln!(self.out).unwrap();
+        }
+
+        // Print messages of all tests (or as many as we are allowed to)
+        let num_shown = self.max_failures_shown.unwrap_or(fails.len()).min(fails.len());
+        for (test_info, msg) in &fails[..num_shown] {
+            // Messages may contain ANSI escape codes (e.g. colored diffs).
+            // Those are passed through if we print colors ourselves, and
+            // removed otherwise.
+            let msg = msg.as_deref().map(|msg| match self.out.supports_color() {
+                true => Cow::Borrowed(msg),
+                false => strip_ansi(msg),
+            });
+            let location = test_info.location_string();
+            if self.compact_failures {
+                match msg {
+                    Some(msg) => {
+                        let msg = msg.lines().collect::<Vec<_>>().join("; ");
+                        write!(self.out, "    {}: {}", test_info.name, msg).unwrap();
+                    }
+                    None => write!(self.out, "    {}", test_info.name).unwrap(),
+                }
+                match location {
+                    Some(location) => writeln!(self.out, " (at {})", location).unwrap(),
+                    None => writeln!(self.out).unwrap(),
+                }
+                continue;
+            }
 
-        // Print messages of all tests
-        for (test_info, msg) in fails {
-            writeln!(self.out, "---- {} ----", test_info.name).unwrap();
+            match captured.get(test_info.test_name_with_kind().as_ref()) {
+                Some(output) => {
+                    writeln!(self.out, "---- {} stdout ----", test_info.name).unwrap();
+                    writeln!(self.out, "{}", output.trim_end()).unwrap();
+                }
+                None => writeln!(self.out, "---- {} ----", test_info.name).unwrap(),
+            }
             if let Some(msg) = msg {
                 writeln!(self.out, "{}", msg).unwrap();
             }
+            if let Some(location) = location {
+                writeln!(self.out, "at {}", location).unwrap();
+            }
+            writeln!(self.out).unwrap();
+        }
+        if num_shown < fails.len() {
+            let num_hidden = fails.len() - num_shown;
+            let plural_s = if num_hidden == 1 { "" } else { "s" };
+            match self.compact_failures {
+                true => {
+                    writeln!(
+                        self.out,
+                        "    ... and {} more failure{}",
+                        num_hidden,
+                        plural_s,
+                    ).unwrap();
+                }
+                false => {
+                    writeln!(self.out, "... and {} more failure{}", num_hidden, plural_s).unwrap();
+                    writeln!(self.out).unwrap();
+                }
+            }
+        }
+
+        // Print summary list of failed tests. In compact mode, all names were
+        // already printed above (unless hidden).
+        if !self.compact_failures {
+            writeln!(self.out).unwrap();
+            writeln!(self.out, "{}:", heading).unwrap();
+            for (test_info, _) in fails {
+                writeln!(self.out, "    {}", test_info.name).unwrap();
+            }
+        }
+    }
+
+    /// Prints the `(test name, output)` of re-running failed tests with
+    /// `--bisect-verbose`.
+    pub(crate) fn print_verbose_reruns(&mut self, reruns: &[(String, String)]) {
+        if self.is_structured() {
+            return;
+        }
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "verbose re-run of failed tests:").unwrap();
+        writeln!(self.out).unwrap();
+        for (name, output) in reruns {
+            writeln!(self.out, "---- {} ----", name).unwrap();
+            write!(self.out, "{}", output).unwrap();
             writeln!(self.out).unwrap();
         }
+    }
+
+    /// Prints the tests that were added or removed compared to the
+    /// `--baseline-list`. Prints nothing in JSON mode or if nothing changed.
+    pub(crate) fn print_baseline_diff(&mut self, diff: &BaselineDiff) {
+        if self.is_structured() || diff.is_empty() {
+            return;
+        }
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        writeln!(
+            self.out,
+            "compared to the baseline list: {} test{} added, {} test{} removed",
+            diff.added.len(),
+            plural(diff.added.len()),
+            diff.removed.len(),
+            plural(diff.removed.len()),
+        ).unwrap();
+        for name in &diff.added {
+            writeln!(self.out, "    + {}", name).unwrap();
+        }
+        for name in &diff.removed {
+            writeln!(self.out, "    - {}", name).unwrap();
+        }
+    }
+
+    /// Prints the names of all tests that were ignored (see
+    /// `--list-ignored-after`). Prints nothing in JSON mode or if `names` is
+    /// empty.
+    pub(crate) fn print_ignored_list(&mut self, names: &[(String, Option<String>)]) {
+        if self.is_structured() || names.is_empty() {
+            return;
+        }
+        self.clear_progress();
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "ignored tests:").unwrap();
+        for (name, reason) in names {
+            match reason {
+                Some(reason) => writeln!(self.out, "    {}, {}", name, reason).unwrap(),
+                None => writeln!(self.out, "    {}", name).unwrap(),
+            }
+        }
+    }
+
+    /// Prints all `(test name, message)` warnings emitted by tests. Prints
+    /// nothing in JSON mode.
+    pub(crate) fn print_warnings(&mut self, warnings: &[(String, String)]) {
+        if self.is_structured() {
+            return;
+        }
+        self.clear_progress();
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "warnings:").unwrap();
+        for (name, msg) in warnings {
+            writeln!(self.out, "    {}: {}", name, msg).unwrap();
+        }
+    }
+
+    /// Prints benchmarks with their coefficient of variation (see
+    /// `--bench-unstable-threshold`). Prints nothing in JSON mode or if
+    /// `unstable` is empty.
+    pub(crate) fn print_unstable_benches(&mut self, unstable: &[(&str, f64)]) {
+        if self.is_structured() || unstable.is_empty() {
+            return;
+        }
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "unstable benchmarks:").unwrap();
+        for (name, cv) in unstable {
+            writeln!(self.out, "    {:>7.1}% {}", cv * 100.0, name).unwrap();
+        }
+    }
+
+    /// Prints the pass/fail counts per module (see `Conclusion::modules`).
+    /// Prints nothing in JSON mode or if no test has a module.
+    pub(crate) fn print_module_counts(&mut self, modules: &[(String, ModuleCounts)]) {
+        if self.is_structured() || modules.is_empty() {
+            return;
+        }
+        writeln!(self.out).unwrap();
+        writeln!(self.out, "results per module:").unwrap();
+        for (module, counts) in modules {
+            writeln!(
+                self.out,
+                "    {}: {} passed; {} failed; {} ignored",
+                module,
+                counts.num_passed,
+                counts.num_failed,
+                counts.num_ignored,
+            ).unwrap();
+        }
+    }
 
-        // Print summary list of failed tests
+    /// Prints the given slowest tests (see `--slowest`). Prints nothing in
+    /// JSON mode or if `slowest` is empty.
+    pub(crate) fn print_slowest(&mut self, slowest: &[(String, Duration)]) {
+        if self.is_structured() || slowest.is_empty() {
+            return;
+        }
         writeln!(self.out).unwrap();
-        writeln!(self.out, "failures:").unwrap();
-        for (test_info, _) in fails {
-            writeln!(self.out, "    {}", test_info.name).unwrap();
+        writeln!(self.out, "slowest tests:").unwrap();
+        for (name, duration) in slowest {
+            writeln!(self.out, "    {:>8.3}s {}", duration.as_secs_f64(), name).unwrap();
+        }
+    }
+
+    /// Prints a hint to review pending snapshots, as `num` snapshot tests
+    /// failed. Prints nothing in JSON mode.
+    pub(crate) fn print_snapshot_hint(&mut self, num: usize) {
+        if self.is_structured() {
+            return;
+        }
+        let plural_s = if num == 1 { "" } else { "s" };
+        writeln!(self.out).unwrap();
+        writeln!(
+            self.out,
+            "note: {} snapshot test{} failed, review pending snapshots with `cargo insta review`",
+            num,
+            plural_s,
+        ).unwrap();
+    }
+
+    /// Returns `true` for formats that are meant to be read by other tools,
+    /// so that any additional output (e.g. hints) has to be omitted.
+    fn is_structured(&self) -> bool {
+        matches!(
+            self.format,
+            FormatSetting::Json | FormatSetting::Markdown | FormatSetting::Junit
+            | FormatSetting::Tap,
+        )
+    }
+
+    /// Returns `true` if the given outcome is a failure of an ignored test that
+    /// was only run because of `--run-ignored-soft`.
+    fn is_soft_failure(&self, info: &TestInfo, outcome: &Outcome) -> bool {
+        self.run_ignored_soft
+            && info.is_ignored
+            && matches!(outcome, Outcome::Failed(_) | Outcome::TimedOut(_))
+    }
+
+    /// Returns the `ColorSpec` associated with the given outcome.
+    fn color_of_outcome(&self, outcome: &Outcome) -> ColorSpec {
+        let mut out = ColorSpec::new();
+        let color = match outcome {
+            Outcome::Passed => self.theme.pass,
+            Outcome::Failed { .. } | Outcome::TimedOut(_) => self.theme.fail,
+            Outcome::Ignored | Outcome::Cached => self.theme.ignore,
+            Outcome::Measured { .. } => self.theme.bench,
+        };
+        out.set_fg(Some(color));
+        out
+    }
+
+    /// Prints how many tests would run (and be ignored) with `--no-run`.
+    /// Printed as the same line in every format.
+    pub(crate) fn print_no_run(&mut self, num_run: u64, num_ignored: u64, num_filtered_out: u64) {
+        let plural_s = if num_run == 1 { "" } else { "s" };
+        writeln!(
+            self.out,
+            "{} test{} would run, {} ignored, {} filtered out (--no-run)",
+            num_run,
+            plural_s,
+            num_ignored,
+            num_filtered_out,
+        ).unwrap();
+    }
+
+    /// Prints the effective configuration for `--print-config`: the format
+    /// and color decision of this printer, followed by the given entries.
+    /// Printed as `key: value` lines in every format.
+    pub(crate) fn print_config(&mut self, color: ColorSetting, entries: &[(&str, String)]) {
+        let name = |value: Option<clap::builder::PossibleValue>| {
+            value.expect("no skipped variants").get_name().to_owned()
+        };
+        let decision = match self.out.supports_color() {
+            true => "enabled",
+            false => "disabled",
+        };
+        let own = [
+            ("format", name(self.format.to_possible_value())),
+            ("color", format!("{} ({})", name(color.to_possible_value()), decision)),
+        ];
+        let all = own.iter().chain(entries).collect::<Vec<_>>();
+        let key_width = all.iter().map(|(key, _)| key.len() + 1).max().unwrap_or(0);
+        for (key, value) in all {
+            writeln!(self.out, "{:<2$} {}", format!("{}:", key), value, key_width).unwrap();
+        }
+    }
+
+    /// Prints the TAP line of a test, e.g. `ok 1 - foo` or `not ok 2 - bar`,
+    /// followed by a YAML block with the message for failures. Ignored tests
+    /// use the `SKIP` directive, soft failures the `TODO` directive.
+    fn print_tap_outcome(&mut self, info: &TestInfo, outcome: &Outcome) {
+        let number = self.num_finished + 1;
+        let name = info.test_name_with_kind().replace('#', "\\#");
+        let (ok, directive, msg) = match outcome {
+            Outcome::Passed => ("ok", String::new(), None),
+            Outcome::Failed(Failed { msg }) if self.is_soft_failure(info, outcome) => {
+                ("not ok", " # TODO ignored (failed)".into(), msg.clone())
+            }
+            Outcome::TimedOut(timeout) if self.is_soft_failure(info, outcome) => {
+                ("not ok", " # TODO ignored (failed)".into(), Some(timeout_message(*timeout)))
+            }
+            Outcome::Failed(Failed { msg }) => ("not ok", String::new(), msg.clone()),
+            Outcome::TimedOut(timeout) => {
+                ("not ok", String::new(), Some(timeout_message(*timeout)))
+            }
+            Outcome::Ignored => match ignored_reason(info, outcome) {
+                Some(reason) => ("ok", format!(" # SKIP {}", reason.replace('\n', " ")), None),
+                None => ("ok", " # SKIP".into(), None),
+            },
+            Outcome::Cached => ("ok", " # SKIP cached".into(), None),
+            Outcome::Measured(measurement) => {
+                ("ok", format!(" # bench: {}", fmt_measurement(measurement, 0)), None)
+            }
+        };
+        writeln!(self.out, "{} {} - {}{}", ok, number, name, directive).unwrap();
+        if let Some(msg) = msg {
+            writeln!(self.out, "  ---").unwrap();
+            writeln!(self.out, "  message: |").unwrap();
+            for line in strip_ansi(&msg).lines() {
+                writeln!(self.out, "    {}", line).unwrap();
+            }
+            writeln!(self.out, "  ...").unwrap();
         }
     }
 
-    /// Prints a colored 'ok'/'FAILED'/'ignored'/'bench'.
+    /// Prints a colored 'ok'/'FAILED'/'ignored'/'bench'/'cached' (or the text
+    /// returned by `RunConfig::with_status_text`).
     fn print_outcome_pretty(&mut self, outcome: &Outcome) {
-        let s = match outcome {
-            Outcome::Passed => "ok",
-            Outcome::Failed { .. } => "FAILED",
-            Outcome::Ignored => "ignored",
-            Outcome::Measured { .. } => "bench",
+        let s = match &self.status_text {
+            Some(status_text) => Cow::Owned(status_text(outcome)),
+            None => Cow::Borrowed(match outcome {
+                Outcome::Passed => "ok",
+                Outcome::Failed { .. } | Outcome::TimedOut(_) => "FAILED",
+                Outcome::Ignored => "ignored",
+                Outcome::Measured { .. } => "bench",
+                Outcome::Cached => "cached",
+            }),
         };
 
-        self.out.set_color(&color_of_outcome(outcome)).unwrap();
+        self.out.set_color(&self.color_of_outcome(outcome)).unwrap();
         write!(self.out, "{}", s).unwrap();
         self.out.reset().unwrap();
 
-        if let Outcome::Measured(Measurement { avg, variance }) = outcome {
-            write!(
-                self.out,
-                ": {:>11} ns/iter (+/- {})",
-                fmt_with_thousand_sep(*avg),
-                fmt_with_thousand_sep(*variance),
-            ).unwrap();
+        if let Outcome::Measured(measurement) = outcome {
+            write!(self.out, ": {}", fmt_measurement(measurement, 11)).unwrap();
+        }
+    }
+}
+
+/// Output shared with the harness author (see `RunConfig::with_writer`).
+struct SharedOutput(config::Writer);
+
+impl io::Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).flush()
+    }
+}
+
+/// The first write error of a printer's output (see
+/// `Printer::track_write_errors`).
+#[derive(Default)]
+pub(crate) struct WriteErrors {
+    /// Set as soon as an error occurred.
+    pub(crate) occurred: Arc<AtomicBool>,
+    error: Mutex<Option<io::Error>>,
+}
+
+impl WriteErrors {
+    /// Takes the stored error, if any.
+    pub(crate) fn take(&self) -> Option<io::Error> {
+        self.error.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+
+    /// Records `e` (if it's the first error) and reports success, so that
+    /// the printer's `unwrap`s don't panic.
+    fn record(&self, result: io::Result<()>) -> io::Result<()> {
+        if let Err(e) = result {
+            let mut error = self.error.lock().unwrap_or_else(|e| e.into_inner());
+            if !self.occurred.swap(true, Ordering::SeqCst) {
+                *error = Some(e);
+            }
+        }
+        Ok(())
+    }
+
+    fn failed(&self) -> bool {
+        self.occurred.load(Ordering::SeqCst)
+    }
+}
+
+/// Output that records write errors in `WriteErrors` and discards
+/// everything after the first one.
+struct TrackedOutput<'out> {
+    inner: Box<dyn WriteColor + 'out>,
+    errors: Arc<WriteErrors>,
+}
+
+impl io::Write for TrackedOutput<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.errors.failed() {
+            return Ok(buf.len());
+        }
+        match self.inner.write(buf) {
+            Ok(n) => Ok(n),
+            // Pretend everything was written so that `write_all` stops.
+            Err(e) => self.errors.record(Err(e)).map(|()| buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.errors.failed() {
+            return Ok(());
+        }
+        let result = self.inner.flush();
+        self.errors.record(result)
+    }
+}
+
+impl WriteColor for TrackedOutput<'_> {
+    fn supports_color(&self) -> bool {
+        self.inner.supports_color()
+    }
+
+    fn set_color(&mut self, spec: &ColorSpec) -> io::Result<()> {
+        if self.errors.failed() {
+            return Ok(());
+        }
+        let result = self.inner.set_color(spec);
+        self.errors.record(result)
+    }
+
+    fn reset(&mut self) -> io::Result<()> {
+        if self.errors.failed() {
+            return Ok(());
+        }
+        let result = self.inner.reset();
+        self.errors.record(result)
+    }
+}
+
+/// Formats a measurement like `1,234 ns/iter (+/- 56)`, with the average
+/// right-aligned to `avg_width` characters, the `unit` (if set) instead of
+/// `ns`, `min` and `max` (if set) after the variance, e.g. `(+/- 56, min
+/// 1,200, max 1,300)`, and the throughput (if `bytes` is set), e.g.
+/// ` = 3.09 MiB/s`.
+fn fmt_measurement(measurement: &Measurement, avg_width: usize) -> String {
+    let mut spread = fmt_with_thousand_sep(measurement.variance);
+    if let Some(min) = measurement.min {
+        spread += &format!(", min {}", fmt_with_thousand_sep(min));
+    }
+    if let Some(max) = measurement.max {
+        spread += &format!(", max {}", fmt_with_thousand_sep(max));
+    }
+    let mut out = format!(
+        "{:>width$} {}/iter (+/- {})",
+        fmt_with_thousand_sep(measurement.avg),
+        measurement.unit.as_deref().unwrap_or("ns"),
+        spread,
+        width = avg_width,
+    );
+    if let Some(mib_per_second) = mib_per_second(measurement) {
+        out += &format!(" = {:.2} MiB/s", mib_per_second);
+    }
+    out
+}
+
+/// Returns the throughput of a benchmark that set `bytes`, if the average is
+/// in nanoseconds (i.e. no other `unit` is set) and not 0.
+fn mib_per_second(measurement: &Measurement) -> Option<f64> {
+    match (measurement.bytes, &measurement.unit, measurement.avg) {
+        (Some(bytes), None, avg) if avg > 0 => {
+            Some(bytes as f64 * 1e9 / avg as f64 / (1024.0 * 1024.0))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the reason why the test is ignored (see
+/// `Trial::with_ignored_reason`) if it is reported as ignored with the given
+/// outcome. All formats read the reason from here.
+fn ignored_reason<'i>(info: &'i TestInfo, outcome: &Outcome) -> Option<&'i str> {
+    match outcome {
+        Outcome::Ignored => info.ignored_reason(),
+        _ => None,
+    }
+}
+
+/// Returns the `"stdout"` key of a failed test's JSON event: the captured
+/// `output` followed by the failure message (like in the failures block of
+/// the pretty format). Empty if there is neither.
+fn json_stdout(output: Option<&str>, msg: Option<&str>) -> String {
+    let mut stdout = output.unwrap_or("").to_owned();
+    if let Some(msg) = msg {
+        if !stdout.is_empty() && !stdout.ends_with('\n') {
+            stdout.push('\n');
+        }
+        stdout.push_str(&format!("Error: \"{}\"\n", msg));
+    }
+    match stdout.is_empty() {
+        true => String::new(),
+        false => format!(r#", "stdout": "{}""#, escape8259::escape(stdout)),
+    }
+}
+
+/// Number of outcome characters per line in terse mode (like libtest).
+const TERSE_LINE_WIDTH: u64 = 88;
+
+/// Names are never truncated to fewer characters than this, even if the
+/// terminal is very narrow.
+const MIN_NAME_WIDTH: usize = 10;
+
+/// Truncates `name` to at most `width` characters, replacing the last
+/// character(s) with an ellipsis if it had to be shortened. With `ascii`, the
+/// ellipsis is `...` instead of `…`.
+fn truncate_name(name: &str, width: usize, ascii: bool) -> Cow<'_, str> {
+    if name.chars().count() <= width {
+        Cow::Borrowed(name)
+    } else {
+        let ellipsis = if ascii { "..." } else { "…" };
+        let keep = width.saturating_sub(ellipsis.chars().count());
+        let mut out = name.chars().take(keep).collect::<String>();
+        out.push_str(ellipsis);
+        Cow::Owned(out)
+    }
+}
+
+/// Removes all ANSI escape sequences (CSI sequences like `\x1b[31m` and OSC
+/// sequences like hyperlinks) from the given string.
+pub(crate) fn strip_ansi(s: &str) -> Cow<'_, str> {
+    if !s.contains('\x1b') {
+        return Cow::Borrowed(s);
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            // CSI: parameters and intermediate bytes, terminated by a byte in
+            // the range `@` to `~`.
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: terminated by BEL or ST (`ESC \`).
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Other two-character sequences.
+            _ => {}
         }
     }
+
+    Cow::Owned(out)
 }
 
 /// Formats the given integer with `,` as thousand separator.
@@ -346,15 +1580,79 @@ pub fn fmt_with_thousand_sep(mut v: u64) -> String {
     out
 }
 
-/// Returns the `ColorSpec` associated with the given outcome.
-fn color_of_outcome(outcome: &Outcome) -> ColorSpec {
-    let mut out = ColorSpec::new();
-    let color = match outcome {
-        Outcome::Passed => Color::Green,
-        Outcome::Failed { .. } => Color::Red,
-        Outcome::Ignored => Color::Yellow,
-        Outcome::Measured { .. } => Color::Cyan,
-    };
-    out.set_fg(Some(color));
-    out
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate() {
+        assert_eq!(truncate_name("foo", 3, false), "foo");
+        assert_eq!(truncate_name("foo", 10, false), "foo");
+        assert_eq!(truncate_name("foobar", 4, false), "foo…");
+        assert_eq!(truncate_name("äöüß", 2, false), "ä…");
+    }
+
+    #[test]
+    fn truncate_ascii() {
+        assert_eq!(truncate_name("foo", 3, true), "foo");
+        assert_eq!(truncate_name("foobar", 5, true), "fo...");
+        assert!(truncate_name("a_very_long_test_name", 10, true).is_ascii());
+    }
+
+    #[test]
+    fn terminal_width() {
+        let tests = vec![
+            Trial::test("short", || Ok(())),
+            Trial::test("a_very_long_test_name_that_does_not_fit", || Ok(())),
+        ];
+        let args = Arguments { format: Some(FormatSetting::Pretty), ..Arguments::default() };
+        let config = RunConfig::default();
+        let mut out = Vec::new();
+        let mut printer = Printer::with_output(&args, &config, Box::new(NoColor::new(&mut out)));
+        printer.terminal_width = Some(40);
+        printer.set_name_widths(&tests);
+        printer.print_test(&tests[1].info);
+        printer.print_test(&tests[0].info);
+        drop(printer);
+
+        // 40 columns minus "test ", " ... " and "ignored".
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, "test a_very_long_test_name_… ... test short                   ... ");
+    }
+
+    #[test]
+    fn write_errors() {
+        struct Failing(usize);
+        impl io::Write for Failing {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                self.0 += 1;
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut failing = Failing(0);
+        let args = Arguments::default();
+        let config = RunConfig::default();
+        let out = Box::new(NoColor::new(&mut failing));
+        let mut printer = Printer::with_output(&args, &config, out);
+        let errors = printer.track_write_errors();
+        printer.print_title(3);
+        printer.print_title(3);
+        drop(printer);
+
+        assert!(errors.occurred.load(Ordering::SeqCst));
+        assert_eq!(errors.take().map(|e| e.kind()), Some(io::ErrorKind::BrokenPipe));
+        assert_eq!(failing.0, 1, "output after the first error is discarded");
+    }
+
+    #[test]
+    fn strip() {
+        assert_eq!(strip_ansi("no colors"), "no colors");
+        assert_eq!(strip_ansi("\x1b[31mred\x1b[0m text"), "red text");
+        assert_eq!(strip_ansi("\x1b[1;38;5;208mbold\x1b[m"), "bold");
+        assert_eq!(strip_ansi("\x1b]8;;https://x.y\x1b\\link\x1b]8;;\x07!"), "link!");
+    }
 }
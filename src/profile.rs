@@ -0,0 +1,33 @@
+//! Writing a timeline of all test runs (`--profile`).
+//!
+//! The file uses the Chrome Trace Event format (JSON object with a
+//! `traceEvents` array of "complete" events), which can be opened with
+//! Perfetto (<https://ui.perfetto.dev>) or `chrome://tracing`. Each test that
+//! ran is one span named after the test. Its `tid` is the index of the thread
+//! that ran it (in order of first use), and `ts`/`dur` are in microseconds,
+//! with `ts` relative to the start of the first test.
+
+use std::{fs, io, time::UNIX_EPOCH};
+
+use crate::timing::Timing;
+
+/// Writes the given `(test name, timing)` spans to a profile file.
+pub(crate) fn write(path: &str, spans: &[(String, Timing)]) -> io::Result<()> {
+    let micros = |timing: &Timing| {
+        timing.start.duration_since(UNIX_EPOCH).unwrap_or_default().as_micros()
+    };
+    let first = spans.iter().map(|(_, timing)| micros(timing)).min().unwrap_or(0);
+
+    let events = spans.iter()
+        .map(|(name, timing)| {
+            format!(
+                r#"{{ "name": "{}", "ph": "X", "ts": {}, "dur": {}, "pid": 1, "tid": {} }}"#,
+                escape8259::escape(name),
+                micros(timing) - first,
+                timing.duration.as_micros(),
+                timing.thread,
+            )
+        })
+        .collect::<Vec<_>>();
+    fs::write(path, format!("{{ \"traceEvents\": [\n{}\n] }}\n", events.join(",\n")))
+}
@@ -0,0 +1,42 @@
+//! Re-running failed tests with more diagnostics (`--bisect-verbose`).
+//!
+//! Runners are `FnOnce` and cannot be called twice, so failed tests are re-run
+//! by executing the current binary again, once per test. This requires that
+//! the binary creates the same tests on every invocation, i.e. that its `main`
+//! acts as factory for the tests.
+
+use std::{env, process::Command};
+
+/// Environment variable set for the re-executed binary. If it is set,
+/// `--bisect-verbose` is ignored to avoid re-running recursively.
+const RERUN_ENV: &str = "LIBTEST_MIMIC_VERBOSE_RERUN";
+
+/// Returns whether this process is a re-run started by `rerun_verbose`.
+pub(crate) fn is_rerun() -> bool {
+    env::var_os(RERUN_ENV).is_some()
+}
+
+/// Re-runs each of the given tests in a new process of the current binary with
+/// `RUST_BACKTRACE=1` and without capturing. Returns the combined output
+/// (stdout, then stderr) of each re-run.
+pub(crate) fn rerun_verbose(names: &[String]) -> Vec<(String, String)> {
+    names.iter()
+        .map(|name| {
+            let output = env::current_exe()
+                .and_then(|exe| {
+                    Command::new(exe)
+                        .args([name.as_str(), "--exact", "--nocapture", "--test-threads", "1"])
+                        .env("RUST_BACKTRACE", "1")
+                        .env(RERUN_ENV, "1")
+                        .output()
+                })
+                .map(|output| {
+                    let mut s = String::from_utf8_lossy(&output.stdout).into_owned();
+                    s.push_str(&String::from_utf8_lossy(&output.stderr));
+                    s
+                })
+                .unwrap_or_else(|e| format!("failed to re-run test: {e}\n"));
+            (name.clone(), output)
+        })
+        .collect()
+}
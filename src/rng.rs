@@ -0,0 +1,75 @@
+//! A tiny, deterministic PRNG used for all randomness in this crate.
+//!
+//! All randomness of a run is derived from a single master seed (`--seed`),
+//! so that a run can be reproduced exactly. Every subsystem gets its own
+//! sub-seed derived from the master seed, so that e.g. enabling an additional
+//! feature does not change the shuffled order of tests.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The parts of this crate that use randomness. Each gets its own sub-seed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Subsystem {
+    /// The order of tests with `--shuffle`.
+    Shuffle = 1,
+}
+
+/// Returns a master seed derived from the system clock. Used if no seed is
+/// specified explicitly.
+pub(crate) fn seed_from_clock() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// A SplitMix64 generator. Not cryptographically secure, but fast, small and
+/// good enough for shuffling tests.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator for the given subsystem from the master seed.
+    pub(crate) fn new(master_seed: u64, subsystem: Subsystem) -> Self {
+        let mut derive = Self { state: master_seed ^ (subsystem as u64) };
+        Self { state: derive.next_u64() }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`. `bound` must not be 0.
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Shuffles the slice in place (Fisher-Yates).
+    pub(crate) fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            let j = self.below(i + 1);
+            slice.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deterministic() {
+        let mut a = (0..20).collect::<Vec<_>>();
+        let mut b = a.clone();
+        Rng::new(42, Subsystem::Shuffle).shuffle(&mut a);
+        Rng::new(42, Subsystem::Shuffle).shuffle(&mut b);
+        assert_eq!(a, b);
+        assert_ne!(a, (0..20).collect::<Vec<_>>());
+    }
+}
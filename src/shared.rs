@@ -0,0 +1,108 @@
+//! Trials whose runners get a reference to a context shared by all trials
+//! (see [`SharedTrial`][crate::SharedTrial]).
+
+use std::sync::Arc;
+
+use crate::{Failed, Outcome, TestInfo, Trial};
+
+type SharedRunner<C> = Box<dyn FnOnce(&C) -> Result<(), Failed> + Send>;
+
+/// A test whose runner gets a reference to a shared context of type `C`. Turn
+/// them into normal trials via [`into_trials`][Self::into_trials] to run
+/// them.
+///
+/// This is useful for read-only configuration used by many tests (e.g.
+/// endpoints or credentials), as it avoids cloning an `Arc` into every
+/// runner closure.
+pub struct SharedTrial<C> {
+    runner: SharedRunner<C>,
+    info: TestInfo,
+}
+
+impl<C: Send + Sync + 'static> SharedTrial<C> {
+    /// Creates a (non-benchmark) test with the given name and runner. See
+    /// [`Trial::test`].
+    pub fn test<R>(name: impl Into<String>, runner: R) -> Self
+    where
+        R: FnOnce(&C) -> Result<(), Failed> + Send + 'static,
+    {
+        Self {
+            runner: Box::new(runner),
+            info: TestInfo::new(name.into(), false),
+        }
+    }
+
+    /// Sets the "kind" of this test. See [`Trial::with_kind`].
+    pub fn with_kind(self, kind: impl Into<String>) -> Self {
+        self.map_info(|trial| trial.with_kind(kind))
+    }
+
+    /// Sets whether or not this test is considered "ignored". See
+    /// [`Trial::with_ignored_flag`].
+    pub fn with_ignored_flag(self, is_ignored: bool) -> Self {
+        self.map_info(|trial| trial.with_ignored_flag(is_ignored))
+    }
+
+    /// Returns the name of this test.
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    /// Applies a builder method of `Trial` to the metadata of this test.
+    fn map_info(self, f: impl FnOnce(Trial) -> Trial) -> Self {
+        let trial = f(Trial {
+            runner: Box::new(|_| Outcome::Passed),
+            repeatable: None,
+            future: None,
+            info: self.info,
+        });
+        Self { info: trial.info, ..self }
+    }
+
+    /// Turns the given tests into normal trials whose runners get a reference
+    /// to `ctx`.
+    ///
+    /// As the tests are run in a thread pool (unless `--test-threads 1` is
+    /// set), the context is accessed from several threads at the same time.
+    /// That's why it has to be `Send + Sync`. It is only ever accessed via
+    /// shared reference, so use interior mutability (e.g. a `Mutex`) if it
+    /// needs to change.
+    ///
+    /// ```no_run
+    /// use libtest_mimic::{Arguments, SharedTrial};
+    ///
+    /// struct Config {
+    ///     endpoint: String,
+    /// }
+    ///
+    /// let tests = vec![
+    ///     SharedTrial::test("endpoint_set", |config: &Config| {
+    ///         if config.endpoint.is_empty() {
+    ///             return Err("no endpoint".into());
+    ///         }
+    ///         Ok(())
+    ///     }),
+    /// ];
+    /// let config = Config { endpoint: "localhost:8080".into() };
+    /// let tests = SharedTrial::into_trials(tests, config);
+    /// libtest_mimic::run(&Arguments::from_args(), tests).exit();
+    /// ```
+    pub fn into_trials(tests: Vec<Self>, ctx: C) -> Vec<Trial> {
+        let ctx = Arc::new(ctx);
+        tests.into_iter().map(|test| test.into_trial(Arc::clone(&ctx))).collect()
+    }
+
+    /// Turns this into a normal trial that calls the runner with `ctx`.
+    fn into_trial(self, ctx: Arc<C>) -> Trial {
+        let runner = self.runner;
+        Trial {
+            runner: Box::new(move |_test_mode| match runner(&ctx) {
+                Ok(()) => Outcome::Passed,
+                Err(failed) => Outcome::Failed(failed),
+            }),
+            repeatable: None,
+            future: None,
+            info: self.info,
+        }
+    }
+}
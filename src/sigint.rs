@@ -0,0 +1,72 @@
+//! Graceful handling of `SIGINT` (Ctrl-C) while a run is active (see
+//! `RunConfig::with_handle_sigint`).
+//!
+//! Signal handlers cannot be removed again without unsafe code, so the
+//! actions registered here stay installed once the first run handles
+//! `SIGINT`. They run in this order:
+//! 1. If no run is active, the default disposition is emulated (i.e. the
+//!    process is terminated), as if the handler was never installed.
+//! 2. If a run was already interrupted by a previous `SIGINT`, the process is
+//!    terminated as well, so that a second Ctrl-C does not wait for the
+//!    running tests.
+//! 3. Otherwise, the interrupt flag of the active runs is set.
+//!
+//! A handler that was installed before (e.g. via the `ctrlc` crate) is still
+//! called for every `SIGINT` by `signal-hook`.
+
+use std::{
+    io,
+    sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex},
+};
+
+use signal_hook::{consts::SIGINT, flag};
+
+/// The flags read by the registered actions.
+struct Flags {
+    /// Whether no run is active.
+    inactive: Arc<AtomicBool>,
+
+    /// Whether a `SIGINT` arrived during the active runs.
+    received: Arc<AtomicBool>,
+}
+
+/// The number of active runs and the flags, once the actions are registered.
+/// Several runs can be active at the same time, e.g. when `run_with_config`
+/// is called from several `#[test]` functions.
+static INSTALLED: Mutex<(usize, Option<Flags>)> = Mutex::new((0, None));
+
+/// Starts handling `SIGINT` and returns the flag that is set once it arrives.
+/// Has to be paired with a call to `uninstall`.
+pub(crate) fn install() -> io::Result<Arc<AtomicBool>> {
+    let mut guard = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+    let installed = &mut *guard;
+    let flags = match &mut installed.1 {
+        Some(flags) => flags,
+        slot @ None => {
+            let flags = Flags {
+                inactive: Arc::new(AtomicBool::new(true)),
+                received: Arc::new(AtomicBool::new(false)),
+            };
+            flag::register_conditional_default(SIGINT, Arc::clone(&flags.inactive))?;
+            flag::register_conditional_default(SIGINT, Arc::clone(&flags.received))?;
+            flag::register(SIGINT, Arc::clone(&flags.received))?;
+            slot.insert(flags)
+        }
+    };
+    if installed.0 == 0 {
+        flags.received.store(false, Ordering::SeqCst);
+        flags.inactive.store(false, Ordering::SeqCst);
+    }
+    installed.0 += 1;
+    Ok(Arc::clone(&flags.received))
+}
+
+/// Restores the default behavior of `SIGINT` once the last active run
+/// finished.
+pub(crate) fn uninstall() {
+    let mut installed = INSTALLED.lock().unwrap_or_else(|e| e.into_inner());
+    installed.0 -= 1;
+    if let (0, Some(flags)) = &*installed {
+        flags.inactive.store(true, Ordering::SeqCst);
+    }
+}
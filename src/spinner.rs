@@ -0,0 +1,61 @@
+//! Animated spinner shown next to the running test (`--spinner`).
+
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+const FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const ASCII_FRAMES: &[char] = &['|', '/', '-', '\\'];
+const INTERVAL: Duration = Duration::from_millis(100);
+
+/// A spinner drawn at the cursor position by a background thread. Dropping it
+/// stops the thread and erases the spinner, leaving the cursor where it was.
+pub(crate) struct Spinner {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Spinner {
+    /// Starts drawing the spinner to stdout. The caller must have flushed
+    /// everything it printed before.
+    pub(crate) fn start(ascii: bool) -> Self {
+        let frames = if ascii { ASCII_FRAMES } else { FRAMES };
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = Arc::clone(&stop);
+            thread::spawn(move || {
+                // Each frame is followed by a backspace, so that the next
+                // frame (or the outcome) overwrites it.
+                for frame in frames.iter().cycle() {
+                    thread::park_timeout(INTERVAL);
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let mut stdout = io::stdout().lock();
+                    let _ = write!(stdout, "{}\x08", frame);
+                    let _ = stdout.flush();
+                }
+            })
+        };
+        Self { stop, thread: Some(thread) }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+        let mut stdout = io::stdout().lock();
+        let _ = write!(stdout, " \x08");
+        let _ = stdout.flush();
+    }
+}
@@ -0,0 +1,61 @@
+//! Running tests in a fresh temporary working directory (see
+//! [`Trial::with_temp_cwd`][crate::Trial::with_temp_cwd]).
+//!
+//! The working directory is process-global, so tests using this are always
+//! run sequentially on the main thread (see `run_tests`).
+
+use std::{
+    env, fs, io,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::Outcome;
+
+/// Wraps the given runner so that it runs in a new temporary directory. If
+/// the directory cannot be created, the test fails without being run.
+pub(crate) fn wrap(
+    runner: Box<dyn FnOnce(bool) -> Outcome + Send>,
+) -> Box<dyn FnOnce(bool) -> Outcome + Send> {
+    Box::new(move |test_mode| match TempCwd::enter() {
+        Ok(_cwd) => runner(test_mode),
+        Err(e) => {
+            Outcome::Failed(format!("failed to create temporary working directory: {}", e).into())
+        }
+    })
+}
+
+/// A temporary directory that is the current working directory while this
+/// value is alive. When dropped (also during unwinding after a panic), the
+/// previous working directory is restored and the directory is removed with
+/// all its contents.
+struct TempCwd {
+    dir: PathBuf,
+    prev: PathBuf,
+}
+
+impl TempCwd {
+    fn enter() -> io::Result<Self> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+        let prev = env::current_dir()?;
+        let n = NEXT.fetch_add(1, Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("libtest-mimic-{}-{}", process::id(), n));
+        fs::create_dir_all(&dir)?;
+        if let Err(e) = env::set_current_dir(&dir) {
+            let _ = fs::remove_dir_all(&dir);
+            return Err(e);
+        }
+        Ok(Self { dir, prev })
+    }
+}
+
+impl Drop for TempCwd {
+    fn drop(&mut self) {
+        // Errors are ignored: failing the test (or panicking during unwinding)
+        // just because cleanup failed would not be helpful.
+        let _ = env::set_current_dir(&self.prev);
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
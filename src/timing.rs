@@ -0,0 +1,112 @@
+//! Measuring when and how long tests run.
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Wall clock start and end time of a single test run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Timing {
+    pub(crate) start: SystemTime,
+    pub(crate) end: SystemTime,
+
+    /// Measured with a monotonic clock, so this is more accurate than the
+    /// difference between `end` and `start`.
+    pub(crate) duration: Duration,
+
+    /// Index of the thread that ran the test (see `thread_index`).
+    pub(crate) thread: usize,
+}
+
+impl Timing {
+    /// Calls `f` and measures when and how long it ran.
+    pub(crate) fn measure<T>(f: impl FnOnce() -> T) -> (T, Self) {
+        let start = SystemTime::now();
+        let start_instant = Instant::now();
+        let out = f();
+        let duration = start_instant.elapsed();
+        let end = SystemTime::now();
+        (out, Self { start, end, duration, thread: thread_index() })
+    }
+}
+
+/// Returns a small number identifying the current thread. Threads are
+/// numbered in the order in which they first call this, starting at 0.
+fn thread_index() -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static INDEX: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    INDEX.with(|index| *index)
+}
+
+/// Sorts the given `(name, duration)` pairs by descending duration. Ties are
+/// broken by name, so that the order is deterministic. All sorting by a
+/// duration in this crate should use this.
+pub(crate) fn sort_by_duration(entries: &mut [(String, Duration)]) {
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+}
+
+/// Formats the given time as ISO 8601 string in UTC with millisecond
+/// precision, e.g. `2024-05-10T13:37:00.123Z`.
+pub(crate) fn fmt_iso8601(time: SystemTime) -> String {
+    // Times before the epoch are not really relevant for test runs.
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let days = (secs / 86400) as i64;
+    let secs_of_day = secs % 86400;
+
+    // Convert days since epoch to a civil date. See
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iso8601() {
+        let at = |secs, millis| {
+            UNIX_EPOCH + Duration::from_secs(secs) + Duration::from_millis(millis)
+        };
+        assert_eq!(fmt_iso8601(at(0, 0)), "1970-01-01T00:00:00.000Z");
+        assert_eq!(fmt_iso8601(at(951_782_400, 5)), "2000-02-29T00:00:00.005Z");
+        assert_eq!(fmt_iso8601(at(1_715_348_220, 123)), "2024-05-10T13:37:00.123Z");
+    }
+
+    #[test]
+    fn sort_ties_by_name() {
+        let ms = Duration::from_millis;
+        let mut entries = vec![
+            ("c".to_owned(), ms(5)),
+            ("b".to_owned(), ms(5)),
+            ("slow".to_owned(), ms(9)),
+            ("a".to_owned(), ms(5)),
+            ("fast".to_owned(), ms(1)),
+        ];
+        sort_by_duration(&mut entries);
+        let names = entries.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["slow", "a", "b", "c", "fast"]);
+    }
+}
@@ -0,0 +1,138 @@
+use common::{args, do_run};
+use libtest_mimic::{Conclusion, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("first", || Ok(())),
+        Trial::test("broken", || Err("nope".into())),
+        Trial::test("skipped", || Ok(())),
+        Trial::test("cleanup", || Ok(())).with_always_run(true),
+        Trial::test("also_skipped", || Err("nope".into())),
+        Trial::test("validate", || Err("invalid".into())).with_always_run(true),
+    ]
+}
+
+#[test]
+fn max_failures_with_always_run() {
+    let (c, out) = do_run(args(["--max-failures", "1", "--test-threads", "1"]), tests());
+    assert_eq!(c, conclusion! {
+        num_filtered_out: 0,
+        num_passed: 2,
+        num_failed: 2,
+        num_ignored: 0,
+        num_measured: 0,
+        num_not_run: 2,
+        not_run: vec!["skipped".into(), "also_skipped".into()],
+    });
+    assert_log!(out, "
+        running 6 tests
+        test first        ... ok
+        test broken       ... FAILED
+        test cleanup      ... ok
+        test validate     ... FAILED
+
+        run aborted due to too many failures: 2 tests not run
+
+        failures:
+
+        ---- broken ----
+        nope
+
+        ---- validate ----
+        invalid
+
+
+        failures:
+            broken
+            validate
+
+        test result: FAILED. 2 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn max_failures_in_parallel() {
+    let tests = || {
+        let mut tests = vec![
+            Trial::test("broken", || Err("nope".into())),
+            Trial::test("slow", || {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                Ok(())
+            }),
+        ];
+        tests.extend((0..10).map(|i| Trial::test(format!("never_{i}"), || Ok(()))));
+        tests.push(Trial::test("cleanup", || Ok(())).with_always_run(true));
+        tests
+    };
+
+    let (c, _) = do_run(args(["--max-failures", "1", "--test-threads", "2"]), tests());
+    assert_eq!(c, conclusion! {
+        num_filtered_out: 0,
+        num_passed: 2,
+        num_failed: 1,
+        num_ignored: 0,
+        num_measured: 0,
+        num_not_run: 10,
+        not_run: (0..10).map(|i| format!("never_{i}")).collect(),
+    });
+}
+
+#[test]
+fn not_aborted_below_limit() {
+    let (c, _) = do_run(args(["--max-failures", "3"]), tests());
+    assert_eq!(c.num_failed, 3);
+    assert_eq!(c.num_passed, 3);
+    assert_eq!(c.num_filtered_out, 0);
+    assert_eq!(c.num_not_run, 0);
+}
+
+#[test]
+fn counts_add_up() {
+    fn total(c: &Conclusion) -> u64 {
+        c.num_filtered_out + c.num_passed + c.num_failed + c.num_ignored
+            + c.num_measured + c.num_not_run
+    }
+
+    let with_ignored = || {
+        let mut tests = tests();
+        tests.push(Trial::test("ignored", || Ok(())).with_ignored_flag(true));
+        tests.push(Trial::bench("bench", |_| Ok(None)));
+        tests
+    };
+    let scenarios: &[&[&str]] = &[
+        &[],
+        &["--max-failures", "1"],
+        &["--max-failures", "2", "--skip", "first"],
+        &["--max-failures", "1", "--test"],
+        &["--max-failures", "1", "--include-ignored", "--test-threads", "1"],
+        &["--ignored"],
+    ];
+    for scenario in scenarios {
+        let mut v = vec!["<dummy-executable>"];
+        v.extend(scenario.iter());
+        let (c, _) = do_run(libtest_mimic::Arguments::from_iter(v), with_ignored());
+        assert_eq!(total(&c), 8, "scenario {scenario:?}: {c:?}");
+        assert_eq!(c.num_not_run, c.not_run.len() as u64);
+    }
+}
+
+#[test]
+fn fail_fast() {
+    let (c, out) = do_run(args(["--fail-fast", "--test-threads", "1"]), tests());
+    assert_eq!(c.num_passed, 2);
+    assert_eq!(c.num_failed, 2);
+    assert_eq!(c.not_run, ["skipped", "also_skipped"]);
+    assert!(out.contains("\nrun aborted due to too many failures: 2 tests not run\n"), "{out}");
+
+    let (c, _) = do_run(args(["--fail-fast", "--test-threads", "1"]), vec![
+        Trial::test("a", || Ok(())),
+        Trial::test("b", || Ok(())),
+    ]);
+    assert_eq!(c.num_passed, 2);
+    assert_eq!(c.num_not_run, 0);
+}
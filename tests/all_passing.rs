@@ -1,5 +1,5 @@
 use common::{args, check};
-use libtest_mimic::{Trial, Conclusion};
+use libtest_mimic::Trial;
 use pretty_assertions::assert_eq;
 
 use crate::common::do_run;
@@ -19,7 +19,7 @@ fn tests() -> Vec<Trial> {
 #[test]
 fn normal() {
     check(args([]), tests, 3,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 0,
             num_passed: 3,
             num_failed: 0,
@@ -37,7 +37,7 @@ fn normal() {
 #[test]
 fn filter_one() {
     check(args(["foo"]), tests, 1,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 2,
             num_passed: 1,
             num_failed: 0,
@@ -51,7 +51,7 @@ fn filter_one() {
 #[test]
 fn filter_two() {
     check(args(["bar"]), tests, 2,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 1,
             num_passed: 2,
             num_failed: 0,
@@ -69,7 +69,7 @@ fn filter_two() {
 #[test]
 fn filter_exact() {
     check(args(["bar", "--exact"]), tests, 1,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 2,
             num_passed: 1,
             num_failed: 0,
@@ -80,10 +80,61 @@ fn filter_exact() {
     );
 }
 
+#[test]
+fn filter_multiple() {
+    check(args(["foo", "barro"]), tests, 2,
+        conclusion! {
+            num_filtered_out: 1,
+            num_passed: 2,
+            num_failed: 0,
+            num_ignored: 0,
+            num_measured: 0,
+        },
+        "
+            test foo   ... ok
+            test barro ... ok
+        ",
+    );
+}
+
+#[test]
+fn filter_multiple_exact() {
+    check(args(["--exact", "bar", "foo", "barr"]), tests, 2,
+        conclusion! {
+            num_filtered_out: 1,
+            num_passed: 2,
+            num_failed: 0,
+            num_ignored: 0,
+            num_measured: 0,
+        },
+        "
+            test foo ... ok
+            test bar ... ok
+        ",
+    );
+}
+
+#[test]
+fn filter_multiple_and_skip() {
+    check(args(["foo", "bar", "--skip", "barro"]), tests, 2,
+        conclusion! {
+            num_filtered_out: 1,
+            num_passed: 2,
+            num_failed: 0,
+            num_ignored: 0,
+            num_measured: 0,
+        },
+        "
+            test foo ... ok
+            test bar ... ok
+        ",
+    );
+}
+
 #[test]
 fn filter_two_and_skip() {
     check(args(["--skip", "barro", "bar"]), tests, 1,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 2,
             num_passed: 1,
             num_failed: 0,
@@ -97,7 +148,7 @@ fn filter_two_and_skip() {
 #[test]
 fn skip_nothing() {
     check(args(["--skip", "peter"]), tests, 3,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 0,
             num_passed: 3,
             num_failed: 0,
@@ -115,7 +166,7 @@ fn skip_nothing() {
 #[test]
 fn skip_two() {
     check(args(["--skip", "bar"]), tests, 1,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 2,
             num_passed: 1,
             num_failed: 0,
@@ -129,7 +180,7 @@ fn skip_two() {
 #[test]
 fn skip_exact() {
     check(args(["--exact", "--skip", "bar"]), tests, 2,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 1,
             num_passed: 2,
             num_failed: 0,
@@ -146,7 +197,7 @@ fn skip_exact() {
 #[test]
 fn terse_output() {
     let (c, out) = do_run(args(["--format", "terse"]), tests());
-    assert_eq!(c, Conclusion {
+    assert_eq!(c, conclusion! {
         num_filtered_out: 0,
         num_passed: 3,
         num_failed: 0,
@@ -160,3 +211,20 @@ fn terse_output() {
             finished in 0.00s
     ");
 }
+
+#[test]
+fn terse_output_wraps() {
+    for threads in ["1", "4"] {
+        let tests = (0..200).map(|i| Trial::test(format!("t{i}"), || Ok(()))).collect();
+        let (_, out) = do_run(args(["-q", "--test-threads", threads]), tests);
+        let dots = ".".repeat(88);
+        assert_log!(out, &format!("
+            running 200 tests
+            {dots} 88/200
+            {dots} 176/200
+            {}
+            test result: ok. 200 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+                finished in 0.00s
+        ", ".".repeat(24)));
+    }
+}
@@ -0,0 +1,33 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("ident", || Ok(())).with_module("lexer::tokens"),
+        Trial::test("expr", || Ok(())).with_module("parser"),
+        Trial::test("a_test_name_that_is_much_too_long_for_the_terminal", || Ok(())),
+    ]
+}
+
+#[test]
+fn tree_list() {
+    let (_, out) = do_run(args(["--list", "--ascii"]), tests());
+    assert!(out.is_ascii(), "{out}");
+    assert_log!(out, "
+        a_test_name_that_is_much_too_long_for_the_terminal: test
+        lexer:
+            tokens:
+                ident: test
+        parser:
+            expr: test
+    ");
+}
+
+#[test]
+fn pretty_output() {
+    let (_, out) = do_run(args(["--ascii", "--format", "pretty", "--test-threads", "1"]), tests());
+    assert!(out.is_ascii(), "{out}");
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "async")]
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread,
+    time::Duration,
+};
+
+use common::{args, do_run, do_run_with_config};
+use libtest_mimic::{RunConfig, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+/// A future that is pending until another thread woke it after a short time.
+struct WokenLater(bool);
+
+impl Future for WokenLater {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        let waker = cx.waker().clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            waker.wake();
+        });
+        Poll::Pending
+    }
+}
+
+/// A busy-polling executor does not need to be woken.
+struct NoopWaker;
+
+impl Wake for NoopWaker {
+    fn wake(self: Arc<Self>) {}
+}
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::async_test("passes", || async {
+            WokenLater(false).await;
+            Ok(())
+        }),
+        Trial::async_test("fails", || async { Err("nope".into()) }),
+        Trial::async_test("panics", || async { panic!("boom") }),
+        Trial::test("sync", || Ok(())),
+    ]
+}
+
+#[test]
+fn builtin_executor() {
+    let (c, out) = do_run(args(["--test-threads", "1"]), tests());
+    assert_eq!(c, conclusion! {
+        num_passed: 2,
+        num_failed: 2,
+    });
+    assert!(out.contains("---- fails ----\nnope\n"), "{out}");
+    assert!(out.contains("---- panics ----\ntest panicked: boom\n"), "{out}");
+}
+
+#[test]
+fn custom_executor() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let config = RunConfig::default().with_block_on(move |future| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let mut future = future;
+        let waker = Waker::from(Arc::new(NoopWaker));
+        loop {
+            if let Poll::Ready(result) = future.as_mut().poll(&mut Context::from_waker(&waker)) {
+                return result;
+            }
+            thread::yield_now();
+        }
+    });
+    let (c, _) = do_run_with_config(args(["--test-threads", "4"]), tests(), config);
+    assert_eq!(c.num_passed, 2);
+    assert_eq!(c.num_failed, 2);
+    // Only async tests go through the executor.
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+use common::{args, do_run, in_subprocess, run_in_subprocess};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("kept", || Ok(())),
+        Trial::test("renamed_new", || Ok(())),
+        Trial::test("kept", || Ok(())).with_kind("net"),
+    ]
+}
+
+fn baseline_file(name: &str) -> PathBuf {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, "kept\n[net] kept\n\nrenamed_old\n").unwrap();
+    path
+}
+
+#[test]
+fn prints_added_and_removed() {
+    let path = baseline_file("libtest_mimic_baseline_diff.txt");
+    let (c, out) = do_run(
+        args(["--baseline-list", path.to_str().unwrap(), "--test-threads", "1"]),
+        tests(),
+    );
+    assert_eq!(c.num_passed, 3);
+    assert_log!(out, "
+        compared to the baseline list: 1 test added, 1 test removed
+            + renamed_new
+            - renamed_old
+
+        running 3 tests
+        test       kept        ... ok
+        test       renamed_new ... ok
+        test [net] kept        ... ok
+
+        test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn unchanged_prints_nothing() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_baseline_same.txt");
+    std::fs::write(&path, "kept\nrenamed_new\n[net] kept\n").unwrap();
+    let args = args(["--baseline-list", path.to_str().unwrap(), "--deny-removed-tests"]);
+    let (_, out) = do_run(args, tests());
+    assert!(out.starts_with("\nrunning 3 tests\n"), "{out}");
+}
+
+#[test]
+fn deny_removed_tests() {
+    let path = baseline_file("libtest_mimic_baseline_deny.txt");
+    if in_subprocess() {
+        let _ = do_run(
+            args(["--baseline-list", path.to_str().unwrap(), "--deny-removed-tests"]),
+            tests(),
+        );
+        unreachable!();
+    }
+
+    let output = run_in_subprocess("deny_removed_tests");
+    assert_eq!(output.status.code(), Some(101));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("error: 1 test from the baseline list removed (`--deny-removed-tests`)"),
+        "{stderr}",
+    );
+}
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use common::{args, do_run, in_subprocess, subprocess_command};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+fn xml_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bazel_xml_test.xml")
+}
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("math::adds", || Ok(())),
+        Trial::test("fails", || Err("1 != 2".into())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+/// Returns the value of the attribute `name` in `element`.
+fn attr<'a>(element: &'a str, name: &str) -> &'a str {
+    let start = element.find(&format!(" {name}=\"")).expect(name) + name.len() + 3;
+    let len = element[start..].find('"').unwrap();
+    &element[start..start + len]
+}
+
+#[test]
+fn writes_xml_output_file() {
+    if in_subprocess() {
+        let (_, out) = do_run(args(["--test-threads", "1"]), tests());
+        // The console output is not affected.
+        assert!(out.contains("test result: FAILED. 1 passed; 1 failed; 1 ignored;"), "{out}");
+        return;
+    }
+
+    let path = xml_path();
+    let _ = std::fs::remove_file(&path);
+    let output = subprocess_command("writes_xml_output_file")
+        .env("XML_OUTPUT_FILE", &path)
+        .env("TEST_TARGET", "//pkg:my_test")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let xml = std::fs::read_to_string(&path).unwrap();
+    let mut lines = xml.lines().map(str::trim);
+    assert_eq!(lines.next(), Some(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    assert_eq!(lines.next(), Some("<testsuites>"));
+
+    let suite = lines.next().unwrap();
+    assert!(suite.starts_with("<testsuite "), "{xml}");
+    assert_eq!(attr(suite, "name"), "//pkg:my_test");
+    assert_eq!(attr(suite, "tests"), "3");
+    assert_eq!(attr(suite, "failures"), "1");
+    assert_eq!(attr(suite, "errors"), "0");
+    assert_eq!(attr(suite, "skipped"), "1");
+
+    let cases = xml.lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("<testcase "))
+        .map(|line| {
+            let (classname, name) = (attr(line, "classname"), attr(line, "name"));
+            (classname, name, attr(line, "status"), attr(line, "result"))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(cases, [
+        ("math", "adds", "run", "completed"),
+        ("integration", "fails", "run", "completed"),
+        ("integration", "ignored", "notrun", "skipped"),
+    ]);
+    assert!(xml.contains(r#"<failure type="failure" message="1 != 2">1 != 2</failure>"#), "{xml}");
+    assert!(xml.trim_end().ends_with("</testsuites>"), "{xml}");
+}
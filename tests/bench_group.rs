@@ -0,0 +1,72 @@
+use common::{args, check, do_run};
+use libtest_mimic::{Measurement, Trial};
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    let mut tests = vec![Trial::test("plain", || Ok(()))];
+    tests.extend(Trial::bench_group("sum", [1u64, 10, 100], |n, test_mode| {
+        let sum = (0..n).sum::<u64>();
+        if test_mode {
+            Ok(None)
+        } else {
+            Ok(Some(Measurement { avg: sum, variance: 1, ..Measurement::default() }))
+        }
+    }));
+    tests
+}
+
+#[test]
+fn normal() {
+    check(args([]), tests, 4,
+        conclusion! {
+            num_filtered_out: 0,
+            num_passed: 4,
+            num_failed: 0,
+            num_ignored: 0,
+            num_measured: 0,
+        },
+        "
+            test plain    ... ok
+            test sum[1]   ... ok
+            test sum[10]  ... ok
+            test sum[100] ... ok
+        ",
+    );
+}
+
+#[test]
+fn bench_filtered() {
+    let measured = |avg| Measurement { avg, variance: 1, ..Measurement::default() };
+    check(args(["--bench", "sum"]), tests, 3,
+        conclusion! {
+            num_filtered_out: 1,
+            num_passed: 0,
+            num_failed: 0,
+            num_ignored: 0,
+            num_measured: 3,
+            measurements: vec![
+                ("sum[1]".into(), measured(0)),
+                ("sum[10]".into(), measured(45)),
+                ("sum[100]".into(), measured(4950)),
+            ],
+        },
+        "
+            test sum[1]   ... bench:           0 ns/iter (+/- 1)
+            test sum[10]  ... bench:          45 ns/iter (+/- 1)
+            test sum[100] ... bench:       4,950 ns/iter (+/- 1)
+        ",
+    );
+}
+
+#[test]
+fn unstable_benches() {
+    let (_, out) = do_run(args(["--bench", "--bench-unstable-threshold", "1"]), tests());
+    assert!(out.contains(
+        "\nunstable benchmarks:\n        inf% sum[1]\n        2.2% sum[10]\n\n",
+    ), "{out}");
+
+    let (_, out) = do_run(args(["--bench"]), tests());
+    assert!(!out.contains("unstable"));
+}
@@ -0,0 +1,89 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use common::{args, do_run};
+use libtest_mimic::{Measurement, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+/// A repeatable benchmark measuring 100, 200, 300, ... ns/iter on successive
+/// runs, and a normal benchmark that can only be run once.
+fn tests(runs: &Arc<AtomicU64>) -> Vec<Trial> {
+    let runs = Arc::clone(runs);
+    vec![
+        Trial::bench_repeatable("growing", move |test_mode| {
+            if test_mode {
+                return Ok(None);
+            }
+            let run = runs.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Some(Measurement { avg: 100 * run, variance: 2 * run, ..Measurement::default() }))
+        }),
+        Trial::bench("once", |test_mode| {
+            let measurement = Measurement { avg: 50, variance: 1, ..Measurement::default() };
+            Ok((!test_mode).then_some(measurement))
+        }),
+    ]
+}
+
+#[test]
+fn combines_runs() {
+    let runs = Arc::new(AtomicU64::new(0));
+    let args = args(["--bench", "--bench-iterations", "3", "--test-threads", "1"]);
+    let (c, out) = do_run(args, tests(&runs));
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+    assert_eq!(c.measurements, vec![
+        ("growing".to_owned(), Measurement {
+            avg: 200,
+            variance: 4,
+            min: Some(100),
+            max: Some(300),
+            ..Measurement::default()
+        }),
+        ("once".to_owned(), Measurement { avg: 50, variance: 1, ..Measurement::default() }),
+    ]);
+    assert_log!(out, "
+        running 2 tests
+        test growing ... bench:         200 ns/iter (+/- 4, min 100, max 300)
+        test once    ... bench:          50 ns/iter (+/- 1)
+
+        test result: ok. 0 passed; 0 failed; 0 ignored; 2 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn json_min_max() {
+    let runs = Arc::new(AtomicU64::new(0));
+    let (_, out) = do_run(
+        args(["--bench", "--bench-iterations", "2", "--format", "json", "--test-threads", "1"]),
+        tests(&runs),
+    );
+    assert!(out.contains(
+        concat!(
+            r#"{ "type": "bench", "name": "growing", "median": 150, "deviation": 3,"#,
+            r#" "min": 100, "max": 200 }"#,
+        ),
+    ), "{out}");
+}
+
+#[test]
+fn single_run_by_default() {
+    let runs = Arc::new(AtomicU64::new(0));
+    let (c, out) = do_run(args(["--bench", "--test-threads", "1"]), tests(&runs));
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    let expected = Measurement { avg: 100, variance: 2, ..Measurement::default() };
+    assert_eq!(c.measurements[0].1, expected);
+    assert!(out.contains("test growing ... bench:         100 ns/iter (+/- 2)\n"), "{out}");
+}
+
+#[test]
+fn test_mode_runs_once() {
+    let runs = Arc::new(AtomicU64::new(0));
+    let (c, _) = do_run(args(["--bench-iterations", "5"]), tests(&runs));
+    assert_eq!(c.num_passed, 2);
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,107 @@
+use common::{args, check};
+use libtest_mimic::{Measurement, Trial};
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    let measured = |avg| move |_| Ok(Some(Measurement { avg, ..Measurement::default() }));
+    vec![
+        Trial::bench_with_threshold("faster", 1000, 5.0, measured(900)),
+        Trial::bench_with_threshold("within", 1000, 5.0, measured(1050)),
+        Trial::bench_with_threshold("regressed", 1000, 5.0, measured(1200)),
+    ]
+}
+
+#[test]
+fn bench_mode() {
+    check(args(["--bench"]), tests, 3,
+        conclusion! {
+            num_failed: 1,
+            num_measured: 2,
+            measurements: vec![
+                ("faster".into(), Measurement { avg: 900, ..Measurement::default() }),
+                ("within".into(), Measurement { avg: 1050, ..Measurement::default() }),
+            ],
+        },
+        "
+            test faster    ... bench:         900 ns/iter (+/- 0)
+            test within    ... bench:       1,050 ns/iter (+/- 0)
+            test regressed ... FAILED
+
+            failures:
+
+            ---- regressed ----
+            benchmark regressed: 1,200 ns/iter vs. baseline 1,000 ns/iter (+20.0%, allowed: 5%)
+
+
+            failures:
+                regressed
+        ",
+    );
+}
+
+#[test]
+fn test_mode_does_not_compare() {
+    check(args([]), tests, 3,
+        conclusion! {
+            num_passed: 3,
+        },
+        "
+            test faster    ... ok
+            test within    ... ok
+            test regressed ... ok
+        ",
+    );
+}
+
+fn limited() -> Vec<Trial> {
+    let measured = |avg| move |_| Ok(Some(Measurement { avg, ..Measurement::default() }));
+    vec![
+        Trial::bench_with_limit("fast", 500_000, measured(420_000)),
+        Trial::bench_with_limit("at_limit", 500_000, measured(500_000)),
+        Trial::bench_with_limit("slow", 500_000, measured(612_345)),
+    ]
+}
+
+#[test]
+fn limit_in_bench_mode() {
+    check(args(["--bench"]), limited, 3,
+        conclusion! {
+            num_failed: 1,
+            num_measured: 2,
+            measurements: vec![
+                ("fast".into(), Measurement { avg: 420_000, ..Measurement::default() }),
+                ("at_limit".into(), Measurement { avg: 500_000, ..Measurement::default() }),
+            ],
+        },
+        "
+            test fast     ... bench:     420,000 ns/iter (+/- 0)
+            test at_limit ... bench:     500,000 ns/iter (+/- 0)
+            test slow     ... FAILED
+
+            failures:
+
+            ---- slow ----
+            benchmark exceeded limit: 612,345 ns/iter vs. allowed 500,000 ns/iter
+
+
+            failures:
+                slow
+        ",
+    );
+}
+
+#[test]
+fn limit_not_checked_in_test_mode() {
+    check(args([]), limited, 3,
+        conclusion! {
+            num_passed: 3,
+        },
+        "
+            test fast     ... ok
+            test at_limit ... ok
+            test slow     ... ok
+        ",
+    );
+}
@@ -0,0 +1,42 @@
+use common::{args, do_run};
+use libtest_mimic::{Failed, Trial};
+
+#[macro_use]
+mod common;
+
+// The failing trial has the same name as this test, so that re-executing the
+// test binary with it as filter runs this test again.
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("rerun_failed", || {
+            let backtrace = std::env::var("RUST_BACKTRACE").unwrap_or_default();
+            println!("diagnostics: RUST_BACKTRACE={backtrace}");
+            Err(Failed::from("boom"))
+        }),
+        Trial::test("passes", || Ok(())),
+    ]
+}
+
+#[test]
+fn rerun_failed() {
+    let (c, out) = do_run(args(["--bisect-verbose"]), tests());
+    assert_eq!(c.num_failed, 1);
+
+    // Inside the re-run, `--bisect-verbose` must not trigger another re-run.
+    if std::env::var_os("LIBTEST_MIMIC_VERBOSE_RERUN").is_some() {
+        assert!(!out.contains("verbose re-run"));
+        return;
+    }
+
+    let (summary, rerun) = out.split_once("verbose re-run of failed tests:").unwrap();
+    assert!(summary.contains("test result: FAILED. 1 passed; 1 failed;"));
+    assert!(rerun.contains("---- rerun_failed ----"));
+    assert!(rerun.contains("diagnostics: RUST_BACKTRACE=1"));
+    assert!(!rerun.contains("---- passes ----"));
+}
+
+#[test]
+fn no_rerun_by_default() {
+    let (_, out) = do_run(args([]), tests());
+    assert!(!out.contains("verbose re-run"));
+}
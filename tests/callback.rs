@@ -0,0 +1,49 @@
+use std::{sync::{Arc, Mutex}, thread};
+
+use common::{args, do_run_with_config};
+use libtest_mimic::{Outcome, RunConfig, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("nope".into())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+        Trial::test("filtered", || Ok(())),
+    ]
+}
+
+#[test]
+fn called_for_each_finished_test() {
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&results);
+    let config = RunConfig::default().with_on_result(move |info, outcome| {
+        log.lock().unwrap().push((info.name().to_owned(), outcome.clone()));
+    });
+    let args = args(["--test-threads", "1", "--skip", "filtered"]);
+    let (c, out) = do_run_with_config(args, tests(), config);
+    assert_eq!(c.num_failed, 1);
+    assert!(out.contains("test fails   ... FAILED"), "{out}");
+    assert_eq!(*results.lock().unwrap(), [
+        ("passes".to_owned(), Outcome::Passed),
+        ("fails".to_owned(), Outcome::Failed("nope".into())),
+        ("ignored".to_owned(), Outcome::Ignored),
+    ]);
+}
+
+#[test]
+fn called_on_calling_thread() {
+    let caller = thread::current().id();
+    let num_calls = Arc::new(Mutex::new(0));
+    let calls = Arc::clone(&num_calls);
+    let config = RunConfig::default().with_on_result(move |_, _| {
+        assert_eq!(thread::current().id(), caller);
+        *calls.lock().unwrap() += 1;
+    });
+    let tests = (0..20).map(|i| Trial::test(format!("t{i}"), || Ok(()))).collect();
+    let _ = do_run_with_config(args(["--test-threads", "4"]), tests, config);
+    assert_eq!(*num_calls.lock().unwrap(), 20);
+}
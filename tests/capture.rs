@@ -0,0 +1,83 @@
+//! Output capture requires nightly: run these with
+//! `RUSTFLAGS="--cfg libtest_mimic_capture" RUSTC_BOOTSTRAP=1 cargo test --test capture`.
+#![cfg(libtest_mimic_capture)]
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("fails", || {
+            println!("to stdout");
+            eprintln!("to stderr");
+            Err("oops".into())
+        }),
+        Trial::test("passes", || {
+            println!("never shown");
+            Ok(())
+        }),
+    ]
+}
+
+#[test]
+fn attached_to_failures() {
+    let (_, out) = do_run(args(["--test-threads", "1"]), tests());
+    assert_log!(out, "
+        running 2 tests
+        test fails  ... FAILED
+        test passes ... ok
+
+        failures:
+
+        ---- fails stdout ----
+        to stdout
+        to stderr
+        oops
+
+
+        failures:
+            fails
+
+        test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn json() {
+    let (_, out) = do_run(args(["--test-threads", "1", "--format", "json"]), tests());
+    let event = concat!(
+        r#"{ "type": "test", "name": "fails", "event": "failed", "#,
+        r#""stdout": "to stdout\nto stderr\nError: \"oops\"\n""#,
+    );
+    assert!(out.lines().any(|line| line.starts_with(event)), "{out}");
+}
+
+#[test]
+fn with_timeout() {
+    let (_, out) = do_run(args(["--test-threads", "1", "--timeout", "60"]), tests());
+    assert!(out.contains("---- fails stdout ----\nto stdout\nto stderr\noops\n"), "{out}");
+}
+
+#[test]
+fn nocapture() {
+    let (_, out) = do_run(args(["--test-threads", "1", "--nocapture"]), tests());
+    assert!(out.contains("---- fails ----\noops\n"), "{out}");
+    assert!(!out.contains("stdout ----"), "{out}");
+}
+
+#[test]
+fn scope_both() {
+    let (_, out) = do_run(args(["--test-threads", "1", "--capture", "both"]), tests());
+    assert!(out.contains("---- fails stdout ----\nto stdout\nto stderr\noops\n"), "{out}");
+}
+
+#[test]
+fn scope_none() {
+    let (_, out) = do_run(args(["--test-threads", "1", "--capture", "none"]), tests());
+    assert!(out.contains("---- fails ----\noops\n"), "{out}");
+    assert!(!out.contains("stdout ----"), "{out}");
+}
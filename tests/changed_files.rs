@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use common::{args, check};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("parser", || Ok(())).with_source_files(["src/parser.rs", "src/lexer.rs"]),
+        Trial::test("printer", || Ok(())).with_source_files(["src/printer.rs"]),
+        Trial::test("everything", || Ok(())),
+    ]
+}
+
+#[test]
+fn only_changed() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_changed_files.txt");
+    std::fs::write(&path, "README.md\nsrc/lexer.rs\n").unwrap();
+    let path_str = path.display().to_string();
+
+    check(args(["--changed-files", &path_str]), tests, 2,
+        conclusion! {
+            num_filtered_out: 1,
+            num_passed: 2,
+        },
+        "
+            test parser     ... ok
+            test everything ... ok
+        ",
+    );
+    std::fs::remove_file(&path).unwrap();
+}
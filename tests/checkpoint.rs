@@ -0,0 +1,115 @@
+use std::{path::{Path, PathBuf}, thread, time::Duration};
+
+use common::{args, do_run, in_subprocess, subprocess_command};
+use libtest_mimic::{Arguments, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name)
+}
+
+/// In the subprocess, `killed` exits the process to simulate an interrupted
+/// run. The other tests must not run again when resuming.
+fn tests() -> Vec<Trial> {
+    let run_once = |result: Result<(), &'static str>| move || {
+        if !in_subprocess() {
+            panic!("ran again after resuming");
+        }
+        result.map_err(Into::into)
+    };
+    vec![
+        Trial::test("a", run_once(Ok(()))),
+        Trial::test("b", run_once(Err("first line\nsecond\tline"))),
+        Trial::test("c", run_once(Ok(()))).with_kind("k"),
+        // Sleeps long enough for the checkpoint file to be written again.
+        Trial::test("d", move || {
+            thread::sleep(Duration::from_millis(1100));
+            run_once(Ok(()))()
+        }),
+        Trial::test("killed", || {
+            if in_subprocess() {
+                std::process::exit(3);
+            }
+            Ok(())
+        }),
+        Trial::test("never_reached", || Ok(())),
+    ]
+}
+
+fn checkpoint_args(checkpoint: &Path) -> Arguments {
+    let mut args = args(["--test-threads", "1"]);
+    args.checkpoint = Some(checkpoint.display().to_string());
+    args.resume = Some(checkpoint.display().to_string());
+    args
+}
+
+#[test]
+fn resume_after_interruption() {
+    let checkpoint = path("checkpoint_resume.txt");
+    if in_subprocess() {
+        let _ = do_run(checkpoint_args(&checkpoint), tests());
+        unreachable!();
+    }
+
+    let _ = std::fs::remove_file(&checkpoint);
+    let output = subprocess_command("resume_after_interruption").output().unwrap();
+    assert_eq!(output.status.code(), Some(3), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(std::fs::read_to_string(&checkpoint).unwrap(), concat!(
+        "passed\t\ta\n",
+        "failed\t>first line\\nsecond\\tline\tb\n",
+        "passed\t\t[k] c\n",
+        "passed\t\td\n",
+    ));
+
+    let (c, out) = do_run(checkpoint_args(&checkpoint), tests());
+    assert_eq!(c, conclusion! {
+        num_passed: 5,
+        num_failed: 1,
+    });
+    assert_log!(out, "
+        running 6 tests
+        test     a             ... ok
+        test     b             ... FAILED
+        test [k] c             ... ok
+        test     d             ... ok
+        test     killed        ... ok
+        test     never_reached ... ok
+
+        failures:
+
+        ---- b ----
+        first line
+        second\tline
+
+
+        failures:
+            b
+
+        test result: FAILED. 5 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+
+    // At the end, the checkpoint contains all tests.
+    assert_eq!(std::fs::read_to_string(&checkpoint).unwrap().lines().count(), 6);
+    assert!(!path("checkpoint_resume.txt.tmp").exists());
+}
+
+#[test]
+fn malformed_checkpoint() {
+    let checkpoint = path("checkpoint_malformed.txt");
+    if in_subprocess() {
+        let mut args = args([]);
+        args.resume = Some(checkpoint.display().to_string());
+        let _ = do_run(args, tests());
+        unreachable!();
+    }
+
+    std::fs::write(&checkpoint, "exploded\t\ta\n").unwrap();
+    let output = subprocess_command("malformed_checkpoint").output().unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("malformed line in checkpoint file: exploded"), "{stderr}");
+}
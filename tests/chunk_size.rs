@@ -0,0 +1,50 @@
+use common::{args, check, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    (0..20)
+        .map(|i| {
+            let name = format!("t{i:02}");
+            match i {
+                3 => Trial::test(name, || Ok(())).with_ignored_flag(true),
+                7 | 12 => Trial::test(name, || Err("nope".into())),
+                _ => Trial::test(name, || Ok(())),
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn chunked_results_per_test() {
+    for chunk_size in ["1", "3", "8", "100"] {
+        let (c, out) = do_run(args(["--chunk-size", chunk_size, "--test-threads", "3"]), tests());
+        assert_eq!(c, conclusion! {
+            num_passed: 17,
+            num_failed: 2,
+            num_ignored: 1,
+        }, "chunk size {chunk_size}");
+        for i in 0..20 {
+            assert!(out.contains(&format!("test t{i:02} ...")), "chunk size {chunk_size}: {out}");
+        }
+    }
+}
+
+#[test]
+fn chunked_output() {
+    check(args(["--chunk-size", "4"]), || tests().into_iter().take(4).collect(), 4,
+        conclusion! {
+            num_passed: 3,
+            num_ignored: 1,
+        },
+        "
+            test t00 ... ok
+            test t01 ... ok
+            test t02 ... ok
+            test t03 ... ignored
+        ",
+    );
+}
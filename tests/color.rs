@@ -0,0 +1,39 @@
+use common::{args, in_subprocess, subprocess_command};
+use libtest_mimic::{run, Trial};
+
+mod common;
+
+/// Runs a passing test with the given `--color` value in a subprocess whose
+/// stdout is a pipe (i.e. not a terminal) and returns its output. Returns
+/// `None` inside the subprocess.
+fn piped_output(test_name: &str, color: &str) -> Option<String> {
+    if in_subprocess() {
+        let _ = run(&args(["--color", color]), vec![Trial::test("passes", || Ok(()))]);
+        return None;
+    }
+    let output = subprocess_command(test_name).env_remove("NO_COLOR").output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("test passes ... "), "{stdout}");
+    Some(stdout)
+}
+
+#[test]
+fn always_forces_colors_when_piped() {
+    if let Some(out) = piped_output("always_forces_colors_when_piped", "always") {
+        assert!(out.contains('\x1b'), "{out:?}");
+    }
+}
+
+#[test]
+fn auto_disables_colors_when_piped() {
+    if let Some(out) = piped_output("auto_disables_colors_when_piped", "auto") {
+        assert!(!out.contains('\x1b'), "{out:?}");
+    }
+}
+
+#[test]
+fn never_disables_colors() {
+    if let Some(out) = piped_output("never_disables_colors", "never") {
+        assert!(!out.contains('\x1b'), "{out:?}");
+    }
+}
@@ -1,7 +1,9 @@
+#![allow(dead_code)]
+
 use std::{path::Path, iter::repeat_with, collections::HashMap};
 use pretty_assertions::assert_eq;
 
-use libtest_mimic::{run, Arguments, Conclusion, Trial};
+use libtest_mimic::{run_with_config, Arguments, Conclusion, RunConfig, Trial};
 
 
 const TEMPDIR: &str = env!("CARGO_TARGET_TMPDIR");
@@ -12,18 +14,42 @@ pub fn args<const N: usize>(args: [&str; N]) -> Arguments {
     Arguments::from_iter(v)
 }
 
-pub fn do_run(mut args: Arguments, tests: Vec<Trial>) -> (Conclusion, String) {
+pub fn do_run(args: Arguments, tests: Vec<Trial>) -> (Conclusion, String) {
+    do_run_with_config(args, tests, RunConfig::default())
+}
+
+pub fn do_run_with_config(
+    args: Arguments,
+    tests: Vec<Trial>,
+    config: RunConfig,
+) -> (Conclusion, String) {
+    run_with_logfile(args, |args| run_with_config(args, tests, config))
+}
+
+/// Sets a temporary logfile in `args`, calls `run` with those and returns its
+/// conclusion together with the contents of the logfile. The
+/// `total_duration` of the conclusion is reset to zero and its `results` are
+/// cleared.
+pub fn run_with_logfile(
+    mut args: Arguments,
+    run: impl FnOnce(&Arguments) -> Conclusion,
+) -> (Conclusion, String) {
     // Create path to temporary file.
     let suffix = repeat_with(fastrand::alphanumeric).take(10).collect::<String>();
     let path = Path::new(&TEMPDIR).join(format!("libtest_mimic_output_{suffix}.txt"));
 
     args.logfile = Some(path.display().to_string());
 
-    let c = run(&args, tests);
+    let mut c = run(&args);
     let output = std::fs::read_to_string(&path)
         .expect("Can't read temporary logfile");
     std::fs::remove_file(&path)
         .expect("Can't remove temporary logfile");
+
+    // The duration differs in every run, so it is not compared. The results
+    // are tested separately, so they don't have to be repeated everywhere.
+    c.total_duration = Default::default();
+    c.results.clear();
     (c, output)
 }
 
@@ -79,14 +105,36 @@ pub fn assert_reordered_log(actual: &str, num: u64, expected_lines: &[&str], tai
     }
 }
 
+/// Replaces all durations like "finished in 0.13s" with "finished in 0.00s",
+/// e.g. of the sections of `run_sections` or `--repeat`.
+pub fn normalize_finished_in(s: &str) -> String {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(pos) = rest.find("finished in ") {
+        let (before, after) = rest.split_at(pos + "finished in ".len());
+        out.push_str(before);
+        let len = after.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(after.len());
+        match after[len..].starts_with('s') && len > 0 {
+            true => {
+                out.push_str("0.00");
+                rest = &after[len..];
+            }
+            false => rest = after,
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
 /// Like `assert_eq`, but cleans the expected string (removes indendation). Also
-/// normalizes the "finished in" time if `$expected` ends with "finished in
-/// 0.00s".
+/// normalizes the "finished in" times (see `normalize_finished_in`) and, if
+/// `$expected` ends with "finished in 0.00s", drops everything after the last
+/// one.
 #[macro_export]
 macro_rules! assert_log {
     ($actual:expr, $expected:expr) => {
-        let mut actual = $actual.trim().to_owned();
-        let expected = crate::common::clean_expected_log($expected);
+        let mut actual = $crate::common::normalize_finished_in($actual.trim());
+        let expected = $crate::common::clean_expected_log($expected);
         let expected = expected.trim();
 
         if expected.ends_with("finished in 0.00s") {
@@ -106,6 +154,20 @@ macro_rules! assert_log {
     };
 }
 
+/// Creates a `Conclusion` (which is `#[non_exhaustive]`) from the given
+/// fields. All other fields have their default value.
+#[macro_export]
+macro_rules! conclusion {
+    ($($field:ident $(: $value:expr)?),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut c = libtest_mimic::Conclusion::default();
+        $( c.$field = conclusion!(@value $field $(: $value)?); )*
+        c
+    }};
+    (@value $field:ident) => { $field };
+    (@value $field:ident: $value:expr) => { $value };
+}
+
 pub fn check(
     mut args: Arguments,
     mut tests: impl FnMut() -> Vec<Trial>,
@@ -125,25 +187,61 @@ pub fn check(
     assert_eq!(c, expected_conclusion);
 
     // Run in multithreaded mode.
-    let (c, out) = do_run(args, tests());
+    // Benchmarks can finish in any order here, so the measurements are sorted.
+    let (mut c, out) = do_run(args, tests());
     assert_reordered_log(
         &out,
         num_running_tests,
         &expected_output.lines().collect::<Vec<_>>(),
         &conclusion_to_output(&c),
     );
+    let mut expected_conclusion = expected_conclusion;
+    c.measurements.sort_by(|a, b| a.0.cmp(&b.0));
+    expected_conclusion.measurements.sort_by(|a, b| a.0.cmp(&b.0));
     assert_eq!(c, expected_conclusion);
 }
 
 fn conclusion_to_output(c: &Conclusion) -> String {
-    let Conclusion { num_filtered_out, num_passed, num_failed, num_ignored, num_measured } = *c;
+    let Conclusion {
+        num_filtered_out, num_passed, num_failed, num_ignored, num_ignored_failed, num_measured, ..
+    } = *c;
+    let ignored_failed = if num_ignored_failed > 0 {
+        format!(" {} ignored (failed);", num_ignored_failed)
+    } else {
+        String::new()
+    };
     format!(
-        "test result: {}. {} passed; {} failed; {} ignored; {} measured; {} filtered out;",
+        "test result: {}. {} passed; {} failed; {} ignored;{} {} measured; {} filtered out;",
         if num_failed > 0 { "FAILED" } else { "ok" },
         num_passed,
         num_failed,
         num_ignored,
+        ignored_failed,
         num_measured,
         num_filtered_out,
     )
 }
+
+/// Environment variable that is set when a test is re-executed via
+/// `run_in_subprocess`.
+pub const SUBPROCESS_ENV: &str = "LIBTEST_MIMIC_TEST_SUBPROCESS";
+
+/// Re-executes the current test binary, running only the test with the given
+/// name. Inside the subprocess, `in_subprocess()` returns `true`. Useful to
+/// test code paths that exit the process.
+pub fn run_in_subprocess(test_name: &str) -> std::process::Output {
+    subprocess_command(test_name).output().expect("failed to re-execute test binary")
+}
+
+/// Returns the command used by `run_in_subprocess`.
+pub fn subprocess_command(test_name: &str) -> std::process::Command {
+    let mut command = std::process::Command::new(std::env::current_exe().unwrap());
+    command
+        .args(["--exact", test_name, "--nocapture", "--test-threads", "1"])
+        .env(SUBPROCESS_ENV, "1");
+    command
+}
+
+pub fn in_subprocess() -> bool {
+    std::env::var_os(SUBPROCESS_ENV).is_some()
+}
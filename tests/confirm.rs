@@ -0,0 +1,30 @@
+use common::{args, do_run, in_subprocess, run_in_subprocess};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn no_effect_without_terminal() {
+    if in_subprocess() {
+        let tests = vec![Trial::test("foo", || Ok(())), Trial::test("bar", || Ok(()))];
+        let (c, _) = do_run(args(["--confirm"]), tests);
+        assert_eq!(c.num_passed, 2);
+        return;
+    }
+
+    // `run_in_subprocess` does not connect stdin to a terminal.
+    let output = run_in_subprocess("no_effect_without_terminal");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let note = "note: `--confirm` has no effect as stdin is not a terminal";
+    assert!(stderr.contains(note), "{stderr}");
+    assert!(!stderr.contains("Press enter"), "{stderr}");
+}
+
+#[test]
+fn ignored_when_listing() {
+    let (_, out) = do_run(args(["--confirm", "--list"]), vec![Trial::test("foo", || Ok(()))]);
+    assert_eq!(out.trim(), "foo: test");
+}
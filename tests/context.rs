@@ -0,0 +1,95 @@
+use common::{args, check, do_run};
+use pretty_assertions::assert_eq;
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test_with_context("all_good", |ctx| {
+            ctx.soft_assert(true, "not shown");
+            Ok(())
+        }),
+        Trial::test_with_context("soft", |ctx| {
+            ctx.soft_assert(false, "first check");
+            ctx.soft_assert(true, "not shown");
+            ctx.soft_assert(false, "second check");
+            Ok(())
+        }),
+        Trial::test_with_context("soft_and_hard", |ctx| {
+            ctx.soft_assert(false, "soft check");
+            Err("hard failure".into())
+        }),
+    ]
+}
+
+#[test]
+fn soft_assertions() {
+    check(args([]), tests, 3,
+        conclusion! {
+            num_passed: 1,
+            num_failed: 2,
+        },
+        "
+            test all_good      ... ok
+            test soft          ... FAILED
+            test soft_and_hard ... FAILED
+
+            failures:
+
+            ---- soft ----
+            2 soft assertions failed:
+            - first check
+            - second check
+
+            ---- soft_and_hard ----
+            1 soft assertion failed:
+            - soft check
+
+            hard failure
+
+
+            failures:
+                soft
+                soft_and_hard
+        ",
+    );
+}
+
+#[test]
+fn warnings() {
+    let tests = vec![
+        Trial::test_with_context("deprecated", |ctx| {
+            ctx.warn("uses the old API");
+            ctx.warn("optional check skipped");
+            Ok(())
+        }),
+        Trial::test_with_context("panics", |ctx| {
+            ctx.warn("before panic");
+            panic!("boom");
+        }),
+        Trial::test("plain", || Ok(())),
+    ];
+    let (c, out) = do_run(args([]), tests);
+    assert_eq!(c.num_warnings, 3);
+    assert_eq!(c.num_passed, 2);
+    assert!(out.contains("
+warnings:
+    deprecated: uses the old API
+    deprecated: optional check skipped
+    panics: before panic
+"), "{out}");
+}
+
+#[test]
+fn warnings_do_not_fail_the_run() {
+    let tests = vec![Trial::test_with_context("warns", |ctx| {
+        ctx.warn("just a warning");
+        Ok(())
+    })];
+    let (c, out) = do_run(args([]), tests);
+    assert!(!c.has_failed());
+    assert_eq!(c.num_warnings, 1);
+    assert!(out.contains("test result: ok."));
+}
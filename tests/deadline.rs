@@ -0,0 +1,46 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("a", || Ok(())),
+        Trial::test("b", || Ok(())).with_always_run(true),
+        Trial::test("c", || Ok(())),
+    ]
+}
+
+fn epoch_ms(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH).unwrap().as_millis().to_string()
+}
+
+#[test]
+fn past_deadline_runs_nothing() {
+    let deadline = epoch_ms(SystemTime::now() - Duration::from_millis(10));
+    let (c, out) = do_run(args(["--deadline-epoch", &deadline]), tests());
+    assert_eq!(c, conclusion! {
+        num_not_run: 3,
+        not_run: vec!["a".into(), "b".into(), "c".into()],
+    });
+    assert_log!(out, "
+        running 3 tests
+
+        deadline reached: 3 tests not run
+
+        test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn future_deadline_runs_everything() {
+    let deadline = epoch_ms(SystemTime::now() + Duration::from_secs(3600));
+    let (c, out) = do_run(args(["--deadline-epoch", &deadline]), tests());
+    assert_eq!(c.num_passed, 3);
+    assert!(!out.contains("deadline reached"));
+}
@@ -0,0 +1,87 @@
+#![cfg(unix)]
+
+use std::{io::Read, os::unix::net::UnixListener, path::Path, thread};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("oops".into())),
+    ]
+}
+
+#[test]
+fn streams_json_events() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_events.sock");
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let client = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut events = String::new();
+        stream.read_to_string(&mut events).unwrap();
+        events
+    });
+
+    let (c, out) = do_run(
+        args(["--event-socket", path.to_str().unwrap(), "--test-threads", "1"]),
+        tests(),
+    );
+    let events = client.join().unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // The normal output is unaffected.
+    assert_eq!(c.num_failed, 1);
+    assert!(out.contains("test passes ... ok"));
+
+    let events = events.lines().collect::<Vec<_>>();
+    assert_eq!(events.len(), 6, "{events:#?}");
+    assert_eq!(events[0], r#"{ "type": "suite", "event": "started", "test_count": 2 }"#);
+    assert_eq!(events[1], r#"{ "type": "test", "event": "started", "name": "passes" }"#);
+    assert_eq!(events[2], r#"{ "type": "test", "name": "passes", "event": "ok" }"#);
+    assert_eq!(events[3], r#"{ "type": "test", "event": "started", "name": "fails" }"#);
+    assert!(events[4].starts_with(r#"{ "type": "test", "name": "fails", "event": "failed""#));
+    let suite = r#"{ "type": "suite", "event": "failed", "passed": 1, "failed": 1,"#;
+    assert!(events[5].starts_with(suite));
+}
+
+#[test]
+fn no_listener() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_no_listener.sock");
+    let (c, _) = do_run(args(["--event-socket", path.to_str().unwrap()]), tests());
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(c.num_failed, 1);
+}
+
+/// A listener that accepts the connection but never reads must not block the
+/// run once the socket's buffer is full.
+#[test]
+fn listener_never_reads() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_stuck.sock");
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+    let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+    let client = thread::spawn(move || {
+        let (_stream, _) = listener.accept().unwrap();
+        // Keep the connection open (without reading) until the run finished.
+        let _ = done_rx.recv();
+    });
+
+    // Long failure messages to fill the buffer quickly.
+    let tests = (0..200)
+        .map(|i| Trial::test(format!("t{i}"), || Err("x".repeat(10_000).into())))
+        .collect();
+    let (c, _) = do_run(
+        args(["--event-socket", path.to_str().unwrap(), "--test-threads", "1"]),
+        tests,
+    );
+    done_tx.send(()).unwrap();
+    client.join().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(c.num_failed, 200);
+}
@@ -0,0 +1,41 @@
+use common::{args, do_run, in_subprocess, run_in_subprocess};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn codes() {
+    let passed = conclusion! { num_passed: 1 };
+    assert!(passed.is_success());
+    assert_eq!(passed.raw_exit_code(), 0);
+
+    let failed = conclusion! { num_passed: 1, num_failed: 1 };
+    assert!(!failed.is_success());
+    assert_eq!(failed.raw_exit_code(), 101);
+
+    let interrupted = conclusion! { interrupted: true };
+    assert!(!interrupted.is_success());
+    assert_eq!(interrupted.raw_exit_code(), 130);
+}
+
+#[test]
+fn exit_delegates_to_raw_exit_code() {
+    if in_subprocess() {
+        let (c, _) = do_run(args([]), vec![Trial::test("fails", || Err("nope".into()))]);
+        c.exit();
+    }
+    assert_eq!(run_in_subprocess("exit_delegates_to_raw_exit_code").status.code(), Some(101));
+}
+
+#[test]
+fn custom_exit_code() {
+    if in_subprocess() {
+        let (c, _) = do_run(args(["no_such_test"]), vec![Trial::test("passes", || Ok(()))]);
+        c.exit_with_code(|c| match c.num_filtered_out {
+            n if n > 0 && c.num_passed + c.num_failed == 0 => 2,
+            _ => c.raw_exit_code(),
+        });
+    }
+    assert_eq!(run_in_subprocess("custom_exit_code").status.code(), Some(2));
+}
@@ -0,0 +1,44 @@
+use common::{args, do_run, in_subprocess, run_in_subprocess};
+use libtest_mimic::{Failed, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("multi_line", || Err("first line\nsecond line".into())).with_kind("k"),
+        Trial::test("no_message", || Err(Failed::without_message())),
+        Trial::test("soft", || Err("only soft".into())).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn digest_on_stderr() {
+    if in_subprocess() {
+        let _ = do_run(
+            args([
+                "--failure-digest", "--format", "json", "--run-ignored-soft", "--test-threads", "1",
+            ]),
+            tests(),
+        );
+        return;
+    }
+
+    let output = run_in_subprocess("digest_on_stderr");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let digest = stderr.lines().filter(|l| l.starts_with("FAIL ")).collect::<Vec<_>>();
+    assert_eq!(digest, ["FAIL [k] multi_line: first line", "FAIL no_message"]);
+}
+
+#[test]
+fn no_digest_by_default() {
+    if in_subprocess() {
+        let _ = do_run(args([]), tests());
+        return;
+    }
+
+    let output = run_in_subprocess("no_digest_by_default");
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("FAIL "));
+}
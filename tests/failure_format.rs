@@ -0,0 +1,75 @@
+use common::{args, do_run_with_config};
+use libtest_mimic::{RunConfig, Trial};
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("multi_line", || Err("first\nsecond".into())),
+        Trial::test("no_message", || Err(libtest_mimic::Failed::without_message())),
+    ]
+}
+
+#[test]
+fn custom_header() {
+    let config = RunConfig::default().with_failure_section_header("broken tests");
+    let (_, out) = do_run_with_config(args(["--test-threads", "1"]), tests(), config);
+    assert_log!(out, "
+        running 3 tests
+        test passes     ... ok
+        test multi_line ... FAILED
+        test no_message ... FAILED
+
+        broken tests:
+
+        ---- multi_line ----
+        first
+        second
+
+        ---- no_message ----
+
+
+        broken tests:
+            multi_line
+            no_message
+
+        test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn compact() {
+    let config = RunConfig::default().with_compact_failures(true);
+    let (_, out) = do_run_with_config(args(["--test-threads", "1"]), tests(), config);
+    assert_log!(out, "
+        running 3 tests
+        test passes     ... ok
+        test multi_line ... FAILED
+        test no_message ... FAILED
+
+        failures:
+            multi_line: first; second
+            no_message
+
+        test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn compact_with_hidden_failures() {
+    let config = RunConfig::default()
+        .with_compact_failures(true)
+        .with_failure_section_header("oh no");
+    let (_, out) = do_run_with_config(
+        args(["--test-threads", "1", "--max-failures-shown", "1"]),
+        tests(),
+        config,
+    );
+    assert!(out.contains(
+        "\noh no:\n    multi_line: first; second\n    ... and 1 more failure\n\n",
+    ), "{out}");
+}
@@ -0,0 +1,105 @@
+use common::{args, in_subprocess, run_in_subprocess};
+use libtest_mimic::{list_tests, Arguments, FilterSet, Trial};
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("integration::db::connects", || Ok(())),
+        Trial::test("integration::db::migrates", || Ok(())),
+        Trial::test("unit::integration::helper", || Ok(())),
+        Trial::test("unit::parser::ints", || Ok(())).with_kind("slow"),
+    ]
+}
+
+#[cfg(feature = "regex")]
+fn selected(args: Arguments) -> Vec<String> {
+    list_tests(&args, &tests()).into_iter().map(|info| info.name().to_owned()).collect()
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn anchored() {
+    assert_eq!(selected(args(["--filter-regex", "^integration::"])), [
+        "integration::db::connects",
+        "integration::db::migrates",
+    ]);
+    assert_eq!(selected(args(["--filter-regex", "integration::"])), [
+        "integration::db::connects",
+        "integration::db::migrates",
+        "unit::integration::helper",
+    ]);
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn multiple_and_skip() {
+    assert_eq!(selected(args(["--filter-regex", "db::c", "^unit::"])), [
+        "integration::db::connects",
+        "unit::integration::helper",
+        "unit::parser::ints",
+    ]);
+    assert_eq!(selected(args(["--filter-regex", "--skip", r"::(helper|ints)$"])), [
+        "integration::db::connects",
+        "integration::db::migrates",
+    ]);
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn matches_name_with_kind() {
+    assert_eq!(selected(args(["--filter-regex", r"^\[slow\] "])), ["unit::parser::ints"]);
+    assert_eq!(selected(args(["--filter-regex", "^unit::parser"])), ["unit::parser::ints"]);
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn invalid_regex() {
+    let filters = FilterSet::default().with_regex(true).with_skip("(unclosed");
+    let err = filters.validate().unwrap_err();
+    assert!(err.starts_with("invalid regex `(unclosed`: "), "{err}");
+    assert!(FilterSet::default().with_skip("(unclosed").validate().is_ok());
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn invalid_regex_is_cli_error() {
+    if in_subprocess() {
+        Arguments::from_iter(["harness", "--filter-regex", "a[b"]);
+        return;
+    }
+
+    let output = run_in_subprocess("invalid_regex_is_cli_error");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(2), "{stderr}");
+    assert!(stderr.contains("error: invalid regex `a[b`: "), "{stderr}");
+}
+
+#[test]
+#[cfg(not(feature = "regex"))]
+fn requires_feature() {
+    // `args` would already exit here.
+    let args = Arguments { filter_regex: true, ..Arguments::default() };
+    let filters = FilterSet::from_args(&args);
+    assert!(filters.is_regex());
+    assert_eq!(
+        filters.validate().unwrap_err(),
+        "`--filter-regex` requires the `regex` feature of libtest-mimic",
+    );
+
+    if in_subprocess() {
+        Arguments::from_iter(["harness", "--filter-regex"]);
+        return;
+    }
+    let output = run_in_subprocess("requires_feature");
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("requires the `regex` feature"), "{stderr}");
+}
+
+#[test]
+fn without_flag_patterns_are_substrings() {
+    let names = list_tests(&args(["^integration::"]), &tests());
+    assert!(names.is_empty());
+}
@@ -0,0 +1,122 @@
+use common::{args, do_run_with_config};
+use libtest_mimic::{list_tests, FilterSet, RunConfig, TestInfo, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("parse_int", || Ok(())),
+        Trial::test("parse_float", || Ok(())).with_kind("slow"),
+        Trial::test("parse_hex", || Ok(())).with_ignored_flag(true),
+        Trial::test("lex_ident", || Ok(())),
+        Trial::bench("parse_bench", |_| Ok(None)),
+    ]
+}
+
+/// Returns the names (with kind) of all tests selected by `filters`.
+fn selected(filters: &FilterSet) -> Vec<String> {
+    list_tests(&args([]), &tests())
+        .iter()
+        .filter(|info| filters.matches(info))
+        .map(|info| match info.kind() {
+            "" => info.name().to_owned(),
+            kind => format!("[{}] {}", kind, info.name()),
+        })
+        .collect()
+}
+
+#[test]
+fn default_selects_everything() {
+    assert_eq!(selected(&FilterSet::default()).len(), 5);
+}
+
+#[test]
+fn filter_and_skips() {
+    let filters = FilterSet::default().with_filter("parse").with_skip("float").with_skip("hex");
+    assert_eq!(selected(&filters), ["parse_int", "parse_bench"]);
+
+    // A skip pattern wins over the filter, no matter the order.
+    let filters = FilterSet::default().with_skip("parse_int").with_filter("parse_int");
+    assert!(selected(&filters).is_empty());
+}
+
+#[test]
+fn exact_applies_to_filter_and_skips() {
+    let filters = FilterSet::default().with_filter("parse").with_exact(true);
+    assert!(selected(&filters).is_empty());
+
+    let filters = FilterSet::default().with_skip("parse_float").with_skip("[slow] parse_float");
+    assert_eq!(selected(&filters).len(), 4);
+    let filters = filters.with_exact(true).with_skip("lex");
+    assert_eq!(selected(&filters), ["parse_int", "parse_hex", "lex_ident", "parse_bench"]);
+}
+
+#[test]
+fn skip_exact_is_independent_of_exact() {
+    let filters = FilterSet::default()
+        .with_filter("parse")
+        .with_skip_exact("parse_in")
+        .with_skip_exact("[slow] parse_float");
+    assert_eq!(selected(&filters), ["parse_int", "parse_hex", "parse_bench"]);
+    assert_eq!(filters.skip_exact_patterns(), ["parse_in", "[slow] parse_float"]);
+}
+
+#[test]
+fn skip_exact_from_args() {
+    let tests = vec![
+        Trial::test("integration::flaky_one", || Ok(())),
+        Trial::test("integration::flaky_one_more", || Ok(())),
+        Trial::test("integration::stable", || Ok(())),
+        Trial::test("unit::flaky_one", || Ok(())),
+    ];
+    let args = args([
+        "integration", "--skip-exact", "integration::flaky_one", "--test-threads", "1",
+    ]);
+    let (c, out) = do_run_with_config(args, tests, RunConfig::default());
+    assert_eq!(c.num_filtered_out, 2);
+    assert_eq!(c.num_passed, 2);
+    assert!(out.contains("test integration::flaky_one_more ... ok\n"), "{out}");
+    assert!(out.contains("test integration::stable         ... ok\n"), "{out}");
+}
+
+#[test]
+fn kinds_ignored_and_predicates() {
+    let filters = FilterSet::default().with_only_tests(true).with_filter("parse");
+    assert_eq!(selected(&filters), ["parse_int", "[slow] parse_float", "parse_hex"]);
+
+    let filters = FilterSet::default().with_only_benches(true);
+    assert_eq!(selected(&filters), ["parse_bench"]);
+
+    let filters = FilterSet::default().with_only_ignored(true).with_predicate(|_| true);
+    assert_eq!(selected(&filters), ["parse_hex"]);
+
+    // All predicates have to agree.
+    let filters = FilterSet::default()
+        .with_predicate(|info: &TestInfo| info.is_test())
+        .with_predicate(|info: &TestInfo| info.kind().is_empty())
+        .with_skip("lex");
+    assert_eq!(selected(&filters), ["parse_int", "parse_hex"]);
+    assert_eq!(filters.num_predicates(), 2);
+}
+
+#[test]
+fn from_args() {
+    let args = args(["parse", "--skip", "hex", "--skip", "int", "--test"]);
+    let filters = FilterSet::from_args(&args);
+    assert_eq!(filters.filters(), ["parse"]);
+    assert_eq!(filters.skip_patterns(), ["hex", "int"]);
+    assert!(!filters.is_exact());
+    assert_eq!(selected(&filters), ["[slow] parse_float"]);
+}
+
+#[test]
+fn config_predicate_combined_with_args() {
+    let config = RunConfig::default().with_filter_predicate(|info| info.kind() != "slow");
+    let (c, out) = do_run_with_config(args(["parse", "--test-threads", "1"]), tests(), config);
+    assert_eq!(c.num_filtered_out, 2);
+    assert_eq!(c.num_passed, 2);
+    assert_eq!(c.num_ignored, 1);
+    assert!(out.contains("test parse_int   ... ok\n"), "{out}");
+}
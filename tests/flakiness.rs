@@ -0,0 +1,40 @@
+use std::path::Path;
+
+use common::{args, do_run};
+use libtest_mimic::{merge_runs, TestRunStats, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests(shard: u32) -> Vec<Trial> {
+    vec![
+        Trial::test("stable", || Ok(())),
+        Trial::test("broken", || Err("always".into())),
+        Trial::test("flaky", move || if shard == 0 { Ok(()) } else { Err("sometimes".into()) }),
+        Trial::test("skipped", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn merge_two_shards() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let paths = [dir.join("flakiness_shard_0.txt"), dir.join("flakiness_shard_1.txt")];
+    for (shard, path) in paths.iter().enumerate() {
+        let path = path.display().to_string();
+        let _ = do_run(args(["--results-file", &path]), tests(shard as u32));
+    }
+
+    let report = merge_runs(&paths).unwrap();
+    for path in &paths {
+        std::fs::remove_file(path).unwrap();
+    }
+
+    assert_eq!(report.tests["stable"], TestRunStats { passed: 2, failed: 0 });
+    assert_eq!(report.tests["broken"], TestRunStats { passed: 0, failed: 2 });
+    assert_eq!(report.tests["flaky"], TestRunStats { passed: 1, failed: 1 });
+    assert_eq!(report.tests["skipped"], TestRunStats { passed: 0, failed: 0 });
+    assert_eq!(report.tests["flaky"].pass_ratio(), Some(0.5));
+    assert_eq!(report.tests["skipped"].pass_ratio(), None);
+    assert_eq!(report.flaky_tests().map(|(name, _)| name).collect::<Vec<_>>(), ["flaky"]);
+}
@@ -0,0 +1,47 @@
+use common::{args, do_run, in_subprocess, subprocess_command};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+/// Helper "script" that is executed by the trials below via a subprocess.
+#[test]
+fn script_passes() {
+    if in_subprocess() {
+        println!("all good");
+    }
+}
+
+/// Helper "script" that is executed by the trials below via a subprocess.
+#[test]
+fn script_fails() {
+    if in_subprocess() {
+        println!("some stdout");
+        eprintln!("some stderr");
+        std::process::exit(3);
+    }
+}
+
+#[test]
+fn from_command() {
+    let tests = vec![
+        Trial::from_command("passes", subprocess_command("script_passes")),
+        Trial::from_command("fails", subprocess_command("script_fails")),
+        Trial::from_command("missing", std::process::Command::new("/does/not/exist")),
+    ];
+    let (c, out) = do_run(args(["--test-threads", "1"]), tests);
+    assert_eq!(c, conclusion! {
+        num_passed: 1,
+        num_failed: 2,
+    });
+
+    let failure = out.split("---- fails ----\n").nth(1).unwrap();
+    assert!(failure.starts_with("process exited with "), "{out}");
+    assert!(failure.contains("3\n--- stdout ---\n"));
+    assert!(failure.contains("some stdout\n"));
+    assert!(failure.contains("--- stderr ---\nsome stderr\n"));
+
+    let failure = out.split("---- missing ----\n").nth(1).unwrap();
+    assert!(failure.starts_with("failed to start \"/does/not/exist\": "), "{out}");
+}
@@ -0,0 +1,150 @@
+use std::sync::{Arc, Mutex};
+
+use common::{args, do_run_with_config};
+use libtest_mimic::{RunConfig, TestInfo, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn begin_and_end_around_each_test() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let log = |prefix: &'static str| {
+        let events = Arc::clone(&events);
+        move |name: &str| events.lock().unwrap().push(format!("{prefix} {name}"))
+    };
+    let config = RunConfig::default()
+        .with_on_test_begin(log("begin"))
+        .with_on_test_end(log("end"));
+
+    let test_events = Arc::clone(&events);
+    let tests = vec![
+        Trial::test("first", move || {
+            test_events.lock().unwrap().push("run first".into());
+            Ok(())
+        }),
+        Trial::test("panics", || panic!("boom")).with_kind("k"),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ];
+    let (c, _) = do_run_with_config(args(["--test-threads", "1"]), tests, config);
+    assert_eq!(c.num_failed, 1);
+    assert_eq!(*events.lock().unwrap(), [
+        "begin first",
+        "run first",
+        "end first",
+        "begin [k] panics",
+        "end [k] panics",
+    ]);
+}
+
+#[test]
+fn hooks_in_thread_pool() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let begin_events = Arc::clone(&events);
+    let config = RunConfig::default()
+        .with_on_test_begin(move |name| begin_events.lock().unwrap().push(name.to_owned()));
+
+    let tests = (0..10).map(|i| Trial::test(format!("t{i}"), || Ok(()))).collect();
+    let _ = do_run_with_config(args(["--test-threads", "4"]), tests, config);
+    let mut events = events.lock().unwrap().clone();
+    events.sort();
+    assert_eq!(events, (0..10).map(|i| format!("t{i}")).collect::<Vec<_>>());
+}
+
+#[test]
+fn all_and_each_hooks() {
+    let events = Arc::new(Mutex::new(Vec::<String>::new()));
+    let log_once = |name: &'static str| {
+        let events = Arc::clone(&events);
+        move || events.lock().unwrap().push(name.into())
+    };
+    let log_each = |prefix: &'static str| {
+        let events = Arc::clone(&events);
+        move |info: &TestInfo| events.lock().unwrap().push(format!("{prefix} {}", info.name()))
+    };
+    let config = RunConfig::default()
+        .with_before_all(log_once("before all"))
+        .with_after_all(log_once("after all"))
+        .with_before_each(log_each("before"))
+        .with_after_each(log_each("after"))
+        .with_on_test_begin(log("begin", &events));
+
+    let tests = vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("panics", || panic!("boom")),
+        Trial::test("fails", || Err("nope".into())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ];
+    let (c, _) = do_run_with_config(args(["--test-threads", "1"]), tests, config.clone());
+    assert_eq!(c.num_failed, 2);
+    assert_eq!(*events.lock().unwrap(), [
+        "before all",
+        "before passes",
+        "begin passes",
+        "after passes",
+        "before panics",
+        "begin panics",
+        "after panics",
+        "before fails",
+        "begin fails",
+        "after fails",
+        "after all",
+    ]);
+
+    // The all hooks are only called by the first run.
+    events.lock().unwrap().clear();
+    let _ = do_run_with_config(args(["passes"]), vec![Trial::test("passes", || Ok(()))], config);
+    assert_eq!(*events.lock().unwrap(), ["before passes", "begin passes", "after passes"]);
+}
+
+#[test]
+fn after_all_when_aborted() {
+    let called = Arc::new(Mutex::new(0));
+    let after_called = Arc::clone(&called);
+    let config = RunConfig::default()
+        .with_after_all(move || *after_called.lock().unwrap() += 1);
+
+    let tests = (0..10).map(|i| Trial::test(format!("t{i}"), || Err("x".into()))).collect();
+    let args = args(["--max-failures", "1", "--test-threads", "4"]);
+    let (c, _) = do_run_with_config(args, tests, config);
+    assert!(c.num_not_run > 0);
+    assert_eq!(*called.lock().unwrap(), 1);
+}
+
+#[test]
+fn all_hooks_not_called_for_list() {
+    let called = Arc::new(Mutex::new(false));
+    let before_called = Arc::clone(&called);
+    let config = RunConfig::default()
+        .with_before_all(move || *before_called.lock().unwrap() = true);
+    let _ = do_run_with_config(args(["--list"]), vec![Trial::test("a", || Ok(()))], config);
+    assert!(!*called.lock().unwrap());
+}
+
+#[test]
+fn panicking_each_hooks_fail_the_test() {
+    for threads in ["1", "4"] {
+        let config = RunConfig::default()
+            .with_before_each(|info: &TestInfo| if info.name() == "a" { panic!("before a") })
+            .with_after_each(|info: &TestInfo| if info.name() == "b" { panic!("after b") });
+        let tests = vec![
+            Trial::test("a", || panic!("must not run")),
+            Trial::test("b", || Ok(())),
+            Trial::test("c", || Ok(())),
+        ];
+        let (c, out) = do_run_with_config(args(["--test-threads", threads]), tests, config);
+        assert_eq!((c.num_passed, c.num_failed), (1, 2), "{out}");
+        assert!(out.contains("---- a ----\ntest panicked: before a\n"), "{out}");
+        assert!(out.contains("---- b ----\ntest panicked: after b\n"), "{out}");
+        assert!(!out.contains("must not run"), "{out}");
+    }
+}
+
+fn log(
+    prefix: &'static str,
+    events: &Arc<Mutex<Vec<String>>>,
+) -> impl Fn(&str) + Send + Sync + 'static {
+    let events = Arc::clone(events);
+    move |name| events.lock().unwrap().push(format!("{prefix} {name}"))
+}
@@ -0,0 +1,75 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("needs_network", || Ok(())).with_ignored_reason("requires <network> & \"DNS\""),
+        Trial::test("plain_ignored", || Ok(())).with_ignored_flag(true),
+        Trial::test("runs", || Ok(())),
+    ]
+}
+
+#[test]
+fn reason_getters() {
+    let test = Trial::test("a", || Ok(())).with_ignored_reason("slow");
+    assert!(test.has_ignored_flag());
+    assert_eq!(test.ignored_reason(), Some("slow"));
+    assert_eq!(Trial::test("b", || Ok(())).ignored_reason(), None);
+}
+
+#[test]
+fn pretty() {
+    let (_, out) = do_run(args(["--test-threads", "1", "--list-ignored-after"]), tests());
+    assert_log!(out, "
+        running 3 tests
+        test needs_network ... ignored, requires <network> & \"DNS\"
+        test plain_ignored ... ignored
+        test runs          ... ok
+
+        ignored tests:
+            needs_network, requires <network> & \"DNS\"
+            plain_ignored
+
+        test result: ok. 1 passed; 0 failed; 2 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn json() {
+    let (_, out) = do_run(args(["--format", "json", "--test-threads", "1"]), tests());
+    assert!(out.contains(
+        concat!(
+            r#"{ "type": "test", "name": "needs_network", "event": "ignored","#,
+            r#" "reason": "requires <network> & \"DNS\"" }"#,
+        ),
+    ), "{out}");
+    assert!(out.contains(
+        r#"{ "type": "test", "name": "plain_ignored", "event": "ignored" }"#,
+    ), "{out}");
+}
+
+#[test]
+fn junit() {
+    let (_, out) = do_run(args(["--format", "junit"]), tests());
+    assert!(out.contains(
+        r#"<skipped message="requires &lt;network&gt; &amp; &quot;DNS&quot;""#,
+    ), "{out}");
+}
+
+#[test]
+fn tap() {
+    let (_, out) = do_run(args(["--format", "tap", "--test-threads", "1"]), tests());
+    assert!(out.contains("ok 1 - needs_network # SKIP requires <network> & \"DNS\"\n"), "{out}");
+    assert!(out.contains("ok 2 - plain_ignored # SKIP\n"), "{out}");
+}
+
+#[test]
+fn no_reason_when_run() {
+    let (c, out) = do_run(args(["--include-ignored", "--test-threads", "1"]), tests());
+    assert_eq!(c.num_passed, 3);
+    assert!(out.contains("test needs_network ... ok\n"), "{out}");
+}
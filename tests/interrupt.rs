@@ -0,0 +1,82 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use common::{args, do_run_with_config};
+use libtest_mimic::{RunConfig, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+/// Tests of which the second one sets the interrupt flag.
+fn tests(flag: &Arc<AtomicBool>) -> Vec<Trial> {
+    let flag = Arc::clone(flag);
+    vec![
+        Trial::test("first", || Ok(())),
+        Trial::test("presses_ctrl_c", move || {
+            flag.store(true, Ordering::SeqCst);
+            Err("interrupted".into())
+        }),
+        Trial::test("third", || Ok(())),
+        Trial::test("fourth", || Ok(())).with_always_run(true),
+    ]
+}
+
+#[test]
+fn sequential() {
+    let flag = Arc::new(AtomicBool::new(false));
+    let config = RunConfig::default().with_interrupt_flag(Arc::clone(&flag));
+    let (c, out) = do_run_with_config(args(["--test-threads", "1"]), tests(&flag), config);
+    assert_eq!(c, conclusion! {
+        num_passed: 1,
+        num_failed: 1,
+        num_not_run: 2,
+        not_run: vec!["third".into(), "fourth".into()],
+        interrupted: true,
+    });
+    assert_eq!(c.raw_exit_code(), 130);
+    assert_log!(out, "
+        running 4 tests
+        test first          ... ok
+        test presses_ctrl_c ... FAILED
+
+        run interrupted: 2 tests not run
+
+        failures:
+
+        ---- presses_ctrl_c ----
+        interrupted
+
+
+        failures:
+            presses_ctrl_c
+
+        test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn thread_pool() {
+    let flag = Arc::new(AtomicBool::new(false));
+    let config = RunConfig::default().with_interrupt_flag(Arc::clone(&flag));
+    let tests = tests(&flag).into_iter()
+        .chain((0..50).map(|i| Trial::test(format!("t{i}"), || Ok(()))))
+        .collect();
+    let (c, out) = do_run_with_config(args(["--test-threads", "2"]), tests, config);
+    assert!(c.interrupted);
+    assert!(c.num_not_run > 0, "{out}");
+    assert_eq!(c.num_passed + c.num_failed + c.num_not_run, 54);
+    assert!(out.contains("test result: FAILED."), "{out}");
+}
+
+#[test]
+fn not_interrupted() {
+    let flag = Arc::new(AtomicBool::new(false));
+    let config = RunConfig::default().with_interrupt_flag(flag);
+    let (c, _) = do_run_with_config(args([]), vec![Trial::test("a", || Ok(()))], config);
+    assert!(!c.interrupted);
+    assert_eq!(c.raw_exit_code(), 0);
+}
@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use common::{args, do_run_with_config};
+use libtest_mimic::{try_run_with_config, RunConfig, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+static CLEAN: AtomicBool = AtomicBool::new(true);
+
+// Only used by the single test below, so other tests of this binary running
+// concurrently don't interfere.
+#[test]
+fn leaking_test_fails() {
+    let tests = vec![
+        Trial::test("clean", || Ok(())),
+        Trial::test("leaks", || {
+            CLEAN.store(false, Ordering::SeqCst);
+            Ok(())
+        }),
+        Trial::test("cleans_up", || {
+            CLEAN.store(true, Ordering::SeqCst);
+            Ok(())
+        }),
+        Trial::test("leaks_and_fails", || {
+            CLEAN.store(false, Ordering::SeqCst);
+            Err("broken".into())
+        }),
+    ];
+    let config = RunConfig::default()
+        .with_invariant("always true", || true)
+        .with_invariant("clean", || CLEAN.load(Ordering::SeqCst));
+
+    // Even though 4 threads are requested, invariants need sequential mode.
+    let (c, out) = do_run_with_config(args(["--test-threads", "4"]), tests, config);
+    assert_eq!(c, conclusion! {
+        num_passed: 2,
+        num_failed: 2,
+    });
+    assert_log!(out, "
+        running 4 tests
+        test clean           ... ok
+        test leaks           ... FAILED
+        test cleans_up       ... ok
+        test leaks_and_fails ... FAILED
+
+        failures:
+
+        ---- leaks ----
+        invariant 'clean' violated
+
+        ---- leaks_and_fails ----
+        broken
+        invariant 'clean' violated
+
+
+        failures:
+            leaks
+            leaks_and_fails
+
+        test result: FAILED. 2 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+/// A timed out test keeps running and could break the invariant later.
+#[test]
+fn timeout_rejected() {
+    let config = RunConfig::default().with_invariant("clean", || true);
+    let tests = vec![Trial::test("foo", || panic!("must not run"))];
+    let e = try_run_with_config(&args(["--timeout", "1"]), tests, config).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(
+        e.to_string(),
+        "`--timeout` cannot be used with invariants (see `RunConfig::with_invariant`)",
+    );
+}
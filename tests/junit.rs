@@ -0,0 +1,77 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("parser::parses_ints", || Ok(())),
+        Trial::test("escapes", || Err("expected <a & b>\nbut got \"c\"".into())),
+        Trial::test("no_message", || Err(libtest_mimic::Failed::without_message())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+        Trial::test("with_kind", || Ok(())).with_kind("apple"),
+        Trial::test("filtered", || Ok(())),
+    ]
+}
+
+/// Replaces all `time="..."` values with `time="0.000"`.
+fn normalize_times(s: &str) -> String {
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(pos) = rest.find(r#"time=""#) {
+        let (before, after) = rest.split_at(pos + r#"time=""#.len());
+        out.push_str(before);
+        out.push_str("0.000");
+        rest = &after[after.find('"').unwrap()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[test]
+fn junit() {
+    let (c, out) = do_run(
+        args(["--format", "junit", "--skip", "filtered", "--test-threads", "1"]),
+        tests(),
+    );
+    assert_eq!(c, conclusion! {
+        num_filtered_out: 1,
+        num_passed: 2,
+        num_failed: 2,
+        num_ignored: 1,
+    });
+    assert_eq!(normalize_times(&out), concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#, "\n",
+        "<testsuites>\n",
+        r#"  <testsuite name="test" package="test" id="0" tests="5" errors="0" failures="2""#,
+        r#" skipped="1" time="0.000">"#, "\n",
+        r#"    <testcase classname="parser" name="parses_ints" time="0.000"/>"#, "\n",
+        r#"    <testcase classname="integration" name="escapes" time="0.000">"#, "\n",
+        r#"      <failure type="failure" message="expected &lt;a &amp; b&gt;">"#,
+        r#"expected &lt;a &amp; b&gt;"#, "\n",
+        r#"but got &quot;c&quot;</failure>"#, "\n",
+        "    </testcase>\n",
+        r#"    <testcase classname="integration" name="no_message" time="0.000">"#, "\n",
+        r#"      <failure type="failure"/>"#, "\n",
+        "    </testcase>\n",
+        r#"    <testcase classname="integration" name="ignored" time="0.000">"#, "\n",
+        "      <skipped/>\n",
+        "    </testcase>\n",
+        r#"    <testcase classname="apple" name="with_kind" time="0.000"/>"#, "\n",
+        "    <system-out/>\n",
+        "    <system-err/>\n",
+        "  </testsuite>\n",
+        "</testsuites>\n",
+    ));
+}
+
+#[test]
+fn soft_failures_are_skipped() {
+    let tests = vec![Trial::test("flaky", || Err("boom".into())).with_ignored_flag(true)];
+    let (c, out) = do_run(args(["--format", "junit", "--run-ignored-soft"]), tests);
+    assert_eq!(c.num_ignored_failed, 1);
+    assert!(out.contains(r#"failures="0" skipped="1""#), "{out}");
+    assert!(out.contains(r#"<skipped message="ignored (failed): boom"/>"#), "{out}");
+}
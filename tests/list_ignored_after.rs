@@ -0,0 +1,33 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("zebra", || Ok(())).with_ignored_flag(true),
+        Trial::test("runs", || Ok(())),
+        Trial::test("apple", || Ok(())).with_ignored_flag(true).with_kind("fruit"),
+    ]
+}
+
+#[test]
+fn lists_ignored() {
+    let (_, out) = do_run(args(["--list-ignored-after"]), tests());
+    assert!(out.contains(
+        "\nignored tests:\n    [fruit] apple\n    zebra\n\ntest result:",
+    ), "{out}");
+}
+
+#[test]
+fn nothing_ignored() {
+    let (_, out) = do_run(args(["--list-ignored-after", "--include-ignored"]), tests());
+    assert!(!out.contains("ignored tests:"));
+}
+
+#[test]
+fn not_listed_by_default() {
+    let (_, out) = do_run(args([]), tests());
+    assert!(!out.contains("ignored tests:"));
+}
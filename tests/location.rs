@@ -0,0 +1,69 @@
+use common::{args, check, run_with_logfile};
+use libtest_mimic::{run, Trial};
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("located", || Err("nope".into())).with_location("src/foo.rs", 42, 5),
+        Trial::test("unlocated", || Err("nope".into())),
+        Trial::test("passes", || Ok(())).with_location("src/foo.rs", 50, 5),
+    ]
+}
+
+#[test]
+fn getter() {
+    let test = Trial::test("a", || Ok(())).with_location("src/foo.rs", 42, 5);
+    assert_eq!(test.location(), Some(("src/foo.rs", 42, 5)));
+    assert_eq!(Trial::test("b", || Ok(())).location(), None);
+}
+
+#[test]
+fn failure_block() {
+    check(args(["--test-threads", "1"]), tests, 3,
+        conclusion! {
+            num_filtered_out: 0,
+            num_passed: 1,
+            num_failed: 2,
+            num_ignored: 0,
+            num_measured: 0,
+        },
+        "
+            test located   ... FAILED
+            test unlocated ... FAILED
+            test passes    ... ok
+
+            failures:
+
+            ---- located ----
+            nope
+            at src/foo.rs:42:5
+
+            ---- unlocated ----
+            nope
+
+
+            failures:
+                located
+                unlocated
+        ",
+    );
+}
+
+#[test]
+fn json() {
+    let (_, out) = run_with_logfile(
+        args(["--format", "json", "--test-threads", "1"]),
+        |args| run(args, tests()),
+    );
+    let located = out.lines()
+        .find(|l| l.contains(r#""name": "located", "event": "failed""#))
+        .unwrap();
+    assert!(located.ends_with(r#", "location": "src/foo.rs:42:5" }"#), "{out}");
+    assert!(out.contains(
+        r#""name": "passes", "event": "ok", "location": "src/foo.rs:50:5" }"#,
+    ), "{out}");
+    let unlocated = |l: &str| l.contains(r#""name": "unlocated""#);
+    assert!(!out.lines().any(|l| unlocated(l) && l.contains("location")), "{out}");
+}
@@ -0,0 +1,56 @@
+use std::path::Path;
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("nope".into())).with_kind("k"),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+        Trial::test("filtered", || Ok(())),
+        Trial::test("after_abort", || Ok(())),
+    ]
+}
+
+#[test]
+fn manifest() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_manifest.tsv");
+    let path_str = path.display().to_string();
+    let _ = do_run(
+        args([
+            "--manifest", &path_str,
+            "--skip", "filtered",
+            "--max-failures", "1",
+            "--test-threads", "1",
+        ]),
+        tests(),
+    );
+    let manifest = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let rows = manifest.lines()
+        .map(|line| line.split('\t').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let columns = |i| rows.iter().map(|row| row[i]).collect::<Vec<_>>();
+    assert_eq!(columns(0), ["passes", "[k] fails", "ignored", "filtered", "after_abort"]);
+    assert_eq!(columns(1), ["ok", "failed", "not-run", "filtered", "not-run"]);
+    assert!(rows[0][2].parse::<u64>().is_ok());
+    assert!(rows[1][2].parse::<u64>().is_ok());
+    assert_eq!(&columns(2)[2..], ["-", "-", "-"]);
+}
+
+#[test]
+fn ignored_without_abort() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_manifest_ignored.tsv");
+    let path_str = path.display().to_string();
+    let _ = do_run(args(["--manifest", &path_str, "ignored"]), tests());
+    let manifest = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(manifest.lines().nth(2), Some("ignored\tignored\t-"));
+    assert_eq!(manifest.lines().next(), Some("passes\tfiltered\t-"));
+}
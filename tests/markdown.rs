@@ -0,0 +1,67 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("zebra", || Ok(())),
+        Trial::test("fails", || Err("expected `1`\nbut got ```2```".into())),
+        Trial::test("pipe|name", || Ok(())).with_kind("k"),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+        Trial::test("also_fails", || Err(libtest_mimic::Failed::without_message())),
+        Trial::test("filtered", || Ok(())),
+    ]
+}
+
+#[test]
+fn markdown() {
+    let (c, out) = do_run(args(["--format", "markdown", "--skip", "filtered"]), tests());
+    assert_eq!(c, conclusion! {
+        num_filtered_out: 1,
+        num_passed: 2,
+        num_failed: 2,
+        num_ignored: 1,
+    });
+
+    // The execution time is not deterministic.
+    let (before, after) = out.split_once("finished in ").unwrap();
+    let out = format!("{before}finished in 0.00s{}", &after[after.find('s').unwrap() + 1..]);
+
+    assert_log!(out, "
+        ### Test results: FAILED
+
+        2 passed; 2 failed; 1 ignored; 0 measured; 1 filtered out; finished in 0.00s
+
+        | Test | Status | Duration |
+        | --- | --- | --- |
+        | [k] pipe\\|name | ok | 0.00s |
+        | also_fails | FAILED | 0.00s |
+        | fails | FAILED | 0.00s |
+        | ignored | ignored | - |
+        | zebra | ok | 0.00s |
+
+        <details>
+        <summary>Failures (2)</summary>
+
+        #### also_fails
+
+        #### fails
+
+        ````text
+        expected `1`
+        but got ```2```
+        ````
+
+        </details>
+    ");
+}
+
+#[test]
+fn no_failures() {
+    let (_, out) = do_run(args(["--format", "markdown", "--exact", "zebra"]), tests());
+    assert!(out.starts_with("### Test results: ok\n\n1 passed; 0 failed;"), "{out}");
+    assert!(out.ends_with("| zebra | ok | 0.00s |\n"), "{out}");
+}
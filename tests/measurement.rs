@@ -0,0 +1,70 @@
+use common::{args, do_run};
+use libtest_mimic::{Measurement, Trial};
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    let bench = |name: &str, measurement: Measurement| {
+        Trial::bench(name, move |test_mode| Ok((!test_mode).then_some(measurement)))
+    };
+    vec![
+        bench("plain", Measurement { avg: 1_234, variance: 56, ..Measurement::default() }),
+        bench("throughput", Measurement {
+            avg: 1_000_000,
+            variance: 20,
+            bytes: Some(10 * 1024 * 1024),
+            ..Measurement::default()
+        }),
+        bench("cycles", Measurement {
+            avg: 3_400,
+            variance: 12,
+            unit: Some("cycles".into()),
+            bytes: Some(1024),
+            ..Measurement::default()
+        }),
+    ]
+}
+
+#[test]
+fn pretty() {
+    let (_, out) = do_run(args(["--bench", "--test-threads", "1"]), tests());
+    assert_log!(out, "
+        running 3 tests
+        test plain      ... bench:       1,234 ns/iter (+/- 56)
+        test throughput ... bench:   1,000,000 ns/iter (+/- 20) = 10000.00 MiB/s
+        test cycles     ... bench:       3,400 cycles/iter (+/- 12)
+
+        test result: ok. 0 passed; 0 failed; 0 ignored; 3 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn json() {
+    let (_, out) = do_run(args(["--bench", "--format", "json", "--test-threads", "1"]), tests());
+    assert!(out.contains(
+        r#"{ "type": "bench", "name": "plain", "median": 1234, "deviation": 56 }"#,
+    ), "{out}");
+    assert!(out.contains(
+        concat!(
+            r#"{ "type": "bench", "name": "throughput", "median": 1000000, "deviation": 20,"#,
+            r#" "mib_per_second": 10000 }"#,
+        ),
+    ), "{out}");
+    assert!(out.contains(
+        concat!(
+            r#"{ "type": "bench", "name": "cycles", "median": 3400, "deviation": 12,"#,
+            r#" "unit": "cycles" }"#,
+        ),
+    ), "{out}");
+}
+
+#[test]
+fn tap() {
+    let (_, out) = do_run(args(["--bench", "--format", "tap", "--test-threads", "1"]), tests());
+    assert!(out.contains(
+        "ok 2 - throughput # bench: 1,000,000 ns/iter (+/- 20) = 10000.00 MiB/s\n",
+    ), "{out}");
+    assert!(out.contains("ok 3 - cycles # bench: 3,400 cycles/iter (+/- 12)\n"), "{out}");
+}
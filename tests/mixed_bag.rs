@@ -1,4 +1,4 @@
-use crate::common::{args, check, do_run};
+use crate::common::{args, check, do_run, in_subprocess, run_in_subprocess};
 use libtest_mimic::{Conclusion, Measurement, Trial};
 use pretty_assertions::assert_eq;
 
@@ -8,7 +8,7 @@ mod common;
 
 fn tests() -> Vec<Trial> {
     fn meas(avg: u64, variance: u64) -> Option<Measurement> {
-        Some(Measurement { avg, variance })
+        Some(Measurement { avg, variance, ..Measurement::default() })
     }
 
     vec![
@@ -36,7 +36,7 @@ fn tests() -> Vec<Trial> {
 #[test]
 fn normal() {
     check(args([]), tests, 17,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 0,
             num_passed: 4,
             num_failed: 5,
@@ -92,32 +92,24 @@ fn normal() {
 
 #[test]
 fn test_mode() {
-    check(args(["--test"]), tests, 17,
-        Conclusion {
-            num_filtered_out: 0,
+    check(args(["--test"]), tests, 9,
+        conclusion! {
+            num_filtered_out: 8,
             num_passed: 2,
             num_failed: 3,
-            num_ignored: 12,
+            num_ignored: 4,
             num_measured: 0,
         },
         "
-            test          cat    ... ok
-            test          \"ups\"  ... FAILED
-            test          dog    ... FAILED
-            test [apple]  fox    ... ok
-            test [apple]  bunny  ... FAILED
-            test          frog   ... ignored
-            test          owl    ... ignored
-            test [banana] fly    ... ignored
-            test [banana] bear   ... ignored
-            test          red    ... ignored
-            test          blue   ... ignored
-            test [kiwi]   yellow ... ignored
-            test [kiwi]   green  ... ignored
-            test          purple ... ignored
-            test          cyan   ... ignored
-            test [banana] orange ... ignored
-            test [banana] pink   ... ignored
+            test          cat   ... ok
+            test          \"ups\" ... FAILED
+            test          dog   ... FAILED
+            test [apple]  fox   ... ok
+            test [apple]  bunny ... FAILED
+            test          frog  ... ignored
+            test          owl   ... ignored
+            test [banana] fly   ... ignored
+            test [banana] bear  ... ignored
 
             failures:
 
@@ -141,24 +133,19 @@ fn test_mode() {
 
 #[test]
 fn bench_mode() {
-    check(args(["--bench"]), tests, 17,
-        Conclusion {
-            num_filtered_out: 0,
+    check(args(["--bench"]), tests, 8,
+        conclusion! {
+            num_filtered_out: 9,
             num_passed: 0,
             num_failed: 2,
-            num_ignored: 13,
+            num_ignored: 4,
             num_measured: 2,
+            measurements: vec![
+                ("red".into(), Measurement { avg: 32, variance: 3, ..Measurement::default() }),
+                ("yellow".into(), Measurement { avg: 64, variance: 4, ..Measurement::default() }),
+            ],
         },
         "
-            test          cat    ... ignored
-            test          \"ups\"  ... ignored
-            test          dog    ... ignored
-            test [apple]  fox    ... ignored
-            test [apple]  bunny  ... ignored
-            test          frog   ... ignored
-            test          owl    ... ignored
-            test [banana] fly    ... ignored
-            test [banana] bear   ... ignored
             test          red    ... bench:          32 ns/iter (+/- 3)
             test          blue   ... FAILED
             test [kiwi]   yellow ... bench:          64 ns/iter (+/- 4)
@@ -206,7 +193,7 @@ fn list() {
         [banana] orange: bench
         [banana] pink: bench
     ");
-    assert_eq!(c, Conclusion {
+    assert_eq!(c, conclusion! {
         num_filtered_out: 0,
         num_passed: 0,
         num_failed: 0,
@@ -228,7 +215,7 @@ fn list_ignored() {
         [banana] orange: bench
         [banana] pink: bench
     ");
-    assert_eq!(c, Conclusion {
+    assert_eq!(c, conclusion! {
         num_filtered_out: 0,
         num_passed: 0,
         num_failed: 0,
@@ -237,6 +224,68 @@ fn list_ignored() {
     });
 }
 
+/// A `discovered` event of `--list --format json` for a test without location.
+fn discovered(kind: &str, name: &str, ignore: bool, ignore_message: &str) -> String {
+    format!(
+        concat!(
+            r#"{{ "type": "{}", "event": "discovered", "name": "{}", "ignore": {}, "#,
+            r#""ignore_message": "{}", "source_path": "", "start_line": 0, "start_col": 0, "#,
+            r#""end_line": 0, "end_col": 0 }}"#,
+        ),
+        kind, name, ignore, ignore_message,
+    )
+}
+
+#[test]
+fn list_json() {
+    let (c, out) = common::do_run(args(["--list", "--format", "json", "o"]), tests());
+    let expected = [
+        r#"{ "type": "suite", "event": "discovery" }"#.to_owned(),
+        discovered("test", "dog", false, ""),
+        discovered("test", "fox", false, ""),
+        discovered("test", "frog", true, ""),
+        discovered("test", "owl", true, ""),
+        discovered("bench", "yellow", false, ""),
+        discovered("bench", "orange", true, ""),
+        concat!(
+            r#"{ "type": "suite", "event": "completed", "tests": 4, "benchmarks": 2, "#,
+            r#""total": 6, "ignored": 3 }"#,
+        ).to_owned(),
+    ];
+    assert_log!(out, &expected.join("\n"));
+    assert_eq!(c, Conclusion::default());
+}
+
+#[test]
+fn list_json_ignored() {
+    let tests = vec![
+        Trial::test("runs", || Ok(())),
+        Trial::test("\"quoted\"", || Ok(())).with_ignored_reason("needs \"network\""),
+    ];
+    let (_, out) = common::do_run(args(["--list", "--format", "json", "--ignored"]), tests);
+    let expected = [
+        r#"{ "type": "suite", "event": "discovery" }"#.to_owned(),
+        discovered("test", r#"\"quoted\""#, true, r#"needs \"network\""#),
+        concat!(
+            r#"{ "type": "suite", "event": "completed", "tests": 1, "benchmarks": 0, "#,
+            r#""total": 1, "ignored": 1 }"#,
+        ).to_owned(),
+    ];
+    assert_log!(out, &expected.join("\n"));
+}
+
+#[test]
+fn list_json_location() {
+    let tests = vec![Trial::test("located", || Ok(())).with_location("src/a.rs", 3, 5)];
+    let (_, out) = common::do_run(args(["--list", "--format", "json"]), tests);
+    let event = concat!(
+        r#"{ "type": "test", "event": "discovered", "name": "located", "ignore": false, "#,
+        r#""ignore_message": "", "source_path": "src/a.rs", "start_line": 3, "start_col": 5, "#,
+        r#""end_line": 3, "end_col": 5 }"#,
+    );
+    assert!(out.lines().any(|line| line == event), "{out}");
+}
+
 #[test]
 fn list_with_filter() {
     let (c, out) = common::do_run(args(["--list", "a"]), tests());
@@ -251,7 +300,7 @@ fn list_with_filter() {
         [banana] orange: bench
         [banana] pink: bench
     ");
-    assert_eq!(c, Conclusion {
+    assert_eq!(c, conclusion! {
         num_filtered_out: 0,
         num_passed: 0,
         num_failed: 0,
@@ -267,7 +316,7 @@ fn list_with_filter_exact() {
     assert_log!(out, "
         [apple] fox: test
     ");
-    assert_eq!(c, Conclusion {
+    assert_eq!(c, conclusion! {
         num_filtered_out: 0,
         num_passed: 0,
         num_failed: 0,
@@ -278,7 +327,7 @@ fn list_with_filter_exact() {
     assert_log!(out, "
         [banana] fly: test
     ");
-    assert_eq!(c, Conclusion {
+    assert_eq!(c, conclusion! {
         num_filtered_out: 0,
         num_passed: 0,
         num_failed: 0,
@@ -315,7 +364,7 @@ fn list_with_filter_exact() {
         [banana] orange: bench
         [banana] pink: bench
     ");
-    assert_eq!(c, Conclusion {
+    assert_eq!(c, conclusion! {
         num_filtered_out: 0,
         num_passed: 0,
         num_failed: 0,
@@ -329,7 +378,7 @@ fn list_with_filter_exact() {
 #[test]
 fn filter_c() {
     check(args(["c"]), tests, 2,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 15,
             num_passed: 1,
             num_failed: 0,
@@ -345,21 +394,19 @@ fn filter_c() {
 
 #[test]
 fn filter_o_test() {
-    check(args(["--test", "o"]), tests, 6,
-        Conclusion {
-            num_filtered_out: 11,
+    check(args(["--test", "o"]), tests, 4,
+        conclusion! {
+            num_filtered_out: 13,
             num_passed: 1,
             num_failed: 1,
-            num_ignored: 4,
+            num_ignored: 2,
             num_measured: 0,
         },
         "
-            test          dog    ... FAILED
-            test [apple]  fox    ... ok
-            test          frog   ... ignored
-            test          owl    ... ignored
-            test [kiwi]   yellow ... ignored
-            test [banana] orange ... ignored
+            test         dog  ... FAILED
+            test [apple] fox  ... ok
+            test         frog ... ignored
+            test         owl  ... ignored
 
             failures:
 
@@ -375,21 +422,19 @@ fn filter_o_test() {
 
 #[test]
 fn filter_o_test_include_ignored() {
-    check(args(["--test", "--include-ignored", "o"]), tests, 6,
-        Conclusion {
-            num_filtered_out: 11,
+    check(args(["--test", "--include-ignored", "o"]), tests, 4,
+        conclusion! {
+            num_filtered_out: 13,
             num_passed: 2,
             num_failed: 2,
-            num_ignored: 2,
+            num_ignored: 0,
             num_measured: 0,
         },
         "
-            test          dog    ... FAILED
-            test [apple]  fox    ... ok
-            test          frog   ... ok
-            test          owl    ... FAILED
-            test [kiwi]   yellow ... ignored
-            test [banana] orange ... ignored
+            test         dog  ... FAILED
+            test [apple] fox  ... ok
+            test         frog ... ok
+            test         owl  ... FAILED
 
             failures:
 
@@ -409,18 +454,17 @@ fn filter_o_test_include_ignored() {
 
 #[test]
 fn filter_o_test_ignored() {
-    check(args(["--test", "--ignored", "o"]), tests, 3,
-        Conclusion {
-            num_filtered_out: 14,
+    check(args(["--test", "--ignored", "o"]), tests, 2,
+        conclusion! {
+            num_filtered_out: 15,
             num_passed: 1,
             num_failed: 1,
-            num_ignored: 1,
+            num_ignored: 0,
             num_measured: 0,
         },
         "
-            test          frog   ... ok
-            test          owl    ... FAILED
-            test [banana] orange ... ignored
+            test frog ... ok
+            test owl  ... FAILED
 
             failures:
 
@@ -437,7 +481,7 @@ fn filter_o_test_ignored() {
 #[test]
 fn normal_include_ignored() {
     check(args(["--include-ignored"]), tests, 17,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 0,
             num_passed: 8,
             num_failed: 9,
@@ -510,7 +554,7 @@ fn normal_include_ignored() {
 #[test]
 fn normal_ignored() {
     check(args(["--ignored"]), tests, 8,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 9,
             num_passed: 4,
             num_failed: 4,
@@ -553,18 +597,17 @@ fn normal_ignored() {
 
 #[test]
 fn lots_of_flags() {
-    check(args(["--include-ignored", "--skip", "g", "--test", "o"]), tests, 3,
-        Conclusion {
-            num_filtered_out: 14,
+    check(args(["--include-ignored", "--skip", "g", "--test", "o"]), tests, 2,
+        conclusion! {
+            num_filtered_out: 15,
             num_passed: 1,
             num_failed: 1,
-            num_ignored: 1,
+            num_ignored: 0,
             num_measured: 0,
         },
         "
-            test [apple] fox    ... ok
-            test         owl    ... FAILED
-            test [kiwi]  yellow ... ignored
+            test [apple] fox ... ok
+            test         owl ... FAILED
 
             failures:
 
@@ -578,10 +621,60 @@ fn lots_of_flags() {
     );
 }
 
+#[test]
+fn list_tests_api() {
+    let infos = libtest_mimic::list_tests(&args(["--exact", "fox"]), &tests());
+    assert_eq!(infos.len(), 1);
+    assert_eq!(infos[0].name(), "fox");
+
+    let infos = libtest_mimic::list_tests(&args(["--ignored", "--skip", "o"]), &tests());
+    let names = infos.iter().map(|info| info.name()).collect::<Vec<_>>();
+    assert_eq!(names, ["fly", "bear", "purple", "cyan", "pink"]);
+    assert!(infos.iter().all(|info| info.has_ignored_flag()));
+    assert_eq!(infos[1].kind(), "banana");
+    assert!(infos[1].is_test());
+    assert!(infos[2].is_bench());
+}
+
+#[test]
+fn expect_tests_matching() {
+    let (c, _) = do_run(args(["--expect-tests", "17", "--exact", "cat"]), tests());
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(c.num_filtered_out, 16);
+}
+
+#[test]
+fn expect_tests_mismatch() {
+    if in_subprocess() {
+        let _ = do_run(args(["--expect-tests", "16"]), tests());
+        unreachable!();
+    }
+
+    let output = run_in_subprocess("expect_tests_mismatch");
+    assert_eq!(output.status.code(), Some(101));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("error: expected 16 tests (`--expect-tests`), but 17 were defined"));
+}
+
+#[test]
+fn test_and_bench_conflict() {
+    let mut both = args(["--test"]);
+    both.bench = true;
+    if in_subprocess() {
+        let _ = do_run(both, tests());
+        unreachable!();
+    }
+
+    let output = run_in_subprocess("test_and_bench_conflict");
+    assert_eq!(output.status.code(), Some(101));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("error: `--test` and `--bench` cannot be used together"));
+}
+
 #[test]
 fn terse_output() {
     let (c, out) = do_run(args(["--format", "terse", "--test-threads", "1"]), tests());
-    assert_eq!(c, Conclusion {
+    assert_eq!(c, conclusion! {
         num_filtered_out: 0,
         num_passed: 4,
         num_failed: 5,
@@ -621,12 +714,51 @@ fn terse_output() {
     ");
 }
 
+#[test]
+fn max_failures_shown() {
+    let (c, out) = do_run(
+        args(["--max-failures-shown", "2", "--format", "terse", "--test-threads", "1"]),
+        tests(),
+    );
+    assert_eq!(c, conclusion! {
+        num_filtered_out: 0,
+        num_passed: 4,
+        num_failed: 5,
+        num_ignored: 8,
+        num_measured: 0,
+    });
+    assert_log!(out, "
+        running 17 tests
+        .FF.Fiiii.F.Fiiii
+        failures:
+
+        ---- \"ups\" ----
+        failed to parse \"abc\"
+
+        ---- dog ----
+        was not a good boy
+
+        ... and 3 more failures
+
+
+        failures:
+            \"ups\"
+            dog
+            bunny
+            blue
+            green
+
+        test result: FAILED. 4 passed; 5 failed; 8 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
 #[test]
 fn json_output() {
     let (c, out) = do_run(args(["--format", "json", "--test-threads", "1"]), tests());
     assert_eq!(
         c,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 0,
             num_passed: 4,
             num_failed: 5,
@@ -637,3 +769,10 @@ fn json_output() {
 
     assert_log!(out, include_str!("json-output.json"));
 }
+
+#[test]
+fn json_output_filtered() {
+    let args = args(["--format", "json", "--test-threads", "1", "--exact", "dog"]);
+    let (_, out) = do_run(args, tests());
+    assert_log!(out, include_str!("json-output-filtered.json"));
+}
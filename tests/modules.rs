@@ -0,0 +1,123 @@
+use common::{args, do_run};
+use libtest_mimic::{Conclusion, ModuleCounts, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("ident", || Ok(())).with_module("lexer::tokens"),
+        Trial::test("expr", || Err("bad".into())).with_module("parser"),
+        Trial::test("plain", || Ok(())),
+        Trial::test("whitespace", || Ok(())).with_module("lexer"),
+        Trial::test("stmt", || Ok(())).with_module("parser").with_ignored_flag(true),
+        Trial::test("keyword", || Ok(())).with_module("lexer::tokens"),
+    ]
+}
+
+#[test]
+fn module_getters() {
+    let test = Trial::test("a", || Ok(())).with_module("foo::bar");
+    assert_eq!(test.module(), Some("foo::bar"));
+    assert_eq!(Trial::test("b", || Ok(())).module(), None);
+}
+
+#[test]
+fn per_module_counts() {
+    let (c, out) = do_run(args(["--test-threads", "1"]), tests());
+    assert_eq!(c.modules, vec![
+        ("lexer".into(), ModuleCounts { num_passed: 1, num_failed: 0, num_ignored: 0 }),
+        ("lexer::tokens".into(), ModuleCounts { num_passed: 2, num_failed: 0, num_ignored: 0 }),
+        ("parser".into(), ModuleCounts { num_passed: 0, num_failed: 1, num_ignored: 1 }),
+    ]);
+    assert_log!(out, "
+        running 6 tests
+        test ident      ... ok
+        test expr       ... FAILED
+        test plain      ... ok
+        test whitespace ... ok
+        test stmt       ... ignored
+        test keyword    ... ok
+
+        failures:
+
+        ---- expr ----
+        bad
+
+
+        failures:
+            expr
+
+        results per module:
+            lexer: 1 passed; 0 failed; 0 ignored
+            lexer::tokens: 2 passed; 0 failed; 0 ignored
+            parser: 0 passed; 1 failed; 1 ignored
+
+        test result: FAILED. 4 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn merge_counts() {
+    let (mut a, _) = do_run(args(["--test-threads", "1", "lexer"]), vec![
+        Trial::test("lexer_a", || Ok(())).with_module("lexer"),
+    ]);
+    let (b, _) = do_run(args(["--test-threads", "1"]), vec![
+        Trial::test("lexer_b", || Err("no".into())).with_module("lexer"),
+        Trial::test("ast", || Ok(())).with_module("ast"),
+    ]);
+    a.merge(&b);
+    assert_eq!(a.modules, vec![
+        ("ast".into(), ModuleCounts { num_passed: 1, num_failed: 0, num_ignored: 0 }),
+        ("lexer".into(), ModuleCounts { num_passed: 1, num_failed: 1, num_ignored: 0 }),
+    ]);
+}
+
+#[test]
+fn json_summary() {
+    let (_, out) = do_run(args(["--format", "json", "--test-threads", "1"]), tests());
+    assert!(out.contains(concat!(
+        r#""modules": { "lexer": { "passed": 1, "failed": 0, "ignored": 0 }, "#,
+        r#""lexer::tokens": { "passed": 2, "failed": 0, "ignored": 0 }, "#,
+        r#""parser": { "passed": 0, "failed": 1, "ignored": 1 } } }"#,
+    )), "{out}");
+}
+
+#[test]
+fn list_tree() {
+    let (c, out) = do_run(args(["--list"]), tests());
+    assert_eq!(c, Conclusion::default());
+    assert_log!(out, "
+        plain: test
+        lexer:
+            whitespace: test
+            tokens:
+                ident: test
+                keyword: test
+        parser:
+            expr: test
+            stmt: test
+    ");
+}
+
+#[test]
+fn list_json() {
+    let (_, out) = do_run(args(["--list", "--format", "json"]), tests());
+    assert!(out.contains(concat!(
+        r#"{ "type": "test", "event": "discovered", "name": "ident", "#,
+        r#""module": "lexer::tokens", "ignore": false, "ignore_message": "", "#,
+    )), "{out}");
+    assert!(out.contains(concat!(
+        r#"{ "type": "test", "event": "discovered", "name": "plain", "ignore": false, "#,
+        r#""ignore_message": "", "#,
+    )), "{out}");
+}
+
+#[test]
+fn no_modules_no_summary() {
+    let (c, out) = do_run(args(["--test-threads", "1"]), vec![Trial::test("a", || Ok(()))]);
+    assert!(c.modules.is_empty());
+    assert!(!out.contains("per module"), "{out}");
+}
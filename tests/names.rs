@@ -0,0 +1,95 @@
+use common::{args, do_run, in_subprocess, run_in_subprocess};
+use libtest_mimic::{param_name, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn param_names_are_exact_friendly() {
+    let values = ["simple", "with space", "tab\there", "ümlaut", "a/b:c"];
+    let names = values.iter().map(|v| param_name("case", v)).collect::<Vec<_>>();
+    assert_eq!(names, [
+        "case[simple]",
+        "case[with_space]",
+        "case[tab_here]",
+        "case[ümlaut]",
+        "case[a/b:c]",
+    ]);
+
+    let tests = names.iter().map(|name| Trial::test(name, || Ok(()))).collect();
+    let (c, _) = do_run(args(["--exact", "case[with_space]"]), tests);
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(c.num_filtered_out, 4);
+}
+
+#[test]
+fn same_name_different_kind_is_fine() {
+    let tests = vec![
+        Trial::test("foo", || Ok(())),
+        Trial::test("foo", || Ok(())).with_kind("other"),
+    ];
+    let (c, _) = do_run(args([]), tests);
+    assert_eq!(c.num_passed, 2);
+}
+
+#[test]
+fn duplicate_names() {
+    if in_subprocess() {
+        let tests = vec![
+            Trial::test(param_name("case", "a b"), || Ok(())),
+            Trial::test(param_name("case", "a_b"), || Ok(())),
+            Trial::test("unique", || Ok(())),
+        ];
+        let _ = do_run(args([]), tests);
+        unreachable!();
+    }
+
+    let output = run_in_subprocess("duplicate_names");
+    assert_eq!(output.status.code(), Some(101));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("error: duplicate test names: case[a_b]"), "{stderr}");
+}
+
+#[test]
+fn validate_names() {
+    let tests = vec![
+        Trial::test("b", || Ok(())),
+        Trial::test("a", || Ok(())),
+        Trial::test("b", || Ok(())),
+        Trial::test("a", || Ok(())),
+        Trial::test("a", || Ok(())).with_kind("other"),
+    ];
+    assert_eq!(libtest_mimic::validate_names(&tests), Err("duplicate test names: a, b".into()));
+    assert_eq!(libtest_mimic::validate_names(&tests[..2]), Ok(()));
+}
+
+#[test]
+fn rename_duplicates() {
+    let tests = vec![
+        Trial::test("case", || Ok(())),
+        Trial::test("case", || Ok(())),
+        Trial::test("case#2", || Ok(())),
+        Trial::test("case", || Ok(())),
+    ];
+    let (c, out) = do_run(args(["--rename-duplicates", "--test-threads", "1"]), tests);
+    assert_eq!(c.num_passed, 4);
+    assert_log!(out, "
+        running 4 tests
+        test case   ... ok
+        test case#3 ... ok
+        test case#2 ... ok
+        test case#4 ... ok
+
+        test result: ok. 4 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn renamed_duplicates_can_be_selected() {
+    let tests = vec![Trial::test("case", || Ok(())), Trial::test("case", || Err("second".into()))];
+    let (c, _) = do_run(args(["--rename-duplicates", "--exact", "case#2"]), tests);
+    assert_eq!(c.num_failed, 1);
+    assert_eq!(c.num_filtered_out, 1);
+}
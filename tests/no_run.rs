@@ -0,0 +1,41 @@
+use common::{args, do_run};
+use libtest_mimic::{Conclusion, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("foo", || panic!("must not run")),
+        Trial::test("bar", || panic!("must not run")),
+        Trial::test("barro", || panic!("must not run")).with_ignored_flag(true),
+        Trial::bench("bench", |_| panic!("must not run")),
+    ]
+}
+
+#[test]
+fn counts_without_running() {
+    let (c, out) = do_run(args(["--no-run"]), tests());
+    assert_eq!(c, Conclusion::default());
+    assert_eq!(out, "3 tests would run, 1 ignored, 0 filtered out (--no-run)\n");
+}
+
+#[test]
+fn applies_filters() {
+    let (c, out) = do_run(args(["--no-run", "bar", "--skip", "barro"]), tests());
+    assert_eq!(c, conclusion! { num_filtered_out: 3 });
+    assert_eq!(out, "1 test would run, 0 ignored, 3 filtered out (--no-run)\n");
+}
+
+#[test]
+fn same_in_json() {
+    let (_, out) = do_run(args(["--no-run", "--format", "json", "--test"]), tests());
+    assert_eq!(out, "2 tests would run, 1 ignored, 1 filtered out (--no-run)\n");
+}
+
+#[test]
+fn include_ignored() {
+    let (_, out) = do_run(args(["--no-run", "--include-ignored"]), tests());
+    assert_eq!(out, "4 tests would run, 0 ignored, 0 filtered out (--no-run)\n");
+}
@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    let mut tests = (0..10)
+        .map(|i| Trial::test(format!("test_{i}"), || Ok(())))
+        .collect::<Vec<_>>();
+    tests.push(Trial::test("skipped", || Ok(())).with_ignored_flag(true));
+    tests
+}
+
+fn test_lines(out: &str) -> Vec<&str> {
+    out.lines().filter(|l| l.contains(" ... ")).collect()
+}
+
+#[test]
+fn record_and_replay() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_order.txt");
+    let path_str = path.display().to_string();
+
+    let (_, recorded) = do_run(
+        args(["--shuffle", "--seed", "7", "--test-threads", "1", "--record-order", &path_str]),
+        tests(),
+    );
+    let order = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(order.lines().count(), 10);
+    assert!(!order.contains("skipped"));
+
+    // Replaying works even with multiple threads requested and reproduces the
+    // order. The ignored test wasn't recorded and thus comes last.
+    let (_, replayed) = do_run(args(["--test-threads", "4", "--replay-order", &path_str]), tests());
+    std::fs::remove_file(&path).unwrap();
+
+    let mut expected = test_lines(&recorded);
+    let skipped_pos = expected.iter().position(|l| l.contains("skipped")).unwrap();
+    let skipped = expected.remove(skipped_pos);
+    expected.push(skipped);
+    assert_eq!(test_lines(&replayed), expected);
+}
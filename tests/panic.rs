@@ -1,5 +1,5 @@
-use common::{args, check};
-use libtest_mimic::{Trial, Conclusion};
+use common::{args, check, in_subprocess, run_in_subprocess};
+use libtest_mimic::Trial;
 
 #[macro_use]
 mod common;
@@ -15,7 +15,7 @@ fn tests() -> Vec<Trial> {
 #[test]
 fn normal() {
     check(args([]), tests, 2,
-        Conclusion {
+        conclusion! {
             num_filtered_out: 0,
             num_passed: 1,
             num_failed: 1,
@@ -30,6 +30,7 @@ fn normal() {
 
             ---- panics ----
             test panicked: uh oh
+              --> tests/panic.rs:11:34
 
 
             failures:
@@ -37,3 +38,51 @@ fn normal() {
         "
     );
 }
+
+#[test]
+fn many_panics_in_pool() {
+    let tests = (0..20)
+        .map(|i| match i % 3 {
+            0 => Trial::test(format!("panic_{i:02}"), move || panic!("panic {i}")),
+            1 => Trial::test(format!("panic_{i:02}"), || std::panic::panic_any(42u32)),
+            _ => Trial::test(format!("pass_{i:02}"), || Ok(())),
+        })
+        .collect();
+    let (c, out) = common::do_run(args(["--test-threads", "4"]), tests);
+    assert_eq!(c.num_failed, 14);
+    assert_eq!(c.num_passed, 6);
+    assert!(out.contains(
+        "---- panic_03 ----\ntest panicked: panic 3\n  --> tests/panic.rs:",
+    ), "{out}");
+    assert!(out.contains("---- panic_04 ----\ntest panicked\n  --> tests/panic.rs:"), "{out}");
+    assert!(out.contains("test result: FAILED. 6 passed; 14 failed;"), "{out}");
+}
+
+#[test]
+fn no_stray_panic_output() {
+    let tests = || (0..8).map(|i| Trial::test(format!("panic_{i}"), || panic!("boom"))).collect();
+    if in_subprocess() {
+        // The default hook of older Rust versions writes each message in
+        // several parts, which would interleave between threads.
+        let show = std::env::var_os("SHOW_PANICS").is_some();
+        let mut args = args(["--test-threads", if show { "1" } else { "4" }]);
+        args.show_panic_immediately = show;
+        let c = libtest_mimic::run(&args, tests());
+        assert_eq!(c.num_failed, 8);
+        return;
+    }
+
+    let output = run_in_subprocess("no_stray_panic_output");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "{stderr}");
+    assert!(!stderr.contains("panicked at"), "{stderr}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.matches("test panicked: boom\n  --> tests/panic.rs:").count(), 8, "{stdout}");
+
+    let output = common::subprocess_command("no_stray_panic_output")
+        .env("SHOW_PANICS", "1")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("panicked at tests/panic.rs:").count(), 8, "{stderr}");
+}
@@ -0,0 +1,25 @@
+use common::{args, do_run, in_subprocess, run_in_subprocess};
+use pretty_assertions::assert_eq;
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn pick_requires_terminal() {
+    if in_subprocess() {
+        let _ = do_run(args(["--pick"]), vec![Trial::test("foo", || Ok(()))]);
+        unreachable!();
+    }
+
+    let output = run_in_subprocess("pick_requires_terminal");
+    assert_eq!(output.status.code(), Some(101));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("error: `--pick` requires stdin to be an interactive terminal"));
+}
+
+#[test]
+fn pick_ignored_when_listing() {
+    let (_, out) = do_run(args(["--pick", "--list"]), vec![Trial::test("foo", || Ok(()))]);
+    assert_eq!(out.trim(), "foo: test");
+}
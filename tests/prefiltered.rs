@@ -0,0 +1,90 @@
+use std::path::Path;
+
+use common::{args, do_run_with_config};
+use libtest_mimic::{RunConfig, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("foo", || Ok(())),
+        Trial::test("bar", || Ok(())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+fn prefiltered() -> RunConfig {
+    RunConfig::default().with_prefiltered(true)
+}
+
+#[test]
+fn no_filtering() {
+    let (c, out) = do_run_with_config(
+        args(["foo", "--skip", "bar", "--exact", "--test-threads", "1"]),
+        tests(),
+        prefiltered(),
+    );
+    assert_eq!(c, conclusion! {
+        num_passed: 2,
+        num_ignored: 1,
+    });
+    assert_log!(out, "
+        running 3 tests
+        test foo     ... ok
+        test bar     ... ok
+        test ignored ... ignored
+
+        test result: ok. 2 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn other_args_honored() {
+    let (c, _) = do_run_with_config(args(["bar", "--include-ignored"]), tests(), prefiltered());
+    assert_eq!(c.num_passed, 3);
+
+    let (_, out) = do_run_with_config(args(["bar", "--list"]), tests(), prefiltered());
+    assert_log!(out, "
+        foo: test
+        bar: test
+        ignored: test
+    ");
+}
+
+#[test]
+fn predicates_still_apply() {
+    let config = prefiltered().with_filter_predicate(|info| info.name() != "foo");
+    let (c, _) = do_run_with_config(args(["foo", "--test-threads", "1"]), tests(), config);
+    assert_eq!((c.num_passed, c.num_filtered_out), (1, 1));
+}
+
+#[test]
+fn changed_files_still_apply() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_prefiltered_changed.txt");
+    std::fs::write(&path, "src/bar.rs\n").unwrap();
+    let path_str = path.display().to_string();
+    let tests = vec![
+        Trial::test("foo", || Ok(())).with_source_files(["src/foo.rs"]),
+        Trial::test("bar", || Ok(())).with_source_files(["src/bar.rs"]),
+    ];
+    let args = args(["foo", "--changed-files", &path_str, "--test-threads", "1"]);
+    let (c, _) = do_run_with_config(args, tests, prefiltered());
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!((c.num_passed, c.num_filtered_out), (1, 1));
+}
+
+#[test]
+fn mode_filters_still_apply() {
+    let tests = || vec![
+        Trial::test("foo", || Ok(())),
+        Trial::bench("bench", |_| Ok(None)),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ];
+    let (c, _) = do_run_with_config(args(["foo", "--ignored"]), tests(), prefiltered());
+    assert_eq!((c.num_passed, c.num_filtered_out), (1, 2));
+    let (c, _) = do_run_with_config(args(["foo", "--test"]), tests(), prefiltered());
+    assert_eq!((c.num_passed, c.num_ignored, c.num_filtered_out), (1, 1, 1));
+}
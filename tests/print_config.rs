@@ -0,0 +1,60 @@
+use common::{args, do_run};
+use libtest_mimic::{Conclusion, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("foo", || panic!("must not run")),
+        Trial::test("bar", || panic!("must not run")),
+        Trial::test("barro", || panic!("must not run")),
+    ]
+}
+
+#[test]
+fn known_args() {
+    let (c, out) = do_run(
+        args(["--print-config", "bar", "foo", "--skip", "barro", "--test-threads", "3", "-q"]),
+        tests(),
+    );
+    assert_eq!(c, Conclusion::default());
+    let features = [
+        ("regex", cfg!(feature = "regex")),
+        ("async", cfg!(feature = "async")),
+        ("sigint", cfg!(feature = "sigint")),
+    ];
+    let features = features.iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    let features = if features.is_empty() { "(none)".into() } else { features.join(", ") };
+    assert_eq!(out, format!("\
+        format:        terse\n\
+        color:         auto (disabled)\n\
+        test threads:  3\n\
+        mode:          tests\n\
+        filters:       \"bar\", \"foo\"\n\
+        skip:          \"barro\"\n\
+        skip exact:    (none)\n\
+        exact:         false\n\
+        filter regex:  false\n\
+        ignored tests: skipped\n\
+        selected:      2 of 3 tests\n\
+        features:      {features}\n\
+    "));
+}
+
+#[test]
+fn sequential() {
+    let (_, out) = do_run(
+        args(["--print-config", "--test-threads", "1", "--color", "always", "--include-ignored"]),
+        tests(),
+    );
+    assert!(out.contains("\ncolor:         always (enabled)\n"), "{out}");
+    assert!(out.contains("\ntest threads:  1 (sequentially on the main thread)\n"), "{out}");
+    assert!(out.contains("\nfilters:       (none)\n"), "{out}");
+    assert!(out.contains("\nignored tests: included\n"), "{out}");
+    assert!(out.contains("\nselected:      3 of 3 tests\n"), "{out}");
+}
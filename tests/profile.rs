@@ -0,0 +1,48 @@
+use std::{path::Path, thread, time::Duration};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    let sleeping = |ms| move || {
+        thread::sleep(Duration::from_millis(ms));
+        Ok(())
+    };
+    vec![
+        Trial::test("first", sleeping(20)),
+        Trial::test("second", sleeping(20)),
+        Trial::test("with \"quotes\"", sleeping(0)),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn chrome_trace() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_profile.json");
+    let path_str = path.display().to_string();
+    let _ = do_run(args(["--profile", &path_str, "--test-threads", "2"]), tests());
+    let profile = std::fs::read_to_string(&path).unwrap();
+
+    assert!(profile.starts_with("{ \"traceEvents\": [\n"));
+    assert!(profile.ends_with("\n] }\n"));
+    let mut events = profile.lines()
+        .filter(|l| l.contains(r#""ph": "X""#))
+        .collect::<Vec<_>>();
+    events.sort();
+
+    // Ignored tests did not run and are not part of the profile.
+    assert_eq!(events.len(), 3, "{profile}");
+    assert!(events[0].starts_with(r#"{ "name": "first", "ph": "X", "ts": "#));
+    assert!(events[1].starts_with(r#"{ "name": "second", "#));
+    assert!(events[2].starts_with(r#"{ "name": "with \"quotes\"", "#));
+
+    // Both sleeping tests took at least 20ms = 20000µs.
+    for event in &events[..2] {
+        let dur = event.split(r#""dur": "#).nth(1).unwrap().split(',').next().unwrap();
+        assert!(dur.parse::<u64>().unwrap() >= 20_000, "{event}");
+    }
+}
@@ -0,0 +1,49 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    (0..20).map(|i| Trial::test(format!("test_{i:02}"), || Ok(()))).collect()
+}
+
+#[test]
+fn plain() {
+    let (_, out) = do_run(args(["--progress", "plain", "--test-threads", "1"]), tests());
+    let progress = out.lines().filter(|l| l.starts_with("progress: ")).collect::<Vec<_>>();
+    assert_eq!(progress.len(), 9);
+    assert_eq!(progress[0], "progress: 2/20 tests finished");
+    assert_eq!(progress[8], "progress: 18/20 tests finished");
+    assert!(!out.contains('\r'));
+}
+
+#[test]
+fn inplace() {
+    let (_, out) = do_run(args(["--progress", "inplace", "--test-threads", "1"]), tests());
+    assert!(out.contains("test test_00 ... ok\n[1/20 tests finished]\r"));
+
+    // The progress line is always removed again before the summary.
+    let cleared = format!("[20/20 tests finished]\r{}\r", " ".repeat(22));
+    assert!(out.contains(&cleared), "{out:?}");
+    let (_, summary) = out.rsplit_once('\r').unwrap();
+    assert!(summary.trim().starts_with("test result: ok. 20 passed;"), "{summary:?}");
+}
+
+#[test]
+fn off_by_default_in_logfiles() {
+    let (_, out) = do_run(args(["--test-threads", "1"]), tests());
+    assert!(!out.contains("tests finished"));
+}
+
+#[test]
+fn no_spinner_in_logfiles() {
+    let (_, with_spinner) = do_run(args(["--spinner", "--test-threads", "1"]), tests());
+    let (_, without) = do_run(args(["--test-threads", "1"]), tests());
+    assert!(!with_spinner.contains('\x08'));
+    assert_eq!(
+        with_spinner.lines().filter(|l| l.starts_with("test ")).collect::<Vec<_>>(),
+        without.lines().filter(|l| l.starts_with("test ")).collect::<Vec<_>>(),
+    );
+}
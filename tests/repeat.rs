@@ -0,0 +1,107 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+/// A repeatable test that fails on every second call, a repeatable test that
+/// always passes and a test that can only be run once.
+fn tests(calls: &Arc<AtomicU32>) -> Vec<Trial> {
+    let calls = Arc::clone(calls);
+    vec![
+        Trial::test_repeatable("flaky", move || match calls.fetch_add(1, Ordering::SeqCst) % 2 {
+            0 => Ok(()),
+            _ => Err("unlucky".into()),
+        }),
+        Trial::test_repeatable("stable", || Ok(())),
+        Trial::test("once", || Ok(())),
+    ]
+}
+
+#[test]
+fn repeats_repeatable_tests() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let (c, out) = do_run(args(["--repeat", "3", "--test-threads", "1"]), tests(&calls));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(c, conclusion! {
+        num_passed: 6,
+        num_failed: 1,
+    });
+    assert_log!(out, "
+             Running iteration 1/3
+
+        running 3 tests
+        test flaky  ... ok
+        test stable ... ok
+        test once   ... ok
+
+        test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+
+
+             Running iteration 2/3
+
+        running 2 tests
+        test flaky  ... FAILED
+        test stable ... ok
+
+        failures:
+
+        ---- flaky ----
+        unlucky
+
+
+        failures:
+            flaky
+
+        test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+
+
+             Running iteration 3/3
+
+        running 2 tests
+        test flaky  ... ok
+        test stable ... ok
+
+        test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+
+
+        total: FAILED. 6 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+
+        repeated 3 times: 7 attempts; 1 failures
+    ");
+}
+
+#[test]
+fn filters_apply_to_every_iteration() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let (c, _) = do_run(args(["--repeat", "2", "stable"]), tests(&calls));
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+    assert_eq!(c.num_passed, 2);
+    // `once` is not part of the second iteration at all.
+    assert_eq!(c.num_filtered_out, 3);
+}
+
+#[test]
+fn once_by_default() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let (c, out) = do_run(args([]), tests(&calls));
+    assert_eq!(c.num_passed, 3);
+    assert!(!out.contains("iteration"), "{out}");
+}
+
+#[test]
+fn list_is_not_repeated() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let (_, out) = do_run(args(["--repeat", "2", "--list"]), tests(&calls));
+    assert_eq!(out, "flaky: test\nstable: test\nonce: test\n");
+}
@@ -0,0 +1,76 @@
+use std::{thread, time::Duration};
+
+use common::{args, run_with_logfile};
+use libtest_mimic::{run, Trial};
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("quick", || Ok(())),
+        Trial::test("sleepy", || {
+            thread::sleep(Duration::from_millis(50));
+            Ok(())
+        }),
+        Trial::test("skipped", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+/// Replaces all durations like `(0.05s)` with `(0.00s)`.
+fn normalize(out: &str) -> String {
+    out.lines()
+        .map(|line| match line.rfind(" (") {
+            Some(pos) if line.ends_with("s)") => format!("{} (0.00s)", &line[..pos]),
+            _ => line.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn pretty() {
+    let mut total = Duration::ZERO;
+    let (_, out) = run_with_logfile(
+        args(["--report-time", "--test-threads", "1"]),
+        |args| {
+            let c = run(args, tests());
+            total = c.total_duration;
+            c
+        },
+    );
+    assert!(out.contains("test sleepy  ... ok (0.0"), "{out}");
+    assert_log!(normalize(&out), "
+        running 3 tests
+        test quick   ... ok (0.00s)
+        test sleepy  ... ok (0.00s)
+        test skipped ... ignored
+
+        test result: ok. 2 passed; 0 failed; 1 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+    assert!(total >= Duration::from_millis(50));
+}
+
+#[test]
+fn without_flag() {
+    let (_, out) = run_with_logfile(args([]), |args| run(args, tests()));
+    assert!(!out.contains("s)"), "{out}");
+}
+
+#[test]
+fn json_exec_time() {
+    let (_, out) = run_with_logfile(
+        args(["--report-time", "--format", "json", "sleepy"]),
+        |args| run(args, tests()),
+    );
+    let line = out.lines()
+        .find(|line| line.contains(r#""name": "sleepy", "event": "ok""#))
+        .unwrap();
+    let secs = line.split(r#""exec_time": "#).nth(1).unwrap().trim_end_matches(" }");
+    assert!(secs.parse::<f64>().unwrap() >= 0.05, "{line}");
+
+    let args = args(["--format", "json", "sleepy"]);
+    let (_, out) = run_with_logfile(args, |args| run(args, tests()));
+    assert!(out.contains(r#"{ "type": "test", "name": "sleepy", "event": "ok" }"#), "{out}");
+}
@@ -0,0 +1,77 @@
+use std::sync::{Arc, Mutex};
+
+use common::args;
+use libtest_mimic::{
+    run_with_config, Arguments, Conclusion, Failed, Measurement, Outcome, RunConfig, Trial,
+};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("broken".into())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+        Trial::bench("bench", |test_mode| {
+            let measurement = Measurement { avg: 100, variance: 5, ..Measurement::default() };
+            Ok((!test_mode).then_some(measurement))
+        }),
+        Trial::test("filtered", || Ok(())),
+    ]
+}
+
+fn run(args: Arguments) -> Conclusion {
+    let config = RunConfig::default().with_writer(Arc::new(Mutex::new(std::io::sink())));
+    run_with_config(&args, tests(), config)
+}
+
+#[test]
+fn tests_mode() {
+    let c = run(args(["--test-threads", "1", "--skip", "filtered"]));
+    assert_eq!(c.results, [
+        ("passes".to_owned(), Outcome::Passed),
+        ("fails".to_owned(), Outcome::Failed(Failed::from("broken"))),
+        ("ignored".to_owned(), Outcome::Ignored),
+        ("bench".to_owned(), Outcome::Passed),
+    ]);
+    assert_eq!(c.num_passed, 2);
+    assert_eq!(c.num_filtered_out, 1);
+}
+
+#[test]
+fn bench_mode() {
+    let c = run(args(["--bench", "--test-threads", "1"]));
+    assert_eq!(c.results, [
+        ("bench".to_owned(), Outcome::Measured(Measurement {
+            avg: 100,
+            variance: 5,
+            ..Measurement::default()
+        })),
+    ]);
+}
+
+#[test]
+fn parallel_contains_all() {
+    let c = run(args(["--test-threads", "4"]));
+    let mut names = c.results.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(names, ["bench", "fails", "filtered", "ignored", "passes"]);
+}
+
+#[test]
+fn merge() {
+    let mut c = run(args(["passes", "--exact"]));
+    c.merge(&run(args(["fails"])));
+    assert_eq!(c.results, [
+        ("passes".to_owned(), Outcome::Passed),
+        ("fails".to_owned(), Outcome::Failed(Failed::from("broken"))),
+    ]);
+}
+
+#[test]
+fn not_compared_by_helpers() {
+    let (c, _) = common::do_run(args(["passes", "--exact"]), tests());
+    assert!(c.results.is_empty());
+}
@@ -0,0 +1,151 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use common::{args, do_run};
+use libtest_mimic::{Failed, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn is_transient(failed: &Failed) -> bool {
+    failed.message().is_some_and(|msg| msg.contains("connection reset"))
+}
+
+/// A test that fails with `msg` the first `num_failures` times.
+fn flaky(name: &str, num_failures: usize, msg: &'static str) -> (Trial, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let trial = Trial::test_repeatable(name, move || {
+        if counter.fetch_add(1, Ordering::SeqCst) < num_failures {
+            Err(msg.into())
+        } else {
+            Ok(())
+        }
+    });
+    (trial, calls)
+}
+
+#[test]
+fn retries_only_matching_failures() {
+    let (transient, transient_calls) = flaky("transient", 2, "connection reset by peer");
+    let (deterministic, deterministic_calls) = flaky("deterministic", 2, "assertion failed");
+    let tests = vec![
+        transient.with_retry_if(3, is_transient),
+        deterministic.with_retry_if(3, is_transient),
+    ];
+    let (c, out) = do_run(args(["--test-threads", "1"]), tests);
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(c.num_failed, 1);
+    assert_eq!(transient_calls.load(Ordering::SeqCst), 3);
+    assert_eq!(deterministic_calls.load(Ordering::SeqCst), 1);
+    assert!(out.contains("warnings:\n    transient: passed after 3 attempts\n"), "{out}");
+    assert!(out.contains("---- deterministic ----\nassertion failed\n\n"), "{out}");
+}
+
+#[test]
+fn gives_up_after_max_retries() {
+    let (trial, calls) = flaky("always_resets", 10, "connection reset");
+    let (c, out) = do_run(args([]), vec![trial.with_retry_if(2, is_transient)]);
+    assert_eq!(c.num_failed, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert!(out.contains(
+        "---- always_resets ----\nconnection reset\n(failed after 3 attempts)\n",
+    ), "{out}");
+}
+
+#[test]
+fn retries_panics() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let trial = Trial::test_repeatable("panics_once", move || {
+        if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+            panic!("connection reset");
+        }
+        Ok(())
+    });
+    let (c, _) = do_run(args([]), vec![trial.with_retry_if(1, is_transient)]);
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+#[should_panic(expected = "requires a trial created via `Trial::test_repeatable`")]
+fn requires_repeatable_trial() {
+    let _ = Trial::test("once", || Ok(())).with_retry_if(1, |_| true);
+}
+
+#[test]
+fn retries_flag() {
+    let (flaky_test, flaky_calls) = flaky("flaky", 1, "network down");
+    let (broken, broken_calls) = flaky("broken", 10, "network down");
+    let once_calls = Arc::new(AtomicUsize::new(0));
+    let once_counter = Arc::clone(&once_calls);
+    let tests = vec![
+        flaky_test,
+        broken,
+        Trial::test("once", move || {
+            once_counter.fetch_add(1, Ordering::SeqCst);
+            Err("not repeatable".into())
+        }),
+        Trial::test_repeatable("ignored", || Err("nope".into())).with_ignored_flag(true),
+    ];
+    let (c, out) = do_run(args(["--retries", "2", "--test-threads", "1"]), tests);
+    assert_eq!(c, conclusion! {
+        num_passed: 1,
+        num_flaky: 1,
+        num_failed: 2,
+        num_ignored: 1,
+    });
+    assert_eq!(flaky_calls.load(Ordering::SeqCst), 2);
+    assert_eq!(broken_calls.load(Ordering::SeqCst), 3);
+    assert_eq!(once_calls.load(Ordering::SeqCst), 1);
+    assert_log!(out, "
+        running 4 tests
+        test flaky   ... ok (flaky, passed on attempt 2)
+        test broken  ... FAILED
+        test once    ... FAILED
+        test ignored ... ignored
+
+        failures:
+
+        ---- broken ----
+        network down
+        (failed after 3 attempts)
+
+        ---- once ----
+        not repeatable
+
+
+        failures:
+            broken
+            once
+
+        test result: FAILED. 1 passed; 2 failed; 1 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn retries_flag_in_thread_pool() {
+    let (trials, calls): (Vec<_>, Vec<_>) = (0..8).map(|i| flaky(&format!("t{i}"), 1, "x")).unzip();
+    let (c, _) = do_run(args(["--retries", "1", "--test-threads", "4"]), trials);
+    assert_eq!(c.num_passed, 8);
+    assert_eq!(c.num_flaky, 8);
+    assert!(calls.iter().all(|calls| calls.load(Ordering::SeqCst) == 2));
+}
+
+#[test]
+fn retries_flag_not_for_benchmarks() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let bench = Trial::bench("bench", move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Err("slow".into())
+    });
+    let (c, _) = do_run(args(["--retries", "3", "--bench"]), vec![bench]);
+    assert_eq!(c.num_failed, 1);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
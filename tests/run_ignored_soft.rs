@@ -0,0 +1,58 @@
+use common::{args, check, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("disabled_passes", || Ok(())).with_ignored_flag(true),
+        Trial::test("disabled_fails", || Err("still broken".into())).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn soft_failures() {
+    check(args(["--run-ignored-soft"]), tests, 3,
+        conclusion! {
+            num_passed: 2,
+            num_ignored_failed: 1,
+        },
+        "
+            test passes          ... ok
+            test disabled_passes ... ok
+            test disabled_fails  ... ignored (failed)
+
+            ignored failures:
+
+            ---- disabled_fails ----
+            still broken
+
+
+            ignored failures:
+                disabled_fails
+        ",
+    );
+}
+
+#[test]
+fn summary_line() {
+    let (c, out) = do_run(args(["--run-ignored-soft"]), tests());
+    assert!(!c.has_failed());
+    assert!(out.contains(
+        "test result: ok. 2 passed; 0 failed; 0 ignored; 1 ignored (failed); 0 measured; \
+            0 filtered out;"
+    ));
+}
+
+#[test]
+fn real_failures_still_fail() {
+    let mut tests = tests();
+    tests.push(Trial::test("fails", || Err("nope".into())));
+    let (c, out) = do_run(args(["--run-ignored-soft", "--test-threads", "1"]), tests);
+    assert!(c.has_failed());
+    assert_eq!((c.num_failed, c.num_ignored_failed), (1, 1));
+    assert!(out.contains("test fails           ... FAILED\n"));
+}
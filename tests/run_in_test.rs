@@ -0,0 +1,30 @@
+use libtest_mimic::{run_in_test, Trial};
+
+#[test]
+fn all_passing() {
+    let tests = vec![
+        Trial::test("foo", || Ok(())),
+        Trial::test("bar", || Ok(())).with_ignored_flag(true),
+    ];
+    assert_eq!(run_in_test(tests), Ok(()));
+}
+
+#[test]
+fn failing() {
+    let tests = vec![
+        Trial::test("foo", || Ok(())),
+        Trial::test("bar", || Err("bar is broken".into())),
+    ];
+    let output = run_in_test(tests).unwrap_err();
+    assert!(output.contains("---- bar ----\nbar is broken"));
+    assert!(output.contains("test result: FAILED. 1 passed; 1 failed;"));
+}
+
+#[test]
+fn duplicate_names() {
+    let tests = vec![
+        Trial::test("foo", || panic!("must not run")),
+        Trial::test("foo", || panic!("must not run")),
+    ];
+    assert_eq!(run_in_test(tests), Err("duplicate test names: foo".into()));
+}
@@ -0,0 +1,62 @@
+use common::{args, do_run, do_run_with_config};
+use libtest_mimic::{RunConfig, Trial};
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![Trial::test("passes", || Ok(()))]
+}
+
+fn config() -> RunConfig {
+    RunConfig::default().with_run_metadata([("sha", "abc123"), ("branch", "main")])
+}
+
+#[test]
+fn junit_properties() {
+    let (_, out) = do_run_with_config(
+        args(["--format", "junit", "--run-meta", "build=https://ci/1?a=<b>"]),
+        tests(),
+        config(),
+    );
+    assert!(out.contains(concat!(
+        "    <properties>\n",
+        "      <property name=\"sha\" value=\"abc123\"/>\n",
+        "      <property name=\"branch\" value=\"main\"/>\n",
+        "      <property name=\"build\" value=\"https://ci/1?a=&lt;b&gt;\"/>\n",
+        "    </properties>\n",
+        "    <testcase ",
+    )), "{out}");
+}
+
+#[test]
+fn json_metadata() {
+    let (_, out) = do_run_with_config(
+        args(["--format", "json", "--run-meta", "note=say \"hi\""]),
+        tests(),
+        config(),
+    );
+    let first = out.lines().next().unwrap();
+    assert_eq!(
+        first,
+        concat!(
+            r#"{ "type": "suite", "event": "started", "test_count": 1, "metadata": {"#,
+            r#" "sha": "abc123", "branch": "main", "note": "say \"hi\"" } }"#,
+        ),
+    );
+}
+
+#[test]
+fn no_metadata() {
+    let (_, out) = do_run(args(["--format", "json"]), tests());
+    assert!(!out.contains("metadata"), "{out}");
+    let (_, out) = do_run(args(["--format", "junit"]), tests());
+    assert!(!out.contains("<properties>"), "{out}");
+}
+
+#[test]
+fn pretty_output_ignores_metadata() {
+    let (_, with) = do_run_with_config(args(["--run-meta", "a=b"]), tests(), config());
+    let (_, without) = do_run(args([]), tests());
+    assert_eq!(with, without);
+}
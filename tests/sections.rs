@@ -0,0 +1,66 @@
+use common::{args, run_with_logfile};
+use libtest_mimic::{run_sections, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn sections() -> Vec<(String, Vec<Trial>)> {
+    vec![
+        ("plugin-a".into(), vec![
+            Trial::test("foo", || Ok(())),
+            Trial::test("bar", || Err("broken".into())),
+        ]),
+        ("plugin-b".into(), vec![
+            Trial::test("baz", || Ok(())),
+            Trial::test("foobar", || Ok(())).with_ignored_flag(true),
+        ]),
+    ]
+}
+
+#[test]
+fn two_sections() {
+    let (c, out) = run_with_logfile(
+        args(["--test-threads", "1", "--skip", "baz"]),
+        |args| run_sections(args, sections()),
+    );
+    assert_eq!(c, conclusion! {
+        num_filtered_out: 1,
+        num_passed: 1,
+        num_failed: 1,
+        num_ignored: 1,
+        num_measured: 0,
+    });
+    assert_log!(out, "
+             Running plugin-a
+
+        running 2 tests
+        test foo ... ok
+        test bar ... FAILED
+
+        failures:
+
+        ---- bar ----
+        broken
+
+
+        failures:
+            bar
+
+        test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+
+
+             Running plugin-b
+
+        running 1 test
+        test foobar ... ignored
+
+        test result: ok. 0 passed; 0 failed; 1 ignored; 0 measured; 1 filtered out; \
+            finished in 0.00s
+
+
+        total: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 1 filtered out; \
+            finished in 0.00s
+    ");
+}
@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use common::{args, run_with_logfile};
+use libtest_mimic::{run, SharedTrial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+static CALLS: AtomicU32 = AtomicU32::new(0);
+
+struct Config {
+    endpoint: &'static str,
+}
+
+fn tests() -> Vec<SharedTrial<Config>> {
+    let mut tests = (0..8)
+        .map(|i| SharedTrial::test(format!("call_{i}"), |config: &Config| {
+            assert!(!config.endpoint.is_empty());
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }))
+        .collect::<Vec<_>>();
+    tests.push(SharedTrial::test("endpoint", |config: &Config| {
+        if config.endpoint == "localhost" {
+            Err(format!("unexpected endpoint '{}'", config.endpoint).into())
+        } else {
+            Ok(())
+        }
+    }).with_kind("net"));
+    tests.push(SharedTrial::test("skipped", |_: &Config| Ok(())).with_ignored_flag(true));
+    tests
+}
+
+#[test]
+fn shared_context() {
+    let config = Config { endpoint: "localhost" };
+    let (c, out) = run_with_logfile(args(["--test-threads", "4"]), |args| {
+        run(args, SharedTrial::into_trials(tests(), config))
+    });
+    assert_eq!(c, conclusion! {
+        num_passed: 8,
+        num_failed: 1,
+        num_ignored: 1,
+    });
+    assert!(out.contains("---- endpoint ----\nunexpected endpoint 'localhost'\n"));
+    assert!(out.contains("test [net] endpoint ... FAILED\n"));
+    assert_eq!(CALLS.load(Ordering::SeqCst), 8);
+}
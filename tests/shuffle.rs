@@ -0,0 +1,53 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    (0..20).map(|i| Trial::test(format!("test_{i:02}"), || Ok(()))).collect()
+}
+
+fn test_lines(out: &str) -> Vec<&str> {
+    out.lines().filter(|l| l.contains(" ... ")).collect()
+}
+
+#[test]
+fn same_seed_same_order() {
+    let (_, out_a) = do_run(args(["--shuffle", "--seed", "1234", "--test-threads", "1"]), tests());
+    let (_, out_b) = do_run(args(["--shuffle", "--seed", "1234", "--test-threads", "1"]), tests());
+    assert!(out_a.contains("seed: 1234 (reproduce with `--seed 1234`)"));
+    assert_eq!(test_lines(&out_a), test_lines(&out_b));
+
+    let (_, unshuffled) = do_run(args(["--test-threads", "1"]), tests());
+    assert!(!unshuffled.contains("seed:"));
+    assert_ne!(test_lines(&out_a), test_lines(&unshuffled));
+}
+
+#[test]
+fn seed_is_printed_without_explicit_seed() {
+    let (c, out) = do_run(args(["--shuffle"]), tests());
+    assert_eq!(c.num_passed, 20);
+    assert!(out.contains("seed: "));
+}
+
+#[test]
+fn shuffle_seed_reproduces_order() {
+    let (_, out_a) = do_run(args(["--shuffle", "--test-threads", "1"]), tests());
+    let line = out_a.lines().find(|l| l.starts_with("-- Test order shuffled with seed ")).unwrap();
+    let seed = line.trim_start_matches("-- Test order shuffled with seed ").trim_end_matches(" --");
+
+    let (_, out_b) = do_run(args(["--shuffle-seed", seed, "--test-threads", "1"]), tests());
+    assert!(out_b.contains(line), "{out_b}");
+    assert_eq!(test_lines(&out_a), test_lines(&out_b));
+
+    // Same as `--shuffle --seed`.
+    let (_, out_c) = do_run(args(["--shuffle", "--seed", seed, "--test-threads", "1"]), tests());
+    assert_eq!(test_lines(&out_a), test_lines(&out_c));
+
+    let (_, other) = do_run(args(["--shuffle-seed", "1", "--test-threads", "1"]), tests());
+    assert!(other.contains("-- Test order shuffled with seed 1 --\n"), "{other}");
+    assert!(!other.contains("seed: "), "{other}");
+    assert_ne!(test_lines(&other), test_lines(&out_b));
+}
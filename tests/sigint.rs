@@ -0,0 +1,75 @@
+#![cfg(all(unix, feature = "sigint"))]
+
+use std::{os::unix::process::ExitStatusExt, process::Command, thread, time::Duration};
+
+use common::{args, do_run_with_config, in_subprocess, run_in_subprocess};
+use libtest_mimic::{RunConfig, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+const SIGINT: i32 = 2;
+
+/// Sends `SIGINT` to the current process, like pressing Ctrl-C, and waits a
+/// bit until it arrived.
+fn press_ctrl_c() {
+    let status = Command::new("kill")
+        .args(["-INT", &std::process::id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    thread::sleep(Duration::from_millis(200));
+}
+
+fn config() -> RunConfig {
+    RunConfig::default().with_handle_sigint(true)
+}
+
+#[test]
+fn interrupts_run() {
+    if in_subprocess() {
+        let tests = vec![
+            Trial::test("presses_ctrl_c", || {
+                press_ctrl_c();
+                Ok(())
+            }),
+            Trial::test("second", || Ok(())),
+        ];
+        let (c, out) = do_run_with_config(args(["--test-threads", "1"]), tests, config());
+        assert_eq!(c, conclusion! {
+            num_passed: 1,
+            num_not_run: 1,
+            not_run: vec!["second".into()],
+            interrupted: true,
+        });
+        assert!(out.contains("run interrupted: 1 test not run"), "{out}");
+        eprintln!("run finished");
+
+        // After the run, `SIGINT` terminates the process again.
+        press_ctrl_c();
+        unreachable!("not terminated by SIGINT");
+    }
+
+    let output = run_in_subprocess("interrupts_run");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.signal(), Some(SIGINT), "{stderr}");
+    assert!(stderr.contains("run finished"), "{stderr}");
+}
+
+#[test]
+fn second_sigint_terminates() {
+    if in_subprocess() {
+        let tests = vec![Trial::test("presses_ctrl_c_twice", || {
+            press_ctrl_c();
+            press_ctrl_c();
+            Ok(())
+        })];
+        let _ = do_run_with_config(args(["--test-threads", "1"]), tests, config());
+        unreachable!("not terminated by SIGINT");
+    }
+
+    let output = run_in_subprocess("second_sigint_terminates");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.signal(), Some(SIGINT), "{stderr}");
+}
@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests(version: u64) -> Vec<Trial> {
+    vec![
+        Trial::test("unchanged", || Ok(())).with_fingerprint(1),
+        Trial::test("changed", || Ok(())).with_fingerprint(version),
+        Trial::test("failing", || Err("nope".into())).with_fingerprint(2),
+        Trial::test("no_fingerprint", || Ok(())),
+    ]
+}
+
+#[test]
+fn skip_unchanged() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("libtest_mimic_cache.txt");
+    let _ = std::fs::remove_file(&path);
+    let path_str = path.display().to_string();
+    let args = args(["--skip-unchanged", &path_str, "--test-threads", "1"]);
+
+    // Without a cache file, everything runs.
+    let (c, _) = do_run(args.clone(), tests(10));
+    assert_eq!(c, conclusion! {
+        num_passed: 3,
+        num_failed: 1,
+    });
+
+    // Only passed tests with a fingerprint are skipped the second time.
+    let (c, out) = do_run(args.clone(), tests(11));
+    assert_eq!(c, conclusion! {
+        num_passed: 2,
+        num_failed: 1,
+        num_cached: 1,
+    });
+    assert_log!(out, "
+        running 4 tests
+        test unchanged      ... cached
+        test changed        ... ok
+        test failing        ... FAILED
+        test no_fingerprint ... ok
+
+        failures:
+
+        ---- failing ----
+        nope
+
+
+        failures:
+            failing
+
+        test result: FAILED. 2 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; \
+            1 cached; finished in 0.00s
+    ");
+
+    // The new fingerprint of `changed` was stored as well. In JSON, cached
+    // tests are reported as ignored.
+    let mut args = args;
+    args.format = Some(libtest_mimic::FormatSetting::Json);
+    let (c, out) = do_run(args, tests(11));
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(c.num_cached, 2);
+    let event = r#"{ "type": "test", "name": "unchanged", "event": "ignored", "cached": true }"#;
+    assert!(out.lines().any(|line| line == event), "{out}");
+}
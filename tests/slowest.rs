@@ -0,0 +1,39 @@
+use std::{thread, time::Duration};
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    let sleeping = |ms| move || {
+        thread::sleep(Duration::from_millis(ms));
+        Ok(())
+    };
+    vec![
+        Trial::test("fast", sleeping(0)),
+        Trial::test("slow", sleeping(120)),
+        Trial::test("medium", sleeping(60)),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+#[test]
+fn slowest() {
+    let (_, out) = do_run(args(["--slowest", "2"]), tests());
+    let listed = out
+        .split("slowest tests:\n").nth(1).unwrap()
+        .lines()
+        .take_while(|l| !l.is_empty())
+        .map(|l| l.split_whitespace().nth(1).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(listed, ["slow", "medium"]);
+}
+
+#[test]
+fn not_listed_by_default() {
+    let (_, out) = do_run(args([]), tests());
+    assert!(!out.contains("slowest tests:"));
+}
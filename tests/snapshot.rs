@@ -0,0 +1,49 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn hint_after_failed_snapshot_tests() {
+    let tests = vec![
+        Trial::test("plain_fails", || Err("nope".into())),
+        Trial::test("snap_passes", || Ok(())).with_snapshot_flag(true),
+        Trial::test("snap_fails", || Err("snapshot mismatch".into())).with_snapshot_flag(true),
+    ];
+    let (_, out) = do_run(args(["--test-threads", "1"]), tests);
+    assert_log!(out, "
+        running 3 tests
+        test plain_fails ... FAILED
+        test snap_passes ... ok
+        test snap_fails  ... FAILED
+
+        failures:
+
+        ---- plain_fails ----
+        nope
+
+        ---- snap_fails ----
+        snapshot mismatch
+
+
+        failures:
+            plain_fails
+            snap_fails
+
+        note: 1 snapshot test failed, review pending snapshots with `cargo insta review`
+
+        test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn no_hint_without_snapshot_failures() {
+    let tests = vec![
+        Trial::test("plain_fails", || Err("nope".into())),
+        Trial::test("snap_passes", || Ok(())).with_snapshot_flag(true),
+    ];
+    let (_, out) = do_run(args([]), tests);
+    assert!(!out.contains("cargo insta review"));
+}
@@ -0,0 +1,99 @@
+use common::{args, do_run};
+use libtest_mimic::{Trial, Conclusion};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("foo", || Ok(())),
+        Trial::test("bar", || Err("boom".into())),
+        Trial::test("baz", || Ok(())).with_ignored_flag(true),
+        Trial::test("qux", || Ok(())),
+    ]
+}
+
+fn summary_line(detail: &str) -> (Conclusion, String) {
+    let (c, out) = do_run(args(["--summary-detail", detail, "--skip", "qux"]), tests());
+    let line = out.lines().find(|l| l.starts_with("test result:")).unwrap().to_owned();
+    (c, line)
+}
+
+#[test]
+fn minimal() {
+    let (c, line) = summary_line("minimal");
+    assert_eq!(c, conclusion! {
+        num_filtered_out: 1,
+        num_passed: 1,
+        num_failed: 1,
+        num_ignored: 1,
+        num_measured: 0,
+    });
+    assert_log!(line, "test result: FAILED. 1 passed; 1 failed; finished in 0.00s");
+}
+
+#[test]
+fn normal() {
+    let (_, line) = summary_line("normal");
+    assert_log!(line, "
+        test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 1 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn normal_is_default() {
+    let (_, out) = do_run(args(["--skip", "qux"]), tests());
+    let (_, line) = summary_line("normal");
+    assert!(out.contains(&line[..line.rfind("finished in").unwrap()]));
+}
+
+#[test]
+fn full() {
+    let (_, line) = summary_line("full");
+    assert_log!(line, "
+        test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 ignored (failed); 0 measured; \
+            1 filtered out; 0 cached; 0 not run; 50.0% passed; finished in 0.00s
+    ");
+}
+
+#[test]
+fn full_with_not_run() {
+    let (c, out) = do_run(
+        args(["--summary-detail", "full", "--max-failures", "1", "--test-threads", "1"]),
+        tests(),
+    );
+    assert_eq!(c.num_not_run, 2);
+    let line = out.lines().find(|l| l.starts_with("test result:")).unwrap();
+    assert_log!(line, "
+        test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 ignored (failed); 0 measured; \
+            0 filtered out; 0 cached; 2 not run; 50.0% passed; finished in 0.00s
+    ");
+}
+
+#[test]
+fn pass_rate() {
+    let rate = |num_passed, num_failed| {
+        conclusion! { num_passed, num_failed, num_ignored: 5 }.pass_rate()
+    };
+    assert_eq!(rate(0, 0), None);
+    assert_eq!(rate(3, 0), Some(100.0));
+    assert_eq!(rate(0, 2), Some(0.0));
+    assert_eq!(rate(197, 3), Some(98.5));
+
+    let tests = (0..3)
+        .map(|i| {
+            Trial::test(format!("t{i}"), move || if i == 0 { Err("no".into()) } else { Ok(()) })
+        })
+        .collect();
+    let (_, out) = do_run(args(["--summary-detail", "full"]), tests);
+    assert!(out.contains("; 66.7% passed; finished in"), "{out}");
+
+    // Without any tests that passed or failed, no percentage is printed.
+    let tests = vec![Trial::test("ignored", || Ok(())).with_ignored_flag(true)];
+    let (_, out) = do_run(args(["--summary-detail", "full"]), tests);
+    assert!(out.contains("0 not run; finished in"), "{out}");
+    let (_, out) = do_run(args(["--summary-detail", "full"]), vec![]);
+    assert!(out.contains("0 not run; finished in"), "{out}");
+}
@@ -0,0 +1,95 @@
+use common::{args, do_run};
+use libtest_mimic::{Measurement, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("parser::parses_ints", || Ok(())),
+        Trial::test("multi_line", || Err("expected 1\nbut got 2".into())),
+        Trial::test("no_message", || Err(libtest_mimic::Failed::without_message())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+        Trial::test("issue #3", || Ok(())).with_kind("apple"),
+        Trial::bench("bench", |test_mode| {
+            let measurement = Measurement { avg: 1200, variance: 30, ..Measurement::default() };
+            Ok((!test_mode).then_some(measurement))
+        }),
+    ]
+}
+
+#[test]
+fn tap() {
+    let (c, out) = do_run(args(["--format", "tap", "--test-threads", "1"]), tests());
+    assert_eq!(c, conclusion! {
+        num_passed: 3,
+        num_failed: 2,
+        num_ignored: 1,
+    });
+    assert_eq!(out, concat!(
+        "1..6\n",
+        "ok 1 - parser::parses_ints\n",
+        "not ok 2 - multi_line\n",
+        "  ---\n",
+        "  message: |\n",
+        "    expected 1\n",
+        "    but got 2\n",
+        "  ...\n",
+        "not ok 3 - no_message\n",
+        "ok 4 - ignored # SKIP\n",
+        "ok 5 - [apple] issue \\#3\n",
+        "ok 6 - bench\n",
+        "# 3 passed; 2 failed; 1 ignored; 0 measured; 0 filtered out\n",
+    ));
+}
+
+#[test]
+fn benches_and_filtered() {
+    let (_, out) = do_run(args(["--format", "tap", "--bench", "bench"]), tests());
+    assert_eq!(out, concat!(
+        "1..1\n",
+        "ok 1 - bench # bench: 1,200 ns/iter (+/- 30)\n",
+        "# 0 passed; 0 failed; 0 ignored; 1 measured; 5 filtered out\n",
+    ));
+}
+
+#[test]
+fn numbered_in_finish_order_in_parallel() {
+    let (_, out) = do_run(args(["--format", "tap", "--test-threads", "4"]), tests());
+    let numbers = out.lines()
+        .filter_map(|line| line.strip_prefix("ok ").or_else(|| line.strip_prefix("not ok ")))
+        .map(|rest| rest.split(' ').next().unwrap().parse::<u32>().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, [1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn soft_failures_and_abort() {
+    let tests = vec![
+        Trial::test("flaky", || Err("boom".into())).with_ignored_flag(true),
+        Trial::test("fails", || Err("nope".into())),
+        Trial::test("not_run", || Ok(())),
+    ];
+    let (_, out) = do_run(
+        args([
+            "--format", "tap", "--run-ignored-soft", "--max-failures", "1", "--test-threads", "1",
+        ]),
+        tests,
+    );
+    assert_eq!(out, concat!(
+        "1..3\n",
+        "not ok 1 - flaky # TODO ignored (failed)\n",
+        "  ---\n",
+        "  message: |\n",
+        "    boom\n",
+        "  ...\n",
+        "not ok 2 - fails\n",
+        "  ---\n",
+        "  message: |\n",
+        "    nope\n",
+        "  ...\n",
+        "Bail out! run aborted due to too many failures: 1 test not run\n",
+        "# 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out\n",
+    ));
+}
@@ -0,0 +1,82 @@
+use std::{env, fs, path::PathBuf, sync::Mutex};
+
+use common::{args, do_run};
+use libtest_mimic::{try_run, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+static DIRS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+fn tests() -> Vec<Trial> {
+    let create_file = || {
+        assert!(fs::metadata("data.txt").is_err(), "directory not fresh");
+        fs::write("data.txt", "hello").unwrap();
+        DIRS.lock().unwrap().push(env::current_dir().unwrap());
+    };
+    vec![
+        Trial::test("writes", move || {
+            create_file();
+            Ok(())
+        }).with_temp_cwd(true),
+        Trial::test("writes_again", move || {
+            create_file();
+            Ok(())
+        }).with_temp_cwd(true),
+        Trial::test("panics", move || {
+            create_file();
+            panic!("oh no");
+        }).with_temp_cwd(true),
+        Trial::test("plain", || {
+            DIRS.lock().unwrap().push(env::current_dir().unwrap());
+            Ok(())
+        }),
+    ]
+}
+
+// All checks are in one test as the working directory is process-global.
+#[test]
+fn fresh_dirs_are_removed() {
+    let original = env::current_dir().unwrap();
+    for threads in ["1", "4"] {
+        DIRS.lock().unwrap().clear();
+        let (c, out) = do_run(args(["--test-threads", threads]), tests());
+        assert_eq!(c, conclusion! { num_passed: 3, num_failed: 1 });
+        assert!(out.contains("---- panics ----\ntest panicked: oh no\n"), "{out}");
+        assert_eq!(env::current_dir().unwrap(), original);
+
+        let dirs = DIRS.lock().unwrap().clone();
+        assert_eq!(dirs.len(), 4);
+        assert_eq!(dirs[3], original);
+        for dir in &dirs[..3] {
+            assert_ne!(dir, &original);
+            assert!(!dir.exists(), "{} not removed", dir.display());
+        }
+        assert_ne!(dirs[0], dirs[1]);
+    }
+}
+
+/// A timed out test would keep running after its directory was removed.
+#[test]
+fn timeout_rejected() {
+    let tests = vec![
+        Trial::test("isolated", || panic!("must not run")).with_temp_cwd(true),
+        Trial::test("plain", || panic!("must not run")),
+    ];
+    let e = try_run(&args(["--timeout", "1"]), tests).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(
+        e.to_string(),
+        "`--timeout` cannot be used with tests that run in a temporary working directory \
+            (see `Trial::with_temp_cwd`), like `isolated`",
+    );
+
+    // Without selecting the test, `--timeout` can still be used.
+    let tests = vec![
+        Trial::test("isolated", || panic!("must not run")).with_temp_cwd(true),
+        Trial::test("plain", || Ok(())),
+    ];
+    let (c, _) = do_run(args(["--timeout", "1", "plain"]), tests);
+    assert_eq!(c, conclusion! { num_passed: 1, num_filtered_out: 1 });
+}
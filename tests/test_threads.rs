@@ -0,0 +1,94 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use common::{args, do_run, in_subprocess, subprocess_command};
+use libtest_mimic::{Arguments, Trial};
+
+#[macro_use]
+mod common;
+
+/// Returns the number of distinct threads the tests ran on and whether one of
+/// them was the main thread.
+fn threads_used(args: Arguments) -> (usize, bool) {
+    let threads = Arc::new(Mutex::new(HashSet::new()));
+    let tests = (0..40)
+        .map(|i| {
+            let threads = Arc::clone(&threads);
+            Trial::test(format!("t{i:02}"), move || {
+                threads.lock().unwrap().insert(thread::current().id());
+                thread::sleep(std::time::Duration::from_millis(5));
+                Ok(())
+            })
+        })
+        .collect();
+    let _ = do_run(args, tests);
+    let threads = threads.lock().unwrap();
+    (threads.len(), threads.contains(&thread::current().id()))
+}
+
+/// Runs `test_name` in a subprocess with `RUST_TEST_THREADS` set to `value`
+/// and returns its stderr.
+fn run_with_env(test_name: &str, value: &str) -> String {
+    let output = subprocess_command(test_name).env("RUST_TEST_THREADS", value).output().unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(output.status.success(), "{stderr}");
+    stderr
+}
+
+#[test]
+fn flag() {
+    let (num_threads, on_main) = threads_used(args(["--test-threads", "3"]));
+    assert!(num_threads <= 3 && !on_main);
+    assert_eq!(threads_used(args(["--test-threads", "1"])), (1, true));
+}
+
+#[test]
+fn env_one_runs_on_main_thread() {
+    if in_subprocess() {
+        assert_eq!(threads_used(args([])), (1, true));
+        return;
+    }
+    run_with_env("env_one_runs_on_main_thread", "1");
+}
+
+#[test]
+fn env_used_without_flag() {
+    if in_subprocess() {
+        let (num_threads, on_main) = threads_used(args([]));
+        assert!(num_threads <= 2 && !on_main, "{num_threads}");
+        let (_, out) = do_run(args(["--print-config"]), vec![]);
+        assert!(out.contains("\ntest threads:  2 (from RUST_TEST_THREADS)\n"), "{out}");
+        return;
+    }
+    run_with_env("env_used_without_flag", "2");
+}
+
+#[test]
+fn flag_takes_precedence_over_env() {
+    if in_subprocess() {
+        assert_eq!(threads_used(args(["--test-threads", "1"])), (1, true));
+        let (_, out) = do_run(args(["--print-config", "--test-threads", "4"]), vec![]);
+        assert!(out.contains("\ntest threads:  4\n"), "{out}");
+        return;
+    }
+    run_with_env("flag_takes_precedence_over_env", "3");
+}
+
+#[test]
+fn invalid_env() {
+    if in_subprocess() {
+        let _ = do_run(args([]), vec![Trial::test("a", || Ok(()))]);
+        unreachable!();
+    }
+    let output = subprocess_command("invalid_env")
+        .env("RUST_TEST_THREADS", "many")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let expected = "error: RUST_TEST_THREADS is `many`, should be a positive integer";
+    assert!(stderr.contains(expected), "{stderr}");
+}
@@ -0,0 +1,89 @@
+use common::{args, do_run, do_run_with_config};
+use libtest_mimic::{Color, Outcome, RunConfig, Theme, Trial};
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("fails", || Err("nope".into())),
+    ]
+}
+
+fn colored_failure() -> Vec<Trial> {
+    let msg = "\u{1b}[31m- left\u{1b}[0m\n\u{1b}[32m+ right\u{1b}[0m";
+    vec![Trial::test("diff", || Err(msg.into()))]
+}
+
+#[test]
+fn message_colors_passed_through() {
+    let (_, out) = do_run(args(["--color", "always"]), colored_failure());
+    assert!(out.contains("\u{1b}[31m- left\u{1b}[0m\n\u{1b}[32m+ right\u{1b}[0m"));
+}
+
+#[test]
+fn message_colors_stripped() {
+    let (_, out) = do_run(args(["--color", "never"]), colored_failure());
+    assert!(out.contains("\n- left\n+ right\n"));
+    assert!(!out.contains('\u{1b}'));
+}
+
+#[test]
+fn default_theme() {
+    let (_, out) = do_run_with_config(args(["--color", "always"]), tests(), RunConfig::default());
+    assert!(out.contains("\u{1b}[32mok"));
+    assert!(out.contains("\u{1b}[31mFAILED"));
+}
+
+#[test]
+fn custom_theme() {
+    let theme = Theme {
+        pass: Color::Magenta,
+        fail: Color::Blue,
+        ..Theme::default()
+    };
+    let config = RunConfig::default().with_theme(theme);
+    let (_, out) = do_run_with_config(args(["--color", "always"]), tests(), config);
+    assert!(out.contains("\u{1b}[35mok"));
+    assert!(out.contains("\u{1b}[34mFAILED"));
+    assert!(!out.contains("\u{1b}[32m"));
+}
+
+fn emoji_config() -> RunConfig {
+    RunConfig::default().with_status_text(|outcome| match outcome {
+        Outcome::Passed => "✅ ok".into(),
+        Outcome::Failed(_) => "❌ FAILED".into(),
+        _ => "🤷".into(),
+    })
+}
+
+#[test]
+fn custom_status_text() {
+    let args = args(["--color", "never", "--test-threads", "1"]);
+    let (_, out) = do_run_with_config(args, tests(), emoji_config());
+    assert_log!(out, "
+        running 2 tests
+        test passes ... ✅ ok
+        test fails  ... ❌ FAILED
+
+        failures:
+
+        ---- fails ----
+        nope
+
+
+        failures:
+            fails
+
+        test result: ❌ FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn custom_status_text_colored() {
+    let (_, out) = do_run_with_config(args(["--color", "always"]), tests(), emoji_config());
+    assert!(out.contains("\u{1b}[32m✅ ok\u{1b}[0m"), "{out:?}");
+    assert!(out.contains("\u{1b}[31m❌ FAILED\u{1b}[0m"), "{out:?}");
+}
@@ -0,0 +1,73 @@
+use std::{
+    cell::Cell,
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    thread::{self, ThreadId},
+};
+
+use common::{args, do_run_with_config};
+use libtest_mimic::{RunConfig, Trial};
+
+#[macro_use]
+mod common;
+
+thread_local! {
+    static INITIALIZED: Cell<bool> = const { Cell::new(false) };
+}
+
+#[derive(Default)]
+struct Calls {
+    init: Mutex<Vec<ThreadId>>,
+    teardown: Mutex<Vec<ThreadId>>,
+}
+
+fn config(calls: &Arc<Calls>) -> RunConfig {
+    let init_calls = Arc::clone(calls);
+    let teardown_calls = Arc::clone(calls);
+    RunConfig::default()
+        .with_thread_init(move || {
+            INITIALIZED.with(|i| i.set(true));
+            init_calls.init.lock().unwrap().push(thread::current().id());
+        })
+        .with_thread_teardown(move || {
+            INITIALIZED.with(|i| i.set(false));
+            teardown_calls.teardown.lock().unwrap().push(thread::current().id());
+        })
+}
+
+fn tests() -> Vec<Trial> {
+    (0..20)
+        .map(|i| Trial::test(format!("test_{i:02}"), || {
+            if INITIALIZED.with(Cell::get) {
+                Ok(())
+            } else {
+                Err("thread was not initialized".into())
+            }
+        }))
+        .collect()
+}
+
+#[test]
+fn once_per_worker() {
+    let calls = Arc::new(Calls::default());
+    let (c, _) = do_run_with_config(args(["--test-threads", "4"]), tests(), config(&calls));
+    assert_eq!(c.num_passed, 20);
+
+    let init = calls.init.lock().unwrap().clone();
+    let teardown = calls.teardown.lock().unwrap().clone();
+    assert_eq!(init.len(), 4);
+    assert_eq!(init.iter().collect::<HashSet<_>>().len(), 4);
+    assert!(!init.contains(&thread::current().id()));
+    assert_eq!(init.iter().collect::<HashSet<_>>(), teardown.iter().collect::<HashSet<_>>());
+    assert_eq!(teardown.len(), 4);
+}
+
+#[test]
+fn sequential_on_main_thread() {
+    let calls = Arc::new(Calls::default());
+    let (c, _) = do_run_with_config(args(["--test-threads", "1"]), tests(), config(&calls));
+    assert_eq!(c.num_passed, 20);
+    assert_eq!(*calls.init.lock().unwrap(), [thread::current().id()]);
+    assert_eq!(*calls.teardown.lock().unwrap(), [thread::current().id()]);
+    assert!(!INITIALIZED.with(Cell::get));
+}
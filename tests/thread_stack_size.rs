@@ -0,0 +1,40 @@
+use std::hint::black_box;
+
+use common::{args, do_run, do_run_with_config};
+use libtest_mimic::{RunConfig, Trial};
+
+#[macro_use]
+mod common;
+
+/// Uses roughly 8 MiB of stack, more than the default of 2 MiB.
+fn recurse(depth: usize) -> u64 {
+    let buf = black_box([depth as u8; 4096]);
+    if depth == 0 {
+        0
+    } else {
+        recurse(depth - 1) + u64::from(buf[depth % buf.len()])
+    }
+}
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("deep", || {
+            black_box(recurse(2000));
+            Ok(())
+        }),
+        Trial::test("shallow", || Ok(())),
+    ]
+}
+
+#[test]
+fn deep_recursion_with_large_stack() {
+    let (c, _) = do_run(args(["--test-threads", "2", "--thread-stack-size", "67108864"]), tests());
+    assert_eq!(c.num_passed, 2);
+}
+
+#[test]
+fn config_stack_size() {
+    let config = RunConfig::default().with_thread_stack_size(64 * 1024 * 1024);
+    let (c, _) = do_run_with_config(args(["--test-threads", "2"]), tests(), config);
+    assert_eq!(c.num_passed, 2);
+}
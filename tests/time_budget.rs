@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use common::{args, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    let ms = Duration::from_millis;
+    vec![
+        Trial::test("unknown", || Ok(())),
+        Trial::test("huge", || Ok(())).with_estimated_duration(ms(3_600_000)),
+        Trial::test("medium", || Ok(())).with_estimated_duration(ms(20)),
+        Trial::test("small", || Ok(())).with_estimated_duration(ms(10)),
+    ]
+}
+
+#[test]
+fn runs_what_fits() {
+    let (c, out) = do_run(args(["--time-budget", "60", "--test-threads", "1"]), tests());
+    assert_eq!(c, conclusion! {
+        num_passed: 3,
+        num_not_run: 1,
+        not_run: vec!["huge".into()],
+    });
+    assert_log!(out, "
+        running 4 tests
+        test small   ... ok
+        test medium  ... ok
+        test unknown ... ok
+
+        ran 3 of 4 tests within the time budget of 60s
+
+        test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn always_run_ignores_budget() {
+    let tests = tests().into_iter()
+        .map(|t| if t.name() == "huge" { t.with_always_run(true) } else { t })
+        .collect();
+    let (c, out) = do_run(args(["--time-budget", "0"]), tests);
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(c.not_run.len(), 3);
+    assert!(out.contains("ran 1 of 4 tests within the time budget of 0s"));
+    assert!(!out.contains("run aborted"));
+}
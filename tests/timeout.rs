@@ -0,0 +1,72 @@
+use std::{thread, time::Duration};
+
+use common::{args, check, do_run};
+use libtest_mimic::Trial;
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("fast", || Ok(())),
+        Trial::test("slow", || {
+            thread::sleep(Duration::from_secs(2));
+            Ok(())
+        }),
+        Trial::test("fails", || Err("boom".into())),
+    ]
+}
+
+#[test]
+fn slow_test_times_out() {
+    check(args(["--timeout", "0.1"]), tests, 3,
+        conclusion! {
+            num_passed: 1,
+            num_failed: 2,
+        },
+        "
+            test fast  ... ok
+            test slow  ... FAILED
+            test fails ... FAILED
+
+            failures:
+
+            ---- slow ----
+            test exceeded timeout of 0.1s
+
+            ---- fails ----
+            boom
+
+
+            failures:
+                slow
+                fails
+        ",
+    );
+}
+
+#[test]
+fn without_timeout() {
+    let tests = || vec![
+        Trial::test("napping", || {
+            thread::sleep(Duration::from_millis(50));
+            Ok(())
+        }),
+    ];
+    let (c, _) = do_run(args(["--timeout", "10"]), tests());
+    assert_eq!(c, conclusion! { num_passed: 1 });
+    let (c, _) = do_run(args([]), tests());
+    assert_eq!(c, conclusion! { num_passed: 1 });
+}
+
+#[test]
+fn json_reports_timeout() {
+    let (_, out) = do_run(args(["--timeout", "0.1", "--format", "json", "slow"]), tests());
+    assert!(out.contains(
+        concat!(
+            r#"{ "type": "test", "name": "slow", "event": "failed","#,
+            r#" "stdout": "Error: \"test exceeded timeout of 0.1s\"\n" }"#,
+        )
+    ), "{out}");
+}
@@ -0,0 +1,53 @@
+use common::{args, do_run};
+use libtest_mimic::Trial;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("passes", || Ok(())),
+        Trial::test("ignored", || Ok(())).with_ignored_flag(true),
+    ]
+}
+
+/// Checks that `s` looks like `2024-05-10T13:37:00.123Z`.
+fn is_iso8601(s: &str) -> bool {
+    s.len() == 24
+        && s.chars().enumerate().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            10 => c == 'T',
+            13 | 16 => c == ':',
+            19 => c == '.',
+            23 => c == 'Z',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+#[test]
+fn pretty() {
+    let (_, out) = do_run(args(["--timestamps", "--test-threads", "1"]), tests());
+    let line = out.lines().find(|l| l.starts_with("test passes")).unwrap();
+    let (start, end) = line
+        .strip_prefix("test passes  ... ok [").unwrap()
+        .strip_suffix(']').unwrap()
+        .split_once(" .. ").unwrap();
+    assert!(is_iso8601(start) && is_iso8601(end), "{line}");
+    assert!(start <= end);
+
+    // Ignored tests are not run, so they don't have timestamps.
+    assert!(out.contains("test ignored ... ignored\n"));
+}
+
+#[test]
+fn json() {
+    let (_, out) = do_run(args(["--timestamps", "--format", "json"]), tests());
+    let line = out.lines().find(|l| l.contains(r#""event": "ok""#)).unwrap();
+    let start = line.split(r#""start_time": ""#).nth(1).unwrap();
+    assert!(is_iso8601(&start[..24]), "{line}");
+    let end = line.split(r#""end_time": ""#).nth(1).unwrap();
+    assert!(is_iso8601(&end[..24]), "{line}");
+
+    let line = out.lines().find(|l| l.contains(r#""event": "ignored""#)).unwrap();
+    assert!(!line.contains("start_time"));
+}
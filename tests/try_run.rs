@@ -0,0 +1,81 @@
+use std::{path::Path, sync::{atomic::AtomicBool, Arc}};
+
+use common::{args, run_with_logfile};
+use libtest_mimic::{try_run, try_run_with_config, Conclusion, RunConfig, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+#[test]
+fn ok() {
+    let (c, out) = run_with_logfile(args([]), |args| {
+        let tests = vec![Trial::test("foo", || Ok(())), Trial::test("bar", || Err("nope".into()))];
+        try_run(args, tests).unwrap()
+    });
+    assert_eq!(c, conclusion! { num_passed: 1, num_failed: 1 });
+    assert!(out.contains("test foo ... ok"), "{out}");
+}
+
+#[test]
+fn with_config_interrupt_flag() {
+    let config = RunConfig::default().with_interrupt_flag(Arc::new(AtomicBool::new(true)));
+    let (c, _) = run_with_logfile(args(["--test-threads", "1"]), |args| {
+        try_run_with_config(args, vec![Trial::test("foo", || panic!("must not run"))], config)
+            .unwrap()
+    });
+    assert_eq!(c, conclusion! {
+        num_not_run: 1,
+        not_run: vec!["foo".into()],
+        interrupted: true,
+    });
+}
+
+#[test]
+fn logfile_cannot_be_created() {
+    let logfile = Path::new(env!("CARGO_TARGET_TMPDIR")).join("missing_dir").join("log.txt");
+    let tests = vec![Trial::test("foo", || panic!("must not run"))];
+    let e = try_run(&args(["--logfile", logfile.to_str().unwrap()]), tests).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::NotFound);
+    assert!(e.to_string().starts_with("failed to create logfile"), "{e}");
+}
+
+/// Writes to `/dev/full` fail with `ENOSPC`, like writes to a closed pipe
+/// fail with `EPIPE`.
+#[cfg(target_os = "linux")]
+#[test]
+fn write_error() {
+    let tests = (0..10).map(|i| Trial::test(format!("t{i}"), || Ok(()))).collect();
+    let e = try_run(&args(["--logfile", "/dev/full", "--test-threads", "1"]), tests).unwrap_err();
+    assert_eq!(e.raw_os_error(), Some(28), "{e}");
+}
+
+#[test]
+fn duplicate_names() {
+    let tests = vec![Trial::test("foo", || Ok(())), Trial::test("foo", || Ok(()))];
+    let e = try_run(&args([]), tests).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(e.to_string(), "duplicate test names: foo");
+}
+
+#[test]
+fn expect_tests_mismatch() {
+    let tests = vec![Trial::test("foo", || panic!("must not run"))];
+    let e = try_run(&args(["--expect-tests", "2"]), tests).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(e.to_string(), "expected 2 tests (`--expect-tests`), but 1 were defined");
+}
+
+#[test]
+fn results_file_cannot_be_written() {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("missing_dir").join("results.txt");
+    let mut error = None;
+    let (_, out) = run_with_logfile(args(["--results-file", path.to_str().unwrap()]), |args| {
+        error = try_run(args, vec![Trial::test("foo", || Ok(()))]).err();
+        Conclusion::default()
+    });
+    let e = error.expect("no error");
+    assert_eq!(e.kind(), std::io::ErrorKind::NotFound);
+    assert!(e.to_string().starts_with("failed to write results file"), "{e}");
+    assert!(out.contains("test foo ... ok"), "{out}");
+}
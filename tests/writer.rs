@@ -0,0 +1,84 @@
+use std::sync::{Arc, Mutex};
+
+use common::args;
+use libtest_mimic::{run_with_config, Arguments, Conclusion, RunConfig, Trial};
+use pretty_assertions::assert_eq;
+
+#[macro_use]
+mod common;
+
+fn tests() -> Vec<Trial> {
+    vec![
+        Trial::test("cat", || Ok(())),
+        Trial::test("dog", || Err("was not a good boy".into())),
+    ]
+}
+
+/// Runs `tests` with `RunConfig::with_writer` and returns the output.
+fn run_with_writer(args: &Arguments, tests: Vec<Trial>) -> (Conclusion, String) {
+    let out = Arc::new(Mutex::new(Vec::new()));
+    let c = run_with_config(args, tests, RunConfig::default().with_writer(Arc::clone(&out)));
+    let out = String::from_utf8(out.lock().unwrap().clone()).unwrap();
+    (c, out)
+}
+
+#[test]
+fn output_goes_to_writer() {
+    let (c, out) = run_with_writer(&args(["--test-threads", "1"]), tests());
+    assert_eq!(c.num_passed, 1);
+    assert_eq!(c.num_failed, 1);
+    assert_log!(out, "
+        running 2 tests
+        test cat ... ok
+        test dog ... FAILED
+
+        failures:
+
+        ---- dog ----
+        was not a good boy
+
+
+        failures:
+            dog
+
+        test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; \
+            finished in 0.00s
+    ");
+}
+
+#[test]
+fn colors_only_when_forced() {
+    let (_, out) = run_with_writer(&args(["--color", "always"]), tests());
+    assert!(out.contains('\x1b'));
+
+    let (_, out) = run_with_writer(&args(["--color", "auto"]), tests());
+    assert!(!out.contains('\x1b'));
+}
+
+#[test]
+fn list() {
+    let (c, out) = run_with_writer(&args(["--list"]), tests());
+    assert_eq!(c, Conclusion::default());
+    assert_eq!(out, "cat: test\ndog: test\n");
+}
+
+#[test]
+fn ansi_in_messages() {
+    let tests = || vec![Trial::test("diff", || Err("\x1b[31m- old\x1b[0m".into()))];
+
+    let (_, out) = run_with_writer(&args(["--color", "always"]), tests());
+    assert!(out.contains("\n\x1b[31m- old\x1b[0m\n"));
+
+    let (_, out) = run_with_writer(&args(["--color", "never"]), tests());
+    assert!(!out.contains('\x1b'), "{out:?}");
+    assert!(out.contains("\n- old\n"), "{out}");
+}
+
+#[test]
+fn writer_takes_precedence_over_logfile() {
+    let logfile = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("writer_precedence.txt");
+    let _ = std::fs::remove_file(&logfile);
+    let (_, out) = run_with_writer(&args(["--logfile", logfile.to_str().unwrap()]), tests());
+    assert!(out.contains("test cat ... ok"), "{out}");
+    assert!(!logfile.exists());
+}